@@ -19,6 +19,7 @@ fn main() {
 
     let tree_config = TreeConfig {
         initial_state: BoardState::Turn,
+        depth_limit: None,
         starting_pot: 200,
         effective_stack: 900,
         rake_rate: 0.0,
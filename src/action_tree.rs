@@ -1,5 +1,9 @@
 use crate::bet_size::*;
 use crate::mutex_like::*;
+use crate::range::*;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
 
 #[cfg(feature = "bincode")]
 use bincode::{Decode, Encode};
@@ -42,8 +46,77 @@ pub enum Action {
     Chance(u8),
 }
 
+/// Formats an action as a string, e.g., `"Check"`, `"Bet(50)"`, or `"Qc"` for a chance action.
+impl fmt::Display for Action {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Action::None => write!(f, "None"),
+            Action::Fold => write!(f, "Fold"),
+            Action::Check => write!(f, "Check"),
+            Action::Call => write!(f, "Call"),
+            Action::Bet(amount) => write!(f, "Bet({amount})"),
+            Action::Raise(amount) => write!(f, "Raise({amount})"),
+            Action::AllIn(amount) => write!(f, "AllIn({amount})"),
+            Action::Chance(card) => match card_to_string(card) {
+                Ok(s) => write!(f, "{s}"),
+                Err(_) => write!(f, "Chance({card})"),
+            },
+        }
+    }
+}
+
+/// Parses the string form of [`Display`](fmt::Display).
+///
+/// # Examples
+/// ```
+/// use postflop_solver::*;
+///
+/// assert_eq!("Check".parse(), Ok(Action::Check));
+/// assert_eq!("Bet(50)".parse(), Ok(Action::Bet(50)));
+/// assert_eq!("Qc".parse(), Ok(Action::Chance(card_from_str("Qc").unwrap())));
+/// assert_eq!(Action::AllIn(300).to_string(), "AllIn(300)");
+/// ```
+impl FromStr for Action {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        match s {
+            "None" => return Ok(Action::None),
+            "Fold" => return Ok(Action::Fold),
+            "Check" => return Ok(Action::Check),
+            "Call" => return Ok(Action::Call),
+            _ => {}
+        }
+
+        if let Some((name, rest)) = s.split_once('(') {
+            let arg = rest
+                .strip_suffix(')')
+                .ok_or_else(|| format!("Failed to parse action: {s}"))?;
+            let amount = || {
+                arg.parse::<i32>()
+                    .map_err(|_| format!("Failed to parse action: {s}"))
+            };
+            return match name {
+                "Bet" => Ok(Action::Bet(amount()?)),
+                "Raise" => Ok(Action::Raise(amount()?)),
+                "AllIn" => Ok(Action::AllIn(amount()?)),
+                "Chance" => match arg.parse::<u8>() {
+                    Ok(card) if card < 52 => Ok(Action::Chance(card)),
+                    _ => Err(format!("Failed to parse action: {s}")),
+                },
+                _ => Err(format!("Failed to parse action: {s}")),
+            };
+        }
+
+        card_from_str(s)
+            .map(Action::Chance)
+            .map_err(|_| format!("Failed to parse action: {s}"))
+    }
+}
+
 /// An enum representing the board state.
-#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 #[repr(u8)]
 #[cfg_attr(feature = "bincode", derive(Decode, Encode))]
 pub enum BoardState {
@@ -64,6 +137,7 @@ pub enum BoardState {
 ///
 /// let tree_config = TreeConfig {
 ///     initial_state: BoardState::Turn,
+///     depth_limit: None,
 ///     starting_pot: 200,
 ///     effective_stack: 900,
 ///     rake_rate: 0.05,
@@ -78,12 +152,25 @@ pub enum BoardState {
 ///     merging_threshold: 0.1,
 /// };
 /// ```
-#[derive(Debug, Clone, Default)]
+///
+/// With serde, omitted fields take their default values, and bet sizes are represented by
+/// strings (see [`BetSizeCandidates`]).
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
 #[cfg_attr(feature = "bincode", derive(Decode, Encode))]
 pub struct TreeConfig {
     /// Initial state of the game tree (flop, turn, or river).
     pub initial_state: BoardState,
 
+    /// Last street of the game tree (set `None` to build the tree to the river).
+    ///
+    /// When this street ends without an all-in, the tree stops there and the node becomes a leaf
+    /// whose values are given by the leaf evaluator of the game (see [`LeafEvaluator`]). All-in
+    /// lines are still dealt to the river. Bet sizes of the later streets are ignored.
+    ///
+    /// [`LeafEvaluator`]: crate::LeafEvaluator
+    pub depth_limit: Option<BoardState>,
+
     /// Starting pot size. Must be greater than `0`.
     pub starting_pot: i32,
 
@@ -445,6 +532,14 @@ impl ActionTree {
             ));
         }
 
+        if let Some(depth_limit) = config.depth_limit {
+            if depth_limit < config.initial_state {
+                return Err(format!(
+                    "Depth limit must not precede the initial state: {depth_limit:?}"
+                ));
+            }
+        }
+
         if config.add_allin_threshold < 0.0 {
             return Err(format!(
                 "Add all-in threshold must be non-negative: {}",
@@ -695,14 +790,9 @@ impl ActionTree {
         // merge bet actions with close amounts
         actions = merge_bet_actions(actions, pot, prev_amount, self.config.merging_threshold);
 
-        let player_after_call = match node.board_state {
-            BoardState::River => PLAYER_TERMINAL_FLAG,
-            _ => PLAYER_CHANCE_FLAG | player,
-        };
-
         let player_after_check = match player {
             PLAYER_OOP => opponent,
-            _ => player_after_call,
+            _ => self.player_after_street(node, node.amount),
         };
 
         // push actions
@@ -713,7 +803,7 @@ impl ActionTree {
                 Action::Check => player_after_check,
                 Action::Call => {
                     amount += to_call;
-                    player_after_call
+                    self.player_after_street(node, amount)
                 }
                 Action::Bet(_) | Action::Raise(_) | Action::AllIn(_) => {
                     amount += to_call;
@@ -735,6 +825,21 @@ impl ActionTree {
         node.children.shrink_to_fit();
     }
 
+    /// Returns the next player when the street of `node` ends with the bet amount `amount`.
+    ///
+    /// The street ends with a terminal node on the river, and also on the street of the depth
+    /// limit unless the players are all-in (in which case the remaining cards are dealt).
+    #[inline]
+    fn player_after_street(&self, node: &ActionTreeNode, amount: i32) -> u8 {
+        let is_leaf = self.config.depth_limit == Some(node.board_state)
+            && amount < self.config.effective_stack;
+        if node.board_state == BoardState::River || is_leaf {
+            PLAYER_TERMINAL_FLAG
+        } else {
+            PLAYER_CHANCE_FLAG | node.player
+        }
+    }
+
     /// Recursive function to enumerate all invalid terminal nodes.
     fn invalid_terminals_recursive(
         node: &ActionTreeNode,
@@ -858,14 +963,9 @@ impl ActionTree {
             };
         }
 
-        let player_after_call = match node.board_state {
-            BoardState::River => PLAYER_TERMINAL_FLAG,
-            _ => PLAYER_CHANCE_FLAG | player,
-        };
-
         let player_after_check = match player {
             PLAYER_OOP => opponent,
-            _ => player_after_call,
+            _ => self.player_after_street(node, node.amount),
         };
 
         let mut amount = node.amount;
@@ -874,7 +974,7 @@ impl ActionTree {
             Action::Check => player_after_check,
             Action::Call => {
                 amount += to_call;
-                player_after_call
+                self.player_after_street(node, amount)
             }
             Action::Bet(_) | Action::Raise(_) | Action::AllIn(_) => {
                 amount += to_call;
@@ -1038,12 +1138,7 @@ impl BuildTreeInfo {
 /// Returns the number of action nodes of [flop, turn, river].
 pub(crate) fn count_num_action_nodes(node: &ActionTreeNode) -> [u64; 3] {
     let mut ret = [0, 0, 0];
-    count_num_action_nodes_recursive(node, 0, &mut ret);
-    if ret[1] == 0 {
-        ret = [0, 0, ret[0]];
-    } else if ret[2] == 0 {
-        ret = [0, ret[0], ret[1]];
-    }
+    count_num_action_nodes_recursive(node, node.board_state as usize, &mut ret);
     ret
 }
 
@@ -0,0 +1,464 @@
+use crate::action_tree::*;
+use crate::card::*;
+use crate::file::*;
+use crate::game::*;
+use crate::payoff::*;
+use crate::solver::*;
+use std::any::Any;
+use std::ops::ControlFlow;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::PathBuf;
+use std::sync::{Condvar, Mutex};
+use std::thread;
+use std::time::Duration;
+
+/// A spot to be solved by [`solve_batch`].
+#[derive(Debug, Clone)]
+pub struct BatchSpot {
+    /// Name of the spot. Used in the progress reports and as the memo of the saved file.
+    pub name: String,
+
+    /// Card configuration of the spot.
+    pub card_config: CardConfig,
+
+    /// Tree configuration of the spot.
+    pub tree_config: TreeConfig,
+
+    /// Payoff model of the spot (`None` uses the default of [`PostFlopGame`]).
+    pub payoff_model: Option<PayoffModel>,
+
+    /// Path to save the solved game (`None` to discard the result).
+    pub output_path: Option<PathBuf>,
+}
+
+/// A struct containing the configuration of [`solve_batch`].
+#[derive(Debug, Clone)]
+pub struct BatchConfig {
+    /// Total memory budget in bytes shared by all spots solved concurrently.
+    pub memory_budget: u64,
+
+    /// Maximum number of spots solved concurrently. `0` is treated as `1`.
+    ///
+    /// All spots share the global thread pool, so a value larger than `1` is mainly useful for
+    /// small spots that cannot saturate the thread pool by themselves.
+    pub max_concurrency: usize,
+
+    /// Maximum number of iterations of each spot.
+    pub max_num_iterations: u32,
+
+    /// Target exploitability of each spot, relative to its starting pot (e.g., `0.005`).
+    pub target_exploitability: f32,
+
+    /// Maximum solving time of each spot (`None` for no limit).
+    pub max_duration: Option<Duration>,
+
+    /// Maximum fraction of the solving time spent on computing the exploitability (see
+    /// [`SolveOptions::max_exploitability_overhead`]).
+    pub max_exploitability_overhead: Option<f32>,
+
+    /// The variant of the CFR algorithm.
+    pub algorithm: CfrAlgorithm,
+
+    /// Whether to enable compression (`None` to decide automatically).
+    ///
+    /// In the automatic mode, compression is enabled only if the uncompressed game does not fit
+    /// in the memory budget.
+    pub enable_compression: Option<bool>,
+
+    /// zstd compression level of the saved files (`None` to save without compression).
+    pub compression_level: Option<i32>,
+}
+
+impl Default for BatchConfig {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            memory_budget: u64::MAX,
+            max_concurrency: 1,
+            max_num_iterations: 1000,
+            target_exploitability: 0.005,
+            max_duration: None,
+            max_exploitability_overhead: None,
+            algorithm: CfrAlgorithm::default(),
+            enable_compression: None,
+            compression_level: None,
+        }
+    }
+}
+
+/// A report of a successfully solved spot.
+#[derive(Debug, Clone)]
+pub struct BatchSpotReport {
+    /// Exploitability of the obtained strategy.
+    pub exploitability: f32,
+
+    /// Number of performed iterations.
+    pub num_iterations: u32,
+
+    /// The reason why solving stopped.
+    pub stop_reason: StopReason,
+
+    /// Memory usage of the spot in bytes.
+    pub memory_usage: u64,
+
+    /// Whether compression was enabled.
+    pub is_compression_enabled: bool,
+
+    /// Elapsed time of solving (excluding tree building, memory allocation, and saving).
+    pub elapsed: Duration,
+}
+
+/// A progress event of [`solve_batch`].
+#[derive(Debug)]
+pub enum BatchEvent<'a> {
+    /// The spot started to be solved.
+    Started {
+        index: usize,
+        name: &'a str,
+        memory_usage: u64,
+        is_compression_enabled: bool,
+    },
+
+    /// The solver of the spot made progress (reported before the first iteration and after every
+    /// iteration).
+    Progress {
+        index: usize,
+        name: &'a str,
+        progress: &'a SolveProgress,
+    },
+
+    /// The spot was solved (and saved if requested).
+    Finished {
+        index: usize,
+        name: &'a str,
+        report: &'a BatchSpotReport,
+    },
+
+    /// The spot failed. The remaining spots are not affected.
+    Failed {
+        index: usize,
+        name: &'a str,
+        error: &'a str,
+    },
+}
+
+#[derive(Default)]
+struct Resources {
+    memory_usage: u64,
+    num_running: usize,
+}
+
+/// Solves the given spots concurrently within the memory budget.
+///
+/// Spots are started in order. A spot is started when its memory usage fits in the remaining
+/// budget and the number of running spots is below `max_concurrency`. A spot that fails (invalid
+/// configuration, insufficient memory budget, panic while solving, or failure of saving) does not
+/// stop the batch; the error is reported through `callback` and stored in the returned vector.
+///
+/// The `callback` is called from the worker threads, but never concurrently.
+///
+/// This function returns the result of each spot in the same order as `spots`.
+pub fn solve_batch<F>(
+    spots: &[BatchSpot],
+    config: &BatchConfig,
+    callback: F,
+) -> Vec<Result<BatchSpotReport, String>>
+where
+    F: FnMut(&BatchEvent) + Send,
+{
+    let max_concurrency = config.max_concurrency.max(1);
+    let resources = Mutex::new(Resources::default());
+    let condvar = Condvar::new();
+    let callback = Mutex::new(callback);
+    let results = Mutex::new(vec![None; spots.len()]);
+
+    let report = |event: &BatchEvent| {
+        let mut callback = callback.lock().unwrap();
+        (*callback)(event);
+    };
+
+    let store = |index: usize, result: Result<BatchSpotReport, String>| {
+        match &result {
+            Ok(spot_report) => report(&BatchEvent::Finished {
+                index,
+                name: &spots[index].name,
+                report: spot_report,
+            }),
+            Err(error) => report(&BatchEvent::Failed {
+                index,
+                name: &spots[index].name,
+                error,
+            }),
+        }
+        results.lock().unwrap()[index] = Some(result);
+    };
+
+    thread::scope(|scope| {
+        for (index, spot) in spots.iter().enumerate() {
+            let prepared = catch_panic(|| prepare_spot(spot, config));
+            let (game, is_compression_enabled, memory_usage) = match prepared {
+                Ok(prepared) => prepared,
+                Err(error) => {
+                    store(index, Err(error));
+                    continue;
+                }
+            };
+
+            // wait until the spot fits in the budget
+            let mut guard = resources.lock().unwrap();
+            while guard.num_running > 0
+                && (guard.num_running >= max_concurrency
+                    || guard.memory_usage + memory_usage > config.memory_budget)
+            {
+                guard = condvar.wait(guard).unwrap();
+            }
+            guard.memory_usage += memory_usage;
+            guard.num_running += 1;
+            drop(guard);
+
+            report(&BatchEvent::Started {
+                index,
+                name: &spot.name,
+                memory_usage,
+                is_compression_enabled,
+            });
+
+            let (resources, condvar, store, report) = (&resources, &condvar, &store, &report);
+            scope.spawn(move || {
+                let on_progress = |progress: &SolveProgress| {
+                    report(&BatchEvent::Progress {
+                        index,
+                        name: &spot.name,
+                        progress,
+                    })
+                };
+
+                let result = catch_panic(|| {
+                    run_spot(
+                        game,
+                        spot,
+                        config,
+                        is_compression_enabled,
+                        memory_usage,
+                        on_progress,
+                    )
+                });
+
+                let mut guard = resources.lock().unwrap();
+                guard.memory_usage -= memory_usage;
+                guard.num_running -= 1;
+                drop(guard);
+                condvar.notify_all();
+
+                store(index, result);
+            });
+        }
+    });
+
+    results
+        .into_inner()
+        .unwrap()
+        .into_iter()
+        .map(|result| result.unwrap())
+        .collect()
+}
+
+fn prepare_spot(
+    spot: &BatchSpot,
+    config: &BatchConfig,
+) -> Result<(PostFlopGame, bool, u64), String> {
+    let action_tree = ActionTree::new(spot.tree_config.clone())?;
+    let mut game = PostFlopGame::with_config(spot.card_config.clone(), action_tree)?;
+
+    if let Some(payoff_model) = &spot.payoff_model {
+        game.set_payoff_model(payoff_model.clone());
+    }
+
+    game.check_payoff_model()?;
+
+    let (mut uncompressed, mut compressed) = game.memory_usage();
+    if config.algorithm == CfrAlgorithm::PcfrPlus {
+        let (uncompressed_raw, compressed_raw) = game.memory_usage_raw_regrets();
+        uncompressed += uncompressed_raw;
+        compressed += compressed_raw;
+    }
+
+    let enable_compression = config
+        .enable_compression
+        .unwrap_or(uncompressed > config.memory_budget);
+
+    let memory_usage = if enable_compression {
+        compressed
+    } else {
+        uncompressed
+    };
+
+    if memory_usage > config.memory_budget {
+        return Err(format!(
+            "Memory usage ({memory_usage} bytes) exceeds the memory budget ({} bytes)",
+            config.memory_budget
+        ));
+    }
+
+    Ok((game, enable_compression, memory_usage))
+}
+
+fn run_spot(
+    mut game: PostFlopGame,
+    spot: &BatchSpot,
+    config: &BatchConfig,
+    enable_compression: bool,
+    memory_usage: u64,
+    mut on_progress: impl FnMut(&SolveProgress),
+) -> Result<BatchSpotReport, String> {
+    game.allocate_memory(enable_compression);
+
+    let options = SolveOptions {
+        max_num_iterations: config.max_num_iterations,
+        target_exploitability: spot.tree_config.starting_pot as f32 * config.target_exploitability,
+        max_duration: config.max_duration,
+        max_exploitability_overhead: config.max_exploitability_overhead,
+        algorithm: config.algorithm,
+        ..Default::default()
+    };
+
+    let report = solve_with_options(&mut game, &options, |_, progress| {
+        on_progress(progress);
+        ControlFlow::Continue(())
+    });
+
+    if let Some(path) = &spot.output_path {
+        save_data_to_file(&game, &spot.name, path, config.compression_level)?;
+    }
+
+    Ok(BatchSpotReport {
+        exploitability: report.exploitability,
+        num_iterations: report.num_iterations,
+        stop_reason: report.stop_reason,
+        memory_usage,
+        is_compression_enabled: enable_compression,
+        elapsed: report.elapsed,
+    })
+}
+
+fn catch_panic<T>(f: impl FnOnce() -> Result<T, String>) -> Result<T, String> {
+    match panic::catch_unwind(AssertUnwindSafe(f)) {
+        Ok(result) => result,
+        Err(payload) => Err(format!("Panicked: {}", panic_message(&payload))),
+    }
+}
+
+fn panic_message(payload: &Box<dyn Any + Send>) -> &str {
+    if let Some(s) = payload.downcast_ref::<&str>() {
+        s
+    } else if let Some(s) = payload.downcast_ref::<String>() {
+        s
+    } else {
+        "unknown panic"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bet_size::*;
+    use crate::interface::*;
+    use crate::range::*;
+
+    fn river_spot(name: &str, flop: &str, output_path: Option<PathBuf>) -> BatchSpot {
+        let bet_sizes = BetSizeCandidates::try_from(("50%, a", "")).unwrap();
+        BatchSpot {
+            name: name.to_string(),
+            card_config: CardConfig {
+                range: [
+                    "AA,KK,QQ,AK".parse().unwrap(),
+                    "JJ-99,AQ,KQ".parse().unwrap(),
+                ],
+                flop: flop_from_str(flop).unwrap_or([0, 0, 0]),
+                turn: card_from_str("Qc").unwrap(),
+                river: card_from_str("2s").unwrap(),
+            },
+            tree_config: TreeConfig {
+                initial_state: BoardState::River,
+                starting_pot: 100,
+                effective_stack: 300,
+                river_bet_sizes: [bet_sizes.clone(), bet_sizes],
+                ..Default::default()
+            },
+            payoff_model: Some(PayoffModel::ChipEv),
+            output_path,
+        }
+    }
+
+    #[test]
+    fn batch_continues_past_failures() {
+        let dir = std::env::temp_dir().join("postflop-solver-batch-test");
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("spot.bin");
+
+        let spots = [
+            river_spot("invalid", "QcQc2s", None),
+            river_spot("first", "Td9d6h", Some(path.clone())),
+            river_spot("second", "8h7h3c", None),
+        ];
+
+        let config = BatchConfig {
+            max_concurrency: 2,
+            max_num_iterations: 100,
+            ..Default::default()
+        };
+
+        let mut events = Vec::new();
+        let results = solve_batch(&spots, &config, |event| {
+            events.push(match event {
+                BatchEvent::Started { index, .. } => ("started", *index),
+                BatchEvent::Progress { index, .. } => ("progress", *index),
+                BatchEvent::Finished { index, .. } => ("finished", *index),
+                BatchEvent::Failed { index, .. } => ("failed", *index),
+            });
+        });
+
+        assert!(results[0].is_err());
+        assert!(results[1].is_ok());
+        assert!(results[2].is_ok());
+        assert!(!results[1].as_ref().unwrap().is_compression_enabled);
+        assert!(events.contains(&("failed", 0)));
+        assert!(events.contains(&("progress", 1)));
+        assert!(events.contains(&("finished", 1)));
+        assert!(events.contains(&("finished", 2)));
+
+        let (game, memo): (PostFlopGame, _) = load_data_from_file(&path, None).unwrap();
+        assert_eq!(memo, "first");
+        assert!(game.is_solved());
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn batch_memory_budget() {
+        let spot = river_spot("spot", "Td9d6h", None);
+        let action_tree = ActionTree::new(spot.tree_config.clone()).unwrap();
+        let game = PostFlopGame::with_config(spot.card_config.clone(), action_tree).unwrap();
+        let (uncompressed, compressed) = game.memory_usage();
+
+        let config = BatchConfig {
+            memory_budget: compressed,
+            max_num_iterations: 10,
+            ..Default::default()
+        };
+
+        let results = solve_batch(std::slice::from_ref(&spot), &config, |_| {});
+        let report = results[0].as_ref().unwrap();
+        assert!(report.is_compression_enabled);
+        assert!(report.num_iterations <= 10);
+        assert_eq!(report.memory_usage, compressed);
+
+        let config = BatchConfig {
+            memory_budget: compressed - 1,
+            ..config
+        };
+
+        let results = solve_batch(&[spot], &config, |_| {});
+        assert!(results[0].is_err());
+        assert!(uncompressed > compressed);
+    }
+}
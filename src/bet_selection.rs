@@ -0,0 +1,342 @@
+use crate::action_tree::*;
+use crate::card::*;
+use crate::game::*;
+use crate::payoff::*;
+use crate::solver::*;
+use crate::utility::*;
+use std::collections::BTreeMap;
+use std::ops::ControlFlow;
+
+/// A struct containing the configuration of [`select_bet_sizes`].
+#[derive(Debug, Clone)]
+pub struct BetSelectionConfig {
+    /// Maximum number of bet sizes (including raises and all-in) kept at each node.
+    pub max_num_sizes: usize,
+
+    /// Whether to keep the all-in action regardless of its value. If `true`, the all-in action
+    /// does not count toward `max_num_sizes`.
+    pub keep_all_in: bool,
+
+    /// Payoff model of the games (`None` uses the default of [`PostFlopGame`]).
+    pub payoff_model: Option<PayoffModel>,
+
+    /// Options of solving, used for both the full and the reduced trees.
+    pub solve_options: SolveOptions,
+
+    /// Whether to enable compression.
+    pub enable_compression: bool,
+}
+
+impl Default for BetSelectionConfig {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            max_num_sizes: 2,
+            keep_all_in: false,
+            payoff_model: None,
+            solve_options: SolveOptions::default(),
+            enable_compression: false,
+        }
+    }
+}
+
+/// Usage statistics of a bet action of the full tree (see [`select_bet_sizes`]).
+#[derive(Debug, Clone, PartialEq)]
+pub struct BetSizeUsage {
+    /// Line of actions to the node, without chance actions (as in [`ActionTree::remove_line`]).
+    pub line: Vec<Action>,
+
+    /// The bet action (bet, raise, or all-in).
+    pub action: Action,
+
+    /// Frequency of the action at the node, averaged over the runouts and the reaching hands.
+    pub frequency: f32,
+
+    /// EV contribution of the action: the EV that the acting player would lose (averaged over the
+    /// whole range at the root) if the hands taking the action switched to their best other
+    /// action, assuming that the opponent does not adapt.
+    pub ev_contribution: f32,
+
+    /// Whether the action is kept in the reduced tree.
+    pub is_kept: bool,
+}
+
+/// A result of [`select_bet_sizes`].
+#[derive(Debug, Clone)]
+pub struct BetSelectionReport {
+    /// Tree configuration of the reduced tree (the same as the full tree).
+    pub tree_config: TreeConfig,
+
+    /// Added lines of the reduced tree.
+    pub added_lines: Vec<Vec<Action>>,
+
+    /// Removed lines of the reduced tree, including the pruned bet actions.
+    pub removed_lines: Vec<Vec<Action>>,
+
+    /// Usage statistics of the bet actions of the full tree, in depth-first order.
+    pub usages: Vec<BetSizeUsage>,
+
+    /// Expected values of each player at the root of the full tree.
+    pub full_expected_values: [f32; 2],
+
+    /// Expected values of each player at the root of the reduced tree.
+    pub reduced_expected_values: [f32; 2],
+
+    /// Exploitabilities of the full and reduced solutions.
+    pub exploitability: [f32; 2],
+}
+
+impl BetSelectionReport {
+    /// Returns the EV cost of the simplification for each player, i.e., the expected value in
+    /// the full tree minus the one in the reduced tree.
+    #[inline]
+    pub fn ev_cost(&self) -> [f32; 2] {
+        [
+            self.full_expected_values[0] - self.reduced_expected_values[0],
+            self.full_expected_values[1] - self.reduced_expected_values[1],
+        ]
+    }
+
+    /// Rebuilds the reduced [`ActionTree`].
+    pub fn action_tree(&self) -> Result<ActionTree, String> {
+        let mut action_tree = ActionTree::new(self.tree_config.clone())?;
+        for line in &self.removed_lines {
+            action_tree.remove_line(line)?;
+        }
+        for line in &self.added_lines {
+            action_tree.add_line(line)?;
+        }
+        Ok(action_tree)
+    }
+}
+
+/// Selects the most valuable bet sizes of a tree built from a large set of candidates.
+///
+/// The game of `card_config` and `action_tree` is solved, and the usage of each bet action is
+/// measured by its frequency and its EV contribution (see [`BetSizeUsage`]), aggregated over
+/// the runouts. At each node, the `max_num_sizes` bet actions with the largest EV contributions
+/// are kept and the others are removed from `action_tree` with [`ActionTree::remove_line`].
+/// Then the reduced tree is solved again to measure the EV cost of the simplification.
+///
+/// The decision nodes of all runouts are visited with the interpreter, so that this function is
+/// slow for trees starting from the flop. Bunching effect is not supported.
+pub fn select_bet_sizes(
+    card_config: &CardConfig,
+    mut action_tree: ActionTree,
+    config: &BetSelectionConfig,
+) -> Result<BetSelectionReport, String> {
+    if config.max_num_sizes == 0 && !config.keep_all_in {
+        return Err("At least one bet size must be kept".to_string());
+    }
+
+    let tree_config = action_tree.config().clone();
+    let added_lines = action_tree.added_lines().to_vec();
+    let removed_lines = action_tree.removed_lines().to_vec();
+
+    let mut full_game = PostFlopGame::with_config(card_config.clone(), action_tree)?;
+    let full_exploitability = solve_for_selection(&mut full_game, config);
+
+    let full_expected_values = root_expected_values(&mut full_game);
+    let root_weights = [0, 1].map(|player| full_game.normalized_weights(player).iter().sum());
+    let mut collector = UsageCollector::default();
+    collect_usages_recursive(
+        &mut full_game,
+        &mut Vec::new(),
+        1.0,
+        root_weights,
+        &mut collector,
+    );
+    drop(full_game);
+
+    let mut usages = collector.usages;
+    for (usage, &reach) in usages.iter_mut().zip(&collector.reaches) {
+        if reach > 0.0 {
+            usage.frequency /= reach;
+        }
+    }
+
+    // rebuild the full tree and prune the bet actions
+    action_tree = ActionTree::new(tree_config.clone())?;
+    for line in &removed_lines {
+        action_tree.remove_line(line)?;
+    }
+    for line in &added_lines {
+        action_tree.add_line(line)?;
+    }
+
+    mark_kept_usages(&mut usages, config);
+
+    let mut pruned: Vec<Vec<Action>> = Vec::new();
+    for usage in usages.iter().filter(|usage| !usage.is_kept) {
+        if pruned.iter().any(|line| usage.line.starts_with(line)) {
+            continue;
+        }
+        let mut line = usage.line.clone();
+        line.push(usage.action);
+        action_tree.remove_line(&line)?;
+        pruned.push(line);
+    }
+
+    let reduced_added_lines = action_tree.added_lines().to_vec();
+    let reduced_removed_lines = action_tree.removed_lines().to_vec();
+    let mut reduced_game = PostFlopGame::with_config(card_config.clone(), action_tree)?;
+    let reduced_exploitability = solve_for_selection(&mut reduced_game, config);
+    let reduced_expected_values = root_expected_values(&mut reduced_game);
+
+    Ok(BetSelectionReport {
+        tree_config,
+        added_lines: reduced_added_lines,
+        removed_lines: reduced_removed_lines,
+        usages,
+        full_expected_values,
+        reduced_expected_values,
+        exploitability: [full_exploitability, reduced_exploitability],
+    })
+}
+
+/// Allocates the memory of the game and solves it. Returns the exploitability.
+fn solve_for_selection(game: &mut PostFlopGame, config: &BetSelectionConfig) -> f32 {
+    if let Some(payoff_model) = &config.payoff_model {
+        game.set_payoff_model(payoff_model.clone());
+    }
+    game.allocate_memory(config.enable_compression);
+    let report = solve_with_options(
+        game,
+        &config.solve_options,
+        |_, _| ControlFlow::Continue(()),
+    );
+    report.exploitability
+}
+
+/// Computes the expected values of each player at the root. The current node is reset to the
+/// root.
+fn root_expected_values(game: &mut PostFlopGame) -> [f32; 2] {
+    game.back_to_root();
+    game.cache_normalized_weights();
+    [0, 1].map(|player| {
+        compute_average(
+            &game.expected_values(player),
+            game.normalized_weights(player),
+        )
+    })
+}
+
+/// Usage statistics being collected, indexed by the line and the action.
+#[derive(Default)]
+struct UsageCollector {
+    usages: Vec<BetSizeUsage>,
+    reaches: Vec<f32>,
+    indices: BTreeMap<(Vec<Action>, Action), usize>,
+}
+
+/// The recursive helper function for collecting the usage statistics of the bet actions.
+/// `chance_weight` is the probability of the dealt cards so far.
+fn collect_usages_recursive(
+    game: &mut PostFlopGame,
+    line: &mut Vec<Action>,
+    chance_weight: f32,
+    root_weights: [f32; 2],
+    collector: &mut UsageCollector,
+) {
+    if game.is_terminal_node() {
+        return;
+    }
+
+    let history = game.history().to_vec();
+
+    if game.is_chance_node() {
+        let possible_cards = game.possible_cards();
+        let weight = chance_weight / possible_cards.count_ones() as f32;
+        for card in 0..52 {
+            if possible_cards & (1 << card) != 0 {
+                game.play(card);
+                collect_usages_recursive(game, line, weight, root_weights, collector);
+                game.apply_history(&history);
+            }
+        }
+        return;
+    }
+
+    game.cache_normalized_weights();
+    let player = game.current_player();
+    let actions = game.available_actions();
+    let weights = game.normalized_weights(player);
+    let strategy = game.strategy();
+    let ev_detail = game.expected_values_detail(player);
+    let num_hands = weights.len();
+    let reach = chance_weight * weights.iter().sum::<f32>();
+
+    for (i, &action) in actions.iter().enumerate() {
+        if !matches!(action, Action::Bet(_) | Action::Raise(_) | Action::AllIn(_)) {
+            continue;
+        }
+
+        let mut frequency = 0.0;
+        let mut ev_contribution = 0.0;
+        for hand in 0..num_hands {
+            let weight = weights[hand] * strategy[i * num_hands + hand];
+            let best_other = (0..actions.len())
+                .filter(|&j| j != i)
+                .map(|j| ev_detail[j * num_hands + hand])
+                .fold(f32::NEG_INFINITY, f32::max);
+            frequency += weight;
+            ev_contribution += weight * (ev_detail[i * num_hands + hand] - best_other);
+        }
+
+        let index = *collector
+            .indices
+            .entry((line.clone(), action))
+            .or_insert_with(|| {
+                collector.usages.push(BetSizeUsage {
+                    line: line.clone(),
+                    action,
+                    frequency: 0.0,
+                    ev_contribution: 0.0,
+                    is_kept: true,
+                });
+                collector.reaches.push(0.0);
+                collector.usages.len() - 1
+            });
+
+        let usage = &mut collector.usages[index];
+        usage.frequency += chance_weight * frequency;
+        usage.ev_contribution += chance_weight * ev_contribution / root_weights[player];
+        collector.reaches[index] += reach;
+    }
+
+    for (i, &action) in actions.iter().enumerate() {
+        game.play(i);
+        line.push(action);
+        collect_usages_recursive(game, line, chance_weight, root_weights, collector);
+        line.pop();
+        game.apply_history(&history);
+    }
+}
+
+/// Keeps the `max_num_sizes` most valuable bet actions of each node.
+fn mark_kept_usages(usages: &mut [BetSizeUsage], config: &BetSelectionConfig) {
+    let mut start = 0;
+    while start < usages.len() {
+        let end = start
+            + usages[start..]
+                .iter()
+                .take_while(|usage| usage.line == usages[start].line)
+                .count();
+
+        let mut order = (start..end)
+            .filter(|&i| !(config.keep_all_in && matches!(usages[i].action, Action::AllIn(_))))
+            .collect::<Vec<_>>();
+        order.sort_by(|&i, &j| {
+            let (x, y) = (&usages[i], &usages[j]);
+            y.ev_contribution
+                .total_cmp(&x.ev_contribution)
+                .then(y.frequency.total_cmp(&x.frequency))
+        });
+
+        for &i in order.iter().skip(config.max_num_sizes) {
+            usages[i].is_kept = false;
+        }
+
+        start = end;
+    }
+}
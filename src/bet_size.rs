@@ -1,3 +1,6 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
 #[cfg(feature = "bincode")]
 use bincode::{Decode, Encode};
 
@@ -37,7 +40,11 @@ use bincode::{Decode, Encode};
 ///
 /// assert_eq!(bet_size.raise, vec![PrevBetRelative(2.5)]);
 /// ```
-#[derive(Debug, Clone, Default, PartialEq)]
+///
+/// With serde, the candidates are represented by the same strings:
+/// `{ "bet": "50%, 100c, 2e, a", "raise": "2.5x" }`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "BetSizeCandidatesRepr", into = "BetSizeCandidatesRepr")]
 #[cfg_attr(feature = "bincode", derive(Decode, Encode))]
 pub struct BetSizeCandidates {
     /// Bet size candidates for first bet.
@@ -50,14 +57,19 @@ pub struct BetSizeCandidates {
 /// Bet size candidates for the donk bets.
 ///
 /// See the [`BetSizeCandidates`] struct for the description and examples.
-#[derive(Debug, Clone, Default, PartialEq)]
+/// With serde, the candidates are represented by a comma-separated string like `"50%, a"`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
 #[cfg_attr(feature = "bincode", derive(Decode, Encode))]
 pub struct DonkSizeCandidates {
     pub donk: Vec<BetSize>,
 }
 
 /// Bet size specification.
-#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+///
+/// With serde, a bet size is represented by its string form (e.g., `"60%"`, `"2.5x"`, `"a"`).
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
 #[cfg_attr(feature = "bincode", derive(Decode, Encode))]
 pub enum BetSize {
     /// Bet size relative to the current pot size.
@@ -141,6 +153,116 @@ impl TryFrom<&str> for DonkSizeCandidates {
     }
 }
 
+#[derive(Serialize, Deserialize)]
+struct BetSizeCandidatesRepr {
+    #[serde(default)]
+    bet: String,
+    #[serde(default)]
+    raise: String,
+}
+
+impl TryFrom<BetSizeCandidatesRepr> for BetSizeCandidates {
+    type Error = String;
+
+    #[inline]
+    fn try_from(repr: BetSizeCandidatesRepr) -> Result<Self, Self::Error> {
+        Self::try_from((repr.bet.as_str(), repr.raise.as_str()))
+    }
+}
+
+impl From<BetSizeCandidates> for BetSizeCandidatesRepr {
+    #[inline]
+    fn from(candidates: BetSizeCandidates) -> Self {
+        Self {
+            bet: bet_sizes_to_string(&candidates.bet),
+            raise: bet_sizes_to_string(&candidates.raise),
+        }
+    }
+}
+
+impl TryFrom<String> for DonkSizeCandidates {
+    type Error = String;
+
+    #[inline]
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        Self::try_from(s.as_str())
+    }
+}
+
+impl From<DonkSizeCandidates> for String {
+    #[inline]
+    fn from(candidates: DonkSizeCandidates) -> Self {
+        bet_sizes_to_string(&candidates.donk)
+    }
+}
+
+impl TryFrom<&str> for BetSize {
+    type Error = String;
+
+    /// Attempts to convert a string into a bet size.
+    ///
+    /// Raise-only forms (e.g., "2.5x" and "20c3r") are accepted.
+    /// See the [`BetSizeCandidates`] struct for the description.
+    #[inline]
+    fn try_from(s: &str) -> Result<Self, Self::Error> {
+        bet_size_from_str(s.trim(), true)
+    }
+}
+
+impl TryFrom<String> for BetSize {
+    type Error = String;
+
+    #[inline]
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        Self::try_from(s.as_str())
+    }
+}
+
+impl From<BetSize> for String {
+    #[inline]
+    fn from(bet_size: BetSize) -> Self {
+        bet_size.to_string()
+    }
+}
+
+impl fmt::Display for BetSize {
+    /// Formats the bet size in the string form accepted by `try_from()`.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            BetSize::PotRelative(ratio) => write!(f, "{}%", format_float(ratio * 100.0)),
+            BetSize::PrevBetRelative(ratio) => write!(f, "{}x", format_float(ratio)),
+            BetSize::Additive(add, 0) => write!(f, "{add}c"),
+            BetSize::Additive(add, cap) => write!(f, "{add}c{cap}r"),
+            BetSize::Geometric(num_streets, max_pot_rel) => {
+                if num_streets != 0 {
+                    write!(f, "{num_streets}")?;
+                }
+                write!(f, "e")?;
+                if max_pot_rel.is_finite() {
+                    write!(f, "{}%", format_float(max_pot_rel * 100.0))?;
+                }
+                Ok(())
+            }
+            BetSize::AllIn => write!(f, "a"),
+        }
+    }
+}
+
+fn bet_sizes_to_string(bet_sizes: &[BetSize]) -> String {
+    bet_sizes
+        .iter()
+        .map(BetSize::to_string)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Formats a float without the representation error of the multiplication by 100.
+fn format_float(x: f64) -> String {
+    let s = format!("{:.6}", x);
+    let s = s.trim_end_matches('0');
+    s.strip_suffix('.').unwrap_or(s).to_string()
+}
+
 fn parse_float(s: &str) -> Option<f64> {
     if s.contains('+') || s.contains('-') || s.contains(|c: char| c.is_ascii_alphabetic()) {
         None
@@ -323,6 +445,43 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_bet_size_to_string() {
+        let tests = [
+            "0%", "75%", "112.5%", "7%", "1.001x", "3.5x", "0c", "123c", "0c1r", "100c100r", "e",
+            "2e", "e37.5%", "100e0.5%", "a",
+        ];
+
+        for s in tests {
+            let bet_size = bet_size_from_str(s, true).unwrap();
+            assert_eq!(bet_size.to_string(), s);
+        }
+    }
+
+    #[test]
+    fn test_bet_sizes_serde() {
+        let candidates = BetSizeCandidates::try_from(("60%, e, a", "2.5x")).unwrap();
+        let json = serde_json::to_string(&candidates).unwrap();
+        assert_eq!(json, r#"{"bet":"60%, e, a","raise":"2.5x"}"#);
+        assert_eq!(
+            serde_json::from_str::<BetSizeCandidates>(&json).unwrap(),
+            candidates
+        );
+
+        let donk = DonkSizeCandidates::try_from("50%, a").unwrap();
+        let json = serde_json::to_string(&donk).unwrap();
+        assert_eq!(json, r#""50%, a""#);
+        assert_eq!(
+            serde_json::from_str::<DonkSizeCandidates>(&json).unwrap(),
+            donk
+        );
+
+        let json = serde_json::to_string(&BetSize::PrevBetRelative(2.5)).unwrap();
+        assert_eq!(json, r#""2.5x""#);
+        assert!(serde_json::from_str::<BetSize>(r#""abc""#).is_err());
+        assert!(serde_json::from_str::<BetSizeCandidates>(r#"{"bet":"2.5x"}"#).is_err());
+    }
+
     #[test]
     fn test_donk_sizes_from_str() {
         let tests = [
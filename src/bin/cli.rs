@@ -0,0 +1,377 @@
+//! Command-line interface that solves the spots described in job files.
+//!
+//! Usage: `postflop-cli [--quiet] <job-file>...` (build with `--features cli`)
+//!
+//! Each job file is written in JSON or TOML (selected by the `.toml` extension) and describes
+//! the card configuration, the tree configuration, the payoff model, the solve limits, and the
+//! output paths. Relative paths are resolved against the directory of the job file.
+//! The `cards` and `tree` sections are the serde representations of `CardConfig` and
+//! `TreeConfig`, respectively.
+//!
+//! ```toml
+//! name = "BTN vs BB, Td9d6h"
+//!
+//! [cards]
+//! range = ["66+,A8s+,A5s-A4s,AJo+,K9s+,KQo", "QQ-22,AQs-A2s,ATo+,K5s+,KJo+"]
+//! flop = "Td9d6h"
+//! turn = "Qc"
+//!
+//! [tree]
+//! initial_state = "Turn"
+//! starting_pot = 200
+//! effective_stack = 900
+//! turn_bet_sizes = [{ bet = "60%, e, a", raise = "2.5x" }, { bet = "60%, e, a", raise = "2.5x" }]
+//! river_bet_sizes = [{ bet = "60%, e, a", raise = "2.5x" }, { bet = "60%, e, a", raise = "2.5x" }]
+//! river_donk_sizes = "50%"
+//! add_allin_threshold = 1.5
+//! force_allin_threshold = 0.15
+//! merging_threshold = 0.1
+//!
+//! [payoff]
+//! model = "icm" # or "chip_ev" (the default if the section is omitted)
+//! state_file = "state.json"
+//!
+//! [solve]
+//! max_num_iterations = 1000
+//! target_exploitability = 1.0 # defaults to 0.5% of the starting pot
+//! max_seconds = 90.0 # no time limit if omitted
+//! max_exploitability_overhead = 0.1 # computed every 10 iterations if omitted
+//! algorithm = "PcfrPlus" # "Dcfr" (default), "CfrPlus", "LinearCfr", or
+//!                        # { DcfrCustom = { alpha = 1.5, beta = 0.0, gamma = 2.0 } }
+//! chance_sampling = { num_samples = 8, seed = 0 } # all chance outcomes if omitted
+//! temperature = 2.0 # entropy-regularized equilibrium; Nash equilibrium if omitted
+//! compression = false
+//!
+//! [output]
+//! solution = "out/td9d6h-qc.bin"
+//! summary = "out/td9d6h-qc.json"
+//! ```
+//!
+//! The summary is a JSON file containing the exploitability, the number of iterations, the reason
+//! why solving stopped, the elapsed time, and the equity, EV, and root action frequencies of each
+//! player.
+
+use postflop_solver::*;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::{self, Write};
+use std::ops::ControlFlow;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::time::Duration;
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Job {
+    #[serde(default)]
+    name: Option<String>,
+    cards: CardConfig,
+    tree: TreeConfig,
+    #[serde(default)]
+    payoff: Option<PayoffJob>,
+    #[serde(default)]
+    solve: SolveJob,
+    #[serde(default)]
+    output: OutputJob,
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "model", rename_all = "snake_case", deny_unknown_fields)]
+enum PayoffJob {
+    ChipEv,
+    Icm { state_file: PathBuf },
+}
+
+#[derive(Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct SolveJob {
+    max_num_iterations: u32,
+    target_exploitability: Option<f32>,
+    max_seconds: Option<f64>,
+    max_exploitability_overhead: Option<f32>,
+    algorithm: CfrAlgorithm,
+    chance_sampling: Option<ChanceSampling>,
+    temperature: Option<f32>,
+    compression: bool,
+}
+
+impl Default for SolveJob {
+    fn default() -> Self {
+        Self {
+            max_num_iterations: 1000,
+            target_exploitability: None,
+            max_seconds: None,
+            max_exploitability_overhead: None,
+            algorithm: CfrAlgorithm::default(),
+            chance_sampling: None,
+            temperature: None,
+            compression: false,
+        }
+    }
+}
+
+#[derive(Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct OutputJob {
+    solution: Option<PathBuf>,
+    summary: Option<PathBuf>,
+    compression_level: Option<i32>,
+    memo: String,
+}
+
+#[derive(Serialize)]
+struct Summary {
+    name: Option<String>,
+    board: String,
+    starting_pot: i32,
+    effective_stack: i32,
+    memory_usage: u64,
+    compression: bool,
+    max_num_iterations: u32,
+    target_exploitability: f32,
+    exploitability: f32,
+    num_iterations: u32,
+    stop_reason: StopReason,
+    elapsed_seconds: f64,
+    players: [PlayerSummary; 2],
+    root_player: usize,
+    root_actions: Vec<String>,
+    root_frequencies: Vec<f32>,
+}
+
+#[derive(Serialize)]
+struct PlayerSummary {
+    num_hands: usize,
+    equity: f32,
+    expected_value: f32,
+}
+
+fn main() -> ExitCode {
+    let mut quiet = false;
+    let mut paths = Vec::new();
+
+    for arg in std::env::args().skip(1) {
+        match arg.as_str() {
+            "-q" | "--quiet" => quiet = true,
+            "-h" | "--help" => {
+                println!("Usage: postflop-cli [--quiet] <job-file>...");
+                return ExitCode::SUCCESS;
+            }
+            _ => paths.push(PathBuf::from(arg)),
+        }
+    }
+
+    if paths.is_empty() {
+        eprintln!("Usage: postflop-cli [--quiet] <job-file>...");
+        return ExitCode::FAILURE;
+    }
+
+    let mut num_failures = 0;
+
+    for path in &paths {
+        match run_job(path, quiet) {
+            Ok(summary) => {
+                println!(
+                    "{}: exploitability = {:.4}, iterations = {} ({:?}), elapsed = {:.1}s",
+                    path.display(),
+                    summary.exploitability,
+                    summary.num_iterations,
+                    summary.stop_reason,
+                    summary.elapsed_seconds
+                );
+            }
+            Err(e) => {
+                eprintln!("{}: {}", path.display(), e);
+                num_failures += 1;
+            }
+        }
+    }
+
+    if num_failures == 0 {
+        ExitCode::SUCCESS
+    } else {
+        eprintln!("{} of {} jobs failed", num_failures, paths.len());
+        ExitCode::FAILURE
+    }
+}
+
+fn run_job(path: &Path, quiet: bool) -> Result<Summary, String> {
+    let job = read_job(path)?;
+    let base_dir = path.parent().unwrap_or_else(|| Path::new(""));
+
+    let action_tree = ActionTree::new(job.tree)?;
+    let mut game = PostFlopGame::with_config(job.cards, action_tree)?;
+
+    match &job.payoff {
+        Some(PayoffJob::ChipEv) => game.set_payoff_model(PayoffModel::ChipEv),
+        Some(PayoffJob::Icm { state_file }) => {
+            game.set_payoff_model(PayoffModel::icm_from_file(base_dir.join(state_file))?)
+        }
+        None => game.set_payoff_model(PayoffModel::ChipEv),
+    }
+
+    let (memory_usage, memory_usage_compressed) = game.memory_usage();
+    game.allocate_memory(job.solve.compression);
+
+    let target_exploitability = job
+        .solve
+        .target_exploitability
+        .unwrap_or(game.tree_config().starting_pot as f32 * 0.005);
+
+    let max_duration = match job.solve.max_seconds {
+        Some(seconds) => Some(
+            Duration::try_from_secs_f64(seconds)
+                .map_err(|e| format!("Invalid max_seconds: {e}"))?,
+        ),
+        None => None,
+    };
+
+    let options = SolveOptions {
+        max_num_iterations: job.solve.max_num_iterations,
+        target_exploitability,
+        max_duration,
+        max_exploitability_overhead: job.solve.max_exploitability_overhead,
+        algorithm: job.solve.algorithm,
+        chance_sampling: job.solve.chance_sampling,
+        temperature: job.solve.temperature,
+        ..Default::default()
+    };
+
+    let report = solve_with_options(&mut game, &options, |_, progress| {
+        if !quiet {
+            print!(
+                "\riteration: {} / {} (exploitability = {:.4e})",
+                progress.current_iteration, progress.max_num_iterations, progress.exploitability
+            );
+            io::stdout().flush().unwrap();
+        }
+        ControlFlow::Continue(())
+    });
+
+    if !quiet {
+        println!();
+    }
+
+    if let Some(solution) = &job.output.solution {
+        let solution = base_dir.join(solution);
+        create_parent_dir(&solution)?;
+        save_data_to_file(
+            &game,
+            &job.output.memo,
+            &solution,
+            job.output.compression_level,
+        )?;
+    }
+
+    let summary = Summary {
+        name: job.name.clone(),
+        board: board_to_string(&game.current_board())?,
+        starting_pot: game.tree_config().starting_pot,
+        effective_stack: game.tree_config().effective_stack,
+        memory_usage: if job.solve.compression {
+            memory_usage_compressed
+        } else {
+            memory_usage
+        },
+        compression: job.solve.compression,
+        max_num_iterations: job.solve.max_num_iterations,
+        target_exploitability,
+        exploitability: report.exploitability,
+        num_iterations: report.num_iterations,
+        stop_reason: report.stop_reason,
+        elapsed_seconds: report.elapsed.as_secs_f64(),
+        players: [player_summary(&mut game, 0), player_summary(&mut game, 1)],
+        root_player: game.current_player(),
+        root_actions: game
+            .available_actions()
+            .iter()
+            .map(|action| format!("{action:?}"))
+            .collect(),
+        root_frequencies: root_frequencies(&game),
+    };
+
+    if let Some(summary_path) = &job.output.summary {
+        let summary_path = base_dir.join(summary_path);
+        create_parent_dir(&summary_path)?;
+        let json = serde_json::to_string_pretty(&summary)
+            .map_err(|e| format!("Failed to serialize summary: {e}"))?;
+        fs::write(&summary_path, json).map_err(|e| {
+            format!(
+                "Failed to write summary to {}: {}",
+                summary_path.display(),
+                e
+            )
+        })?;
+    }
+
+    Ok(summary)
+}
+
+fn read_job(path: &Path) -> Result<Job, String> {
+    let content = fs::read_to_string(path).map_err(|e| format!("Failed to read job file: {e}"))?;
+    let is_toml = path
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("toml"));
+
+    let job: Job = if is_toml {
+        toml::from_str(&content).map_err(|e| format!("Failed to parse job file: {e}"))?
+    } else {
+        serde_json::from_str(&content).map_err(|e| format!("Failed to parse job file: {e}"))?
+    };
+
+    check_solve_job(&job.solve)?;
+    Ok(job)
+}
+
+/// Checks the solver options that `solve_with_options` would reject with a panic.
+fn check_solve_job(solve: &SolveJob) -> Result<(), String> {
+    if let CfrAlgorithm::DcfrCustom { alpha, beta, gamma } = solve.algorithm {
+        if !(alpha.is_finite() && beta.is_finite() && gamma.is_finite()) {
+            return Err(format!("Invalid algorithm: {:?}", solve.algorithm));
+        }
+    }
+
+    if solve.chance_sampling.is_some_and(|s| s.num_samples == 0) {
+        return Err("Invalid chance_sampling: num_samples must be positive".to_string());
+    }
+
+    if let Some(temperature) = solve.temperature {
+        if !(temperature > 0.0 && temperature.is_finite()) {
+            return Err(format!("Invalid temperature: {temperature}"));
+        }
+    }
+
+    Ok(())
+}
+
+fn board_to_string(board: &[u8]) -> Result<String, String> {
+    board.iter().map(|&card| card_to_string(card)).collect()
+}
+
+fn player_summary(game: &mut PostFlopGame, player: usize) -> PlayerSummary {
+    game.cache_normalized_weights();
+    let weights = game.normalized_weights(player);
+    PlayerSummary {
+        num_hands: game.private_cards(player).len(),
+        equity: compute_average(&game.equity(player), weights),
+        expected_value: compute_average(&game.expected_values(player), weights),
+    }
+}
+
+fn root_frequencies(game: &PostFlopGame) -> Vec<f32> {
+    let strategy = game.strategy();
+    let weights = game.normalized_weights(game.current_player());
+    let num_hands = weights.len();
+    strategy
+        .chunks_exact(num_hands)
+        .map(|chunk| compute_average(chunk, weights))
+        .collect()
+}
+
+fn create_parent_dir(path: &Path) -> Result<(), String> {
+    match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => fs::create_dir_all(dir)
+            .map_err(|e| format!("Failed to create directory {}: {}", dir.display(), e)),
+        _ => Ok(()),
+    }
+}
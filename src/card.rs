@@ -1,5 +1,6 @@
 use crate::hand::*;
 use crate::range::*;
+use serde::{ser::Error, Deserialize, Deserializer, Serialize, Serializer};
 use std::mem;
 
 #[cfg(feature = "bincode")]
@@ -26,6 +27,9 @@ pub const NOT_DEALT: u8 = 0xff;
 ///     river: NOT_DEALT,
 /// };
 /// ```
+///
+/// With serde, cards are represented by strings and undealt cards are omitted:
+/// `{ "range": ["66+,A8s+", "QQ-22,AQs-A2s"], "flop": "Td9d6h", "turn": "Qc" }`.
 #[derive(Debug, Clone)]
 #[cfg_attr(feature = "bincode", derive(Decode, Encode))]
 pub struct CardConfig {
@@ -54,6 +58,73 @@ impl Default for CardConfig {
     }
 }
 
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct CardConfigRepr {
+    range: [Range; 2],
+    flop: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    turn: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    river: Option<String>,
+}
+
+impl Serialize for CardConfig {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let card_to_option = |card: u8| {
+            if card == NOT_DEALT {
+                Ok(None)
+            } else {
+                card_to_string(card).map(Some)
+            }
+        };
+
+        let flop = if self.flop == [NOT_DEALT; 3] {
+            String::new()
+        } else {
+            self.flop
+                .iter()
+                .map(|&card| card_to_string(card))
+                .collect::<Result<String, String>>()
+                .map_err(S::Error::custom)?
+        };
+
+        let repr = CardConfigRepr {
+            range: self.range,
+            flop,
+            turn: card_to_option(self.turn).map_err(S::Error::custom)?,
+            river: card_to_option(self.river).map_err(S::Error::custom)?,
+        };
+
+        repr.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for CardConfig {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        use serde::de::Error;
+
+        let repr = CardConfigRepr::deserialize(deserializer)?;
+        let card_from_option = |card: &Option<String>| match card {
+            Some(card) => card_from_str(card),
+            None => Ok(NOT_DEALT),
+        };
+
+        let flop = if repr.flop.is_empty() {
+            [NOT_DEALT; 3]
+        } else {
+            flop_from_str(&repr.flop).map_err(D::Error::custom)?
+        };
+
+        Ok(Self {
+            range: repr.range,
+            flop,
+            turn: card_from_option(&repr.turn).map_err(D::Error::custom)?,
+            river: card_from_option(&repr.river).map_err(D::Error::custom)?,
+        })
+    }
+}
+
 type PrivateCards = [Vec<(u8, u8)>; 2];
 
 type Indices = [Vec<u16>; 2];
@@ -96,6 +167,69 @@ pub(crate) fn index_to_card_pair(index: usize) -> (u8, u8) {
     (card1 as u8, card2 as u8)
 }
 
+/// Returns the canonical representative of the given flop under suit isomorphism.
+///
+/// The representative is the lexicographically smallest (sorted) flop among the flops obtained by
+/// permuting the suits. The input flop must consist of three distinct cards.
+pub fn canonical_flop(flop: &[u8; 3]) -> [u8; 3] {
+    suit_permutations()
+        .map(|perm| permute_flop(flop, &perm))
+        .min()
+        .unwrap()
+}
+
+/// Returns the number of flops that are isomorphic to the given flop (including itself).
+///
+/// This is the weight of the flop when it represents its isomorphism class.
+pub fn flop_isomorphism_count(flop: &[u8; 3]) -> u32 {
+    let mut flops = suit_permutations()
+        .map(|perm| permute_flop(flop, &perm))
+        .collect::<Vec<_>>();
+    flops.sort_unstable();
+    flops.dedup();
+    flops.len() as u32
+}
+
+/// Returns the 1,755 strategically distinct flops with the number of isomorphic flops of each.
+///
+/// The flops are in ascending order of their canonical representation, and the weights sum up to
+/// 22,100 (= 52 choose 3).
+pub fn canonical_flops() -> Vec<([u8; 3], u32)> {
+    let mut result = Vec::new();
+    for card1 in 0..52 {
+        for card2 in card1 + 1..52 {
+            for card3 in card2 + 1..52 {
+                let flop = [card1, card2, card3];
+                if canonical_flop(&flop) == flop {
+                    result.push((flop, flop_isomorphism_count(&flop)));
+                }
+            }
+        }
+    }
+    result
+}
+
+#[inline]
+fn suit_permutations() -> impl Iterator<Item = [u8; 4]> {
+    (0..24u8).map(|mut index| {
+        let mut suits = vec![0, 1, 2, 3];
+        let mut perm = [0; 4];
+        for (i, item) in perm.iter_mut().enumerate() {
+            let radix = 4 - i as u8;
+            *item = suits.remove((index % radix) as usize);
+            index /= radix;
+        }
+        perm
+    })
+}
+
+#[inline]
+fn permute_flop(flop: &[u8; 3], perm: &[u8; 4]) -> [u8; 3] {
+    let mut result = flop.map(|card| (card & !3) | perm[card as usize & 3]);
+    result.sort_unstable();
+    result
+}
+
 impl CardConfig {
     pub(crate) fn valid_indices(
         &self,
@@ -439,4 +573,45 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_card_config_serde() {
+        let card_config = CardConfig {
+            range: ["AA,KK".parse().unwrap(), "AKs:0.5".parse().unwrap()],
+            flop: flop_from_str("Td9d6h").unwrap(),
+            turn: card_from_str("Qc").unwrap(),
+            river: NOT_DEALT,
+        };
+
+        let json = serde_json::to_string(&card_config).unwrap();
+        assert_eq!(
+            json,
+            r#"{"range":["KK+","AKs:0.5"],"flop":"6h9dTd","turn":"Qc"}"#
+        );
+
+        let decoded = serde_json::from_str::<CardConfig>(&json).unwrap();
+        assert_eq!(decoded.range, card_config.range);
+        assert_eq!(decoded.flop, card_config.flop);
+        assert_eq!(decoded.turn, card_config.turn);
+        assert_eq!(decoded.river, NOT_DEALT);
+
+        let invalid = r#"{"range":["AA","KK"],"flop":"Td9d"}"#;
+        assert!(serde_json::from_str::<CardConfig>(invalid).is_err());
+    }
+
+    #[test]
+    fn test_canonical_flops() {
+        let flops = canonical_flops();
+        assert_eq!(flops.len(), 1755);
+        assert_eq!(flops.iter().map(|&(_, w)| w).sum::<u32>(), 22100);
+
+        let monotone = flop_from_str("AsKsQs").unwrap();
+        let rainbow = flop_from_str("AsKhQd").unwrap();
+        let paired = flop_from_str("AsAhKd").unwrap();
+        assert_eq!(flop_isomorphism_count(&monotone), 4);
+        assert_eq!(flop_isomorphism_count(&rainbow), 24);
+        assert_eq!(flop_isomorphism_count(&paired), 12);
+        assert_eq!(canonical_flop(&monotone), flop_from_str("AcKcQc").unwrap());
+        assert!(flops.contains(&(canonical_flop(&rainbow), 24)));
+    }
 }
@@ -3,19 +3,29 @@
 //  - Magic number (4 bytes): 90 57 f1 09
 //  - Version number (1 byte): 1
 //  - Compression type (1 byte): 0 (none), 1 (zstd)
-//  - Data type (1 byte): 0 (game), 1 (bunching)
+//  - Data type (1 byte): 0 (game), 1 (bunching), 2 (checkpoint)
 //  - Estimated memory usage (`VarIntEncoding`)
 //  - Memo string
 //
 // `VarIntEncoding`: https://github.com/bincode-org/bincode/blob/trunk/docs/spec.md#varintencoding
 
+use crate::action_tree::*;
 use crate::bunching::*;
 use crate::game::*;
 use crate::interface::*;
-use bincode::{Decode, Encode};
-use std::fs::File;
+use crate::solver::*;
+use bincode::{
+    de::Decoder,
+    enc::Encoder,
+    error::{DecodeError, EncodeError},
+    Decode, Encode,
+};
+use std::ffi::OsString;
+use std::fs::{self, File};
 use std::io::{BufReader, BufWriter, Read, Write};
+use std::ops::ControlFlow;
 use std::path::Path;
+use std::time::{Duration, Instant};
 
 const MAGIC: u32 = 0x09f15790;
 const VERSION: u8 = 1;
@@ -24,6 +34,7 @@ const VERSION: u8 = 1;
 pub enum DataType {
     Game = 0,
     Bunching = 1,
+    Checkpoint = 2,
 }
 
 /// A trait for data that can be saved into a file.
@@ -56,6 +67,24 @@ pub fn save_data_into_std_write<T: FileData, W: Write>(
         return Err("Data is not ready to save".to_string());
     }
 
+    save_into_std_write(
+        data,
+        T::data_type(),
+        data.estimated_memory_usage(),
+        memo,
+        writer,
+        compression_level,
+    )
+}
+
+fn save_into_std_write<E: Encode, W: Write>(
+    data: &E,
+    data_type: DataType,
+    estimated_memory_usage: u64,
+    memo: &str,
+    writer: &mut W,
+    compression_level: Option<i32>,
+) -> Result<(), String> {
     #[cfg(not(feature = "zstd"))]
     if compression_level.is_some() {
         return Err("Compression is not supported".to_string());
@@ -67,9 +96,9 @@ pub fn save_data_into_std_write<T: FileData, W: Write>(
     let compression_type = compression_level.is_some() as u8;
     encode_into_std_write(compression_type, writer, "Failed to write compression type")?;
 
-    encode_into_std_write(T::data_type() as u8, writer, "Failed to write data type")?;
+    encode_into_std_write(data_type as u8, writer, "Failed to write data type")?;
     encode_into_std_write(
-        data.estimated_memory_usage(),
+        estimated_memory_usage,
         writer,
         "Failed to write memory usage",
     )?;
@@ -120,6 +149,14 @@ pub fn load_data_from_std_read<T: FileData, R: Read>(
     reader: &mut R,
     max_memory_usage: Option<u64>,
 ) -> Result<(T, String), String> {
+    load_from_std_read(reader, T::data_type(), max_memory_usage)
+}
+
+fn load_from_std_read<D: Decode, R: Read>(
+    reader: &mut R,
+    expected_data_type: DataType,
+    max_memory_usage: Option<u64>,
+) -> Result<(D, String), String> {
     let magic: u32 = decode_from_std_read(reader, "Failed to read magic number")?;
     if magic != MAGIC {
         return Err("Magic number is invalid".to_string());
@@ -141,7 +178,7 @@ pub fn load_data_from_std_read<T: FileData, R: Read>(
     }
 
     let data_type: u8 = decode_from_std_read(reader, "Failed to read data type")?;
-    if data_type != T::data_type() as u8 {
+    if data_type != expected_data_type as u8 {
         return Err("Data type is invalid".to_string());
     }
 
@@ -155,9 +192,9 @@ pub fn load_data_from_std_read<T: FileData, R: Read>(
     let memo: String = decode_from_std_read(reader, "Failed to read memo")?;
 
     #[cfg(not(feature = "zstd"))]
-    let data: T = decode_from_std_read(reader, "Failed to read data")?;
+    let data: D = decode_from_std_read(reader, "Failed to read data")?;
     #[cfg(feature = "zstd")]
-    let data: T = if compression_type == 0 {
+    let data: D = if compression_type == 0 {
         decode_from_std_read(reader, "Failed to read data")?
     } else {
         let mut zstd_decoder = zstd::stream::Decoder::new(reader)
@@ -178,6 +215,143 @@ pub fn load_data_from_file<T: FileData, P: AsRef<Path>>(
     load_data_from_std_read(&mut reader, max_memory_usage)
 }
 
+/// Saves a checkpoint of an in-progress solve into standard writer.
+///
+/// A checkpoint contains the cumulative regrets and strategy of `game` and `num_iterations`, the
+/// number of iterations performed so far, together with the frequency locks and the lock rules.
+/// The game must be ready to solve (i.e., memory is allocated but the game is not finalized yet),
+/// its target storage mode must be `River`, and it must not have a leaf evaluator, which cannot be
+/// serialized.
+pub fn save_checkpoint_into_std_write<W: Write>(
+    game: &PostFlopGame,
+    num_iterations: u32,
+    writer: &mut W,
+    compression_level: Option<i32>,
+) -> Result<(), String> {
+    if !game.is_ready() {
+        return Err("Game is not ready to solve".to_string());
+    }
+
+    if game.target_storage_mode() != BoardState::River {
+        return Err("Target storage mode must be River to save a checkpoint".to_string());
+    }
+
+    if game.leaf_evaluator().is_some() {
+        return Err("Game with a leaf evaluator cannot be checkpointed".to_string());
+    }
+
+    let checkpoint = CheckpointRef {
+        game,
+        num_iterations,
+    };
+
+    save_into_std_write(
+        &checkpoint,
+        DataType::Checkpoint,
+        game.target_memory_usage(),
+        "",
+        writer,
+        compression_level,
+    )
+}
+
+/// Saves a checkpoint of an in-progress solve into file.
+///
+/// The checkpoint is first written to a temporary file next to `path` and then renamed, so the
+/// previous checkpoint at `path` is kept intact if writing fails halfway.
+pub fn save_checkpoint_to_file<P: AsRef<Path>>(
+    game: &PostFlopGame,
+    num_iterations: u32,
+    path: P,
+    compression_level: Option<i32>,
+) -> Result<(), String> {
+    let path = path.as_ref();
+    let mut tmp_path = OsString::from(path);
+    tmp_path.push(".tmp");
+
+    let file = File::create(&tmp_path).map_err(|e| format!("Failed to create file: {}", e))?;
+    let mut writer = BufWriter::new(file);
+    let result =
+        save_checkpoint_into_std_write(game, num_iterations, &mut writer, compression_level);
+    drop(writer);
+
+    match result {
+        Ok(()) => fs::rename(&tmp_path, path).map_err(|e| format!("Failed to rename file: {}", e)),
+        Err(e) => {
+            fs::remove_file(&tmp_path).ok();
+            Err(e)
+        }
+    }
+}
+
+/// Loads a checkpoint from standard reader.
+///
+/// Returns the game and the number of iterations performed so far. Pass the latter as
+/// `start_iteration` of [`SolveOptions`] to resume solving.
+pub fn load_checkpoint_from_std_read<R: Read>(
+    reader: &mut R,
+    max_memory_usage: Option<u64>,
+) -> Result<(PostFlopGame, u32), String> {
+    let (checkpoint, _): (Checkpoint, _) =
+        load_from_std_read(reader, DataType::Checkpoint, max_memory_usage)?;
+    Ok((checkpoint.game, checkpoint.num_iterations))
+}
+
+/// Loads a checkpoint from file.
+///
+/// Returns the game and the number of iterations performed so far. Pass the latter as
+/// `start_iteration` of [`SolveOptions`] to resume solving.
+pub fn load_checkpoint_from_file<P: AsRef<Path>>(
+    path: P,
+    max_memory_usage: Option<u64>,
+) -> Result<(PostFlopGame, u32), String> {
+    let file = File::open(path).map_err(|e| format!("Failed to open file: {}", e))?;
+    let mut reader = BufReader::new(file);
+    load_checkpoint_from_std_read(&mut reader, max_memory_usage)
+}
+
+/// Performs Discounted CFR algorithm like [`solve_with_options`], saving a checkpoint to `path`
+/// whenever `checkpoint_interval` has elapsed since the last one.
+///
+/// To resume an interrupted solve, load the checkpoint with [`load_checkpoint_from_file`] and
+/// call this method again with `start_iteration` set to the loaded iteration count; the result is
+/// identical to that of an uninterrupted solve. Games that cannot be checkpointed are listed in
+/// [`save_checkpoint_into_std_write`]. If saving a checkpoint fails, solving is cancelled (the
+/// game is still finalized) and the error is returned.
+pub fn solve_with_checkpoints<P, F>(
+    game: &mut PostFlopGame,
+    options: &SolveOptions,
+    path: P,
+    checkpoint_interval: Duration,
+    compression_level: Option<i32>,
+    mut callback: F,
+) -> Result<SolveReport, String>
+where
+    P: AsRef<Path>,
+    F: FnMut(&PostFlopGame, &SolveProgress) -> ControlFlow<()>,
+{
+    let path = path.as_ref();
+    let mut last_checkpoint = Instant::now();
+    let mut error = None;
+
+    let report = solve_with_options(game, options, |game, progress| {
+        if last_checkpoint.elapsed() >= checkpoint_interval {
+            let iteration = progress.current_iteration;
+            if let Err(e) = save_checkpoint_to_file(game, iteration, path, compression_level) {
+                error = Some(e);
+                return ControlFlow::Break(());
+            }
+            last_checkpoint = Instant::now();
+        }
+        callback(game, progress)
+    });
+
+    match error {
+        Some(e) => Err(e),
+        None => Ok(report),
+    }
+}
+
 impl FileData for PostFlopGame {
     fn data_type() -> DataType {
         DataType::Game
@@ -206,11 +380,69 @@ impl FileData for BunchingData {
     }
 }
 
+struct CheckpointRef<'a> {
+    game: &'a PostFlopGame,
+    num_iterations: u32,
+}
+
+struct Checkpoint {
+    game: PostFlopGame,
+    num_iterations: u32,
+}
+
+impl Encode for CheckpointRef<'_> {
+    fn encode<E: Encoder>(&self, encoder: &mut E) -> Result<(), EncodeError> {
+        self.num_iterations.encode(encoder)?;
+        self.game.encode(encoder)?;
+
+        // the game omits the cumulative regrets in the `River` storage mode
+        self.game.regret_storage().encode(encoder)?;
+
+        // raw cumulative regrets of PCFR+
+        let (raw_regrets, raw_regret_scales) = self.game.raw_regret_storage();
+        raw_regrets.encode(encoder)?;
+        raw_regret_scales.encode(encoder)?;
+
+        // frequency locks and lock rules are not part of the game
+        self.game.frequency_lock_entries().encode(encoder)?;
+        self.game.lock_rules().encode(encoder)
+    }
+}
+
+impl Decode for Checkpoint {
+    fn decode<D: Decoder>(decoder: &mut D) -> Result<Self, DecodeError> {
+        let num_iterations = u32::decode(decoder)?;
+        let mut game = PostFlopGame::decode(decoder)?;
+
+        let regrets = Vec::<u8>::decode(decoder)?;
+        let storage = game.regret_storage_mut();
+        if regrets.len() != storage.len() {
+            return Err(DecodeError::Other("Regret storage size mismatch"));
+        }
+        storage.copy_from_slice(&regrets);
+
+        let raw_regrets = Vec::<u8>::decode(decoder)?;
+        let raw_regret_scales = Vec::<f32>::decode(decoder)?;
+        game.restore_raw_regret_storage(&raw_regrets, &raw_regret_scales)
+            .map_err(DecodeError::OtherString)?;
+
+        let frequency_locks = Vec::decode(decoder)?;
+        game.restore_frequency_locks(frequency_locks)
+            .map_err(DecodeError::OtherString)?;
+        game.restore_lock_rules(Vec::decode(decoder)?);
+
+        Ok(Self {
+            game,
+            num_iterations,
+        })
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::action_tree::*;
     use crate::card::*;
+    use crate::payoff::*;
     use crate::range::*;
     use crate::utility::*;
 
@@ -318,4 +550,266 @@ mod tests {
         assert!((root_ev_oop - 45.0).abs() < 1e-4);
         assert!((root_ev_ip - 15.0).abs() < 1e-4);
     }
+
+    /// Solves a turn game and reloads it without the river data.
+    fn solve_and_reload_turn(lock: impl FnOnce(&mut PostFlopGame)) -> (PostFlopGame, PostFlopGame) {
+        let card_config = CardConfig {
+            range: [
+                "AA,KK,QQ,AK,T9s".parse().unwrap(),
+                "JJ-99,AQ,KQ".parse().unwrap(),
+            ],
+            flop: flop_from_str("Td9d6h").unwrap(),
+            turn: card_from_str("Qc").unwrap(),
+            ..Default::default()
+        };
+
+        let tree_config = TreeConfig {
+            initial_state: BoardState::Turn,
+            starting_pot: 100,
+            effective_stack: 300,
+            turn_bet_sizes: [("50%", "").try_into().unwrap(), Default::default()],
+            river_bet_sizes: [("50%", "").try_into().unwrap(), Default::default()],
+            ..Default::default()
+        };
+
+        let action_tree = ActionTree::new(tree_config).unwrap();
+        let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+        game.set_payoff_model(PayoffModel::ChipEv);
+        game.allocate_memory(false);
+        lock(&mut game);
+        solve(&mut game, 1000, 0.1, false);
+
+        // save and load without the river data
+        let mut buf = Vec::new();
+        game.set_target_storage_mode(BoardState::Turn).unwrap();
+        save_data_into_std_write(&game, "", &mut buf, None).unwrap();
+        let loaded: PostFlopGame = load_data_from_std_read(&mut &buf[..], None).unwrap().0;
+        assert_eq!(loaded.storage_mode(), BoardState::Turn);
+
+        (game, loaded)
+    }
+
+    #[test]
+    fn on_demand_river() {
+        let (mut game, mut loaded) = solve_and_reload_turn(|_| {});
+
+        // check, check, 2s, OOP checks
+        let river = card_from_str("2s").unwrap() as usize;
+        let history = [0, 0, river, 0];
+        game.apply_history(&history);
+        loaded.apply_history(&history);
+        assert_eq!(loaded.num_on_demand_rivers(), 1);
+        assert_eq!(loaded.history(), &history);
+        assert_eq!(loaded.current_board(), game.current_board());
+        assert_eq!(loaded.available_actions(), game.available_actions());
+        assert_eq!(loaded.total_bet_amount(), game.total_bet_amount());
+        assert_eq!(loaded.weights(0), game.weights(0));
+
+        // the local solution is close to the full one
+        game.cache_normalized_weights();
+        loaded.cache_normalized_weights();
+        for player in 0..2 {
+            let ev = compute_average(
+                &game.expected_values(player),
+                game.normalized_weights(player),
+            );
+            let weights = loaded.normalized_weights(player);
+            let loaded_ev = compute_average(&loaded.expected_values(player), weights);
+            assert!((ev - loaded_ev).abs() < 1.0);
+        }
+        assert_eq!(loaded.strategy().len(), game.strategy().len());
+
+        // the cache is reused
+        loaded.back_to_root();
+        loaded.apply_history(&history[..3]);
+        assert_eq!(loaded.num_on_demand_rivers(), 1);
+        loaded.back_to_root();
+        loaded.apply_history(&[0, 0, card_from_str("3s").unwrap() as usize]);
+        assert_eq!(loaded.num_on_demand_rivers(), 2);
+
+        loaded.clear_on_demand_river_cache();
+        assert_eq!(loaded.num_on_demand_rivers(), 0);
+        assert!(loaded.history().is_empty());
+    }
+
+    #[test]
+    fn on_demand_river_unreachable() {
+        // OOP never bets the turn
+        let (mut game, mut loaded) = solve_and_reload_turn(|game| {
+            let num_hands = game.num_private_hands(0);
+            let mut strategy = vec![0.0; game.available_actions().len() * num_hands];
+            strategy[..num_hands].fill(1.0);
+            game.lock_current_strategy(&strategy);
+        });
+
+        // bet, call, 2s: the river subtree is solved with a placeholder range of OOP
+        let history = [1, 1, card_from_str("2s").unwrap() as usize];
+        game.apply_history(&history);
+        loaded.apply_history(&history);
+        assert_eq!(loaded.num_on_demand_rivers(), 1);
+        assert_eq!(loaded.available_actions(), game.available_actions());
+        assert!(loaded.weights(0).iter().all(|&w| w == 0.0));
+        assert!(loaded.weights(1).iter().any(|&w| w > 0.0));
+
+        loaded.cache_normalized_weights();
+        assert!(loaded.normalized_weights(0).iter().all(|&w| w == 0.0));
+        loaded.play(0);
+        assert!(loaded.weights(0).iter().all(|&w| w == 0.0));
+    }
+
+    #[test]
+    fn checkpoint_and_resume() {
+        let card_config = CardConfig {
+            range: [
+                "AA,KK,QQ,AK".parse().unwrap(),
+                "JJ-99,AQ,KQ".parse().unwrap(),
+            ],
+            flop: flop_from_str("Td9d6h").unwrap(),
+            turn: card_from_str("Qc").unwrap(),
+            ..Default::default()
+        };
+
+        let tree_config = TreeConfig {
+            initial_state: BoardState::Turn,
+            starting_pot: 100,
+            effective_stack: 300,
+            turn_bet_sizes: [("50%", "").try_into().unwrap(), Default::default()],
+            river_bet_sizes: [("50%", "").try_into().unwrap(), Default::default()],
+            ..Default::default()
+        };
+
+        let new_game = |enable_compression| {
+            let action_tree = ActionTree::new(tree_config.clone()).unwrap();
+            let mut game = PostFlopGame::with_config(card_config.clone(), action_tree).unwrap();
+            game.set_payoff_model(PayoffModel::ChipEv);
+            game.allocate_memory(enable_compression);
+            game
+        };
+
+        for (algorithm, enable_compression) in [
+            (CfrAlgorithm::Dcfr, false),
+            (CfrAlgorithm::Dcfr, true),
+            (CfrAlgorithm::PcfrPlus, false),
+            (CfrAlgorithm::PcfrPlus, true),
+        ] {
+            let options = SolveOptions {
+                max_num_iterations: 30,
+                algorithm,
+                ..Default::default()
+            };
+
+            // uninterrupted solve
+            let mut expected = new_game(enable_compression);
+            solve_with_options(&mut expected, &options, |_, _| ControlFlow::Continue(()));
+
+            // interrupted solve
+            let mut game = new_game(enable_compression);
+            solve_with_options(&mut game, &options, |game, progress| {
+                if progress.current_iteration == 13 {
+                    save_checkpoint_to_file(game, 13, "tmpfile.checkpoint", None).unwrap();
+                    return ControlFlow::Break(());
+                }
+                ControlFlow::Continue(())
+            });
+            assert!(save_checkpoint_to_file(&game, 13, "tmpfile.checkpoint", None).is_err());
+
+            // resume
+            let (mut game, start_iteration) =
+                load_checkpoint_from_file("tmpfile.checkpoint", None).unwrap();
+            std::fs::remove_file("tmpfile.checkpoint").unwrap();
+            assert_eq!(start_iteration, 13);
+
+            let options = SolveOptions {
+                start_iteration,
+                ..options
+            };
+            let report = solve_with_options(&mut game, &options, |_, _| ControlFlow::Continue(()));
+            assert_eq!(report.num_iterations, 30);
+
+            assert_eq!(game.strategy(), expected.strategy());
+            game.play(0);
+            expected.play(0);
+            assert_eq!(game.strategy(), expected.strategy());
+            game.cache_normalized_weights();
+            expected.cache_normalized_weights();
+            assert_eq!(game.expected_values(1), expected.expected_values(1));
+        }
+    }
+
+    #[test]
+    fn checkpoint_and_resume_with_locks() {
+        let card_config = CardConfig {
+            range: [
+                "AA,KK,QQ,AK".parse().unwrap(),
+                "JJ-99,AQ,KQ".parse().unwrap(),
+            ],
+            flop: flop_from_str("Td9d6h").unwrap(),
+            turn: card_from_str("Qc").unwrap(),
+            ..Default::default()
+        };
+
+        let tree_config = TreeConfig {
+            initial_state: BoardState::Turn,
+            starting_pot: 100,
+            effective_stack: 300,
+            turn_bet_sizes: [("50%", "").try_into().unwrap(), ("50%", "").try_into().unwrap()],
+            river_bet_sizes: [("50%", "").try_into().unwrap(), Default::default()],
+            ..Default::default()
+        };
+
+        let rules = [LockRule {
+            range: Some("JJ".parse().unwrap()),
+            categories: Vec::new(),
+            frequencies: vec![1.0, 0.0],
+        }];
+
+        let new_game = || {
+            let action_tree = ActionTree::new(tree_config.clone()).unwrap();
+            let mut game = PostFlopGame::with_config(card_config.clone(), action_tree).unwrap();
+            game.set_payoff_model(PayoffModel::ChipEv);
+            game.allocate_memory(false);
+            game.lock_current_frequencies(&[0.3, 0.7]);
+            game.play(0);
+            game.lock_current_strategy_by_rules(&rules);
+            game.back_to_root();
+            game
+        };
+
+        let options = SolveOptions {
+            max_num_iterations: 30,
+            ..Default::default()
+        };
+
+        // uninterrupted solve
+        let mut expected = new_game();
+        solve_with_options(&mut expected, &options, |_, _| ControlFlow::Continue(()));
+
+        // interrupted solve
+        let mut game = new_game();
+        solve_with_options(&mut game, &options, |game, progress| {
+            if progress.current_iteration == 13 {
+                save_checkpoint_to_file(game, 13, "tmpfile.checkpoint2", None).unwrap();
+                return ControlFlow::Break(());
+            }
+            ControlFlow::Continue(())
+        });
+
+        // resume
+        let (mut game, start_iteration) =
+            load_checkpoint_from_file("tmpfile.checkpoint2", None).unwrap();
+        std::fs::remove_file("tmpfile.checkpoint2").unwrap();
+        assert_eq!(game.current_frequency_lock(), Some(vec![0.3, 0.7]));
+        assert_eq!(game.lock_rules(), expected.lock_rules());
+
+        let options = SolveOptions {
+            start_iteration,
+            ..options
+        };
+        solve_with_options(&mut game, &options, |_, _| ControlFlow::Continue(()));
+
+        assert_eq!(game.strategy(), expected.strategy());
+        game.play(0);
+        expected.play(0);
+        assert_eq!(game.strategy(), expected.strategy());
+    }
 }
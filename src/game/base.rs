@@ -43,7 +43,9 @@ impl Game for PostFlopGame {
         player: usize,
         cfreach: &[f32],
     ) {
-        if self.bunching_num_dead_cards == 0 {
+        if node.is_leaf() {
+            self.evaluate_leaf(result, node, player, cfreach);
+        } else if self.bunching_num_dead_cards == 0 {
             self.evaluate_internal(result, node, player, cfreach);
         } else {
             self.evaluate_internal_bunching(result, node, player, cfreach);
@@ -67,6 +69,7 @@ impl Game for PostFlopGame {
     #[inline]
     fn set_solved(&mut self) {
         self.state = State::Solved;
+        self.storage_raw_regrets = Vec::new();
         let history = self.action_history.clone();
         self.apply_history(&history);
     }
@@ -110,10 +113,61 @@ impl Game for PostFlopGame {
         }
     }
 
+    #[inline]
+    fn apply_frequency_lock(&self, node: &Self::Node, strategy: &mut [f32], reach: Option<&[f32]>) {
+        self.apply_frequency_lock_internal(node, strategy, reach);
+    }
+
+    #[inline]
+    fn is_frequency_locked(&self, node: &Self::Node) -> bool {
+        !self.frequency_locks.is_empty() && self.frequency_locks.contains_key(&self.node_index(node))
+    }
+
+    #[inline]
+    fn reach_sums(
+        &self,
+        result: &mut [f32],
+        node: &Self::Node,
+        player: usize,
+        cfreach: &[f32],
+    ) -> bool {
+        if self.bunching_num_dead_cards != 0 {
+            return false;
+        }
+
+        self.reach_sums_internal(result, node, player, cfreach);
+        true
+    }
+
     #[inline]
     fn is_compression_enabled(&self) -> bool {
         self.is_compression_enabled
     }
+
+    fn allocate_raw_regrets(&mut self) -> bool {
+        if !self.is_ready() {
+            return false;
+        }
+
+        if !self.storage_raw_regrets.is_empty() {
+            return true;
+        }
+
+        self.storage_raw_regrets = vec![0; self.storage2.len()];
+
+        let base2 = self.storage2.as_ptr();
+        let base4 = self.storage_raw_regrets.as_mut_ptr();
+        for node in &self.node_arena {
+            let mut node = node.lock();
+            if !node.is_terminal() && !node.is_chance() {
+                let offset = unsafe { node.storage2.offset_from(base2) };
+                node.storage4 = unsafe { base4.offset(offset) };
+                node.scale4 = 0.0;
+            }
+        }
+
+        true
+    }
 }
 
 impl PostFlopGame {
@@ -158,6 +212,8 @@ impl PostFlopGame {
 
         self.state = State::TreeBuilt;
 
+        self.frequency_locks.clear();
+        self.on_demand_river_cache.clear();
         self.init_interpreter();
         self.reset_bunching_effect();
 
@@ -173,6 +229,10 @@ impl PostFlopGame {
             return Err("Game is not successfully initialized".to_string());
         }
 
+        if self.tree_config.depth_limit.is_some() {
+            return Err("Bunching effect is not supported with depth limit".to_string());
+        }
+
         if !bunching_data.is_ready() {
             return Err("Bunching configuration is not ready".to_string());
         }
@@ -204,6 +264,34 @@ impl PostFlopGame {
         self.back_to_root();
     }
 
+    /// Sets the payoff model used at the terminal nodes. The solved result will be invalid.
+    ///
+    /// If not set, the ICM state file at the default location is used; evaluating the terminal
+    /// nodes panics if it cannot be loaded (see [`check_payoff_model`]).
+    ///
+    /// [`check_payoff_model`]: #method.check_payoff_model
+    #[inline]
+    pub fn set_payoff_model(&mut self, payoff_model: PayoffModel) {
+        self.payoff_model = Some(payoff_model);
+        self.clear_on_demand_river_cache();
+    }
+
+    /// Obtains the payoff model (`None` means the default ICM state file).
+    #[inline]
+    pub fn payoff_model(&self) -> Option<&PayoffModel> {
+        self.payoff_model.as_ref()
+    }
+
+    /// Checks that the payoff model is available, i.e., the ICM state file at the default location
+    /// can be loaded if the payoff model is not set.
+    #[inline]
+    pub fn check_payoff_model(&self) -> Result<(), String> {
+        match &self.payoff_model {
+            None => DEFAULT_ICM_UTILITY.as_ref().map(|_| ()).map_err(Clone::clone),
+            Some(_) => Ok(()),
+        }
+    }
+
     /// Obtains the card configuration.
     #[inline]
     pub fn card_config(&self) -> &CardConfig {
@@ -262,6 +350,17 @@ impl PostFlopGame {
         self.memory_usage_bunching_internal()
     }
 
+    /// Returns the estimated additional memory usage in bytes (uncompressed, compressed) when the
+    /// game is solved by PCFR+, which stores the raw cumulative regrets separately.
+    #[inline]
+    pub fn memory_usage_raw_regrets(&self) -> (u64, u64) {
+        if self.state <= State::Uninitialized {
+            panic!("Game is not successfully initialized");
+        }
+
+        (4 * self.num_storage, 2 * self.num_storage)
+    }
+
     /// Remove lines after building the `PostFlopGame` but before allocating memory.
     ///
     /// This allows the removal of chance-specific lines (e.g., remove overbets on board-pairing
@@ -335,6 +434,8 @@ impl PostFlopGame {
 
         self.storage_mode = BoardState::River;
         self.target_storage_mode = BoardState::River;
+
+        self.apply_lock_rules();
     }
 
     /// Checks the card configuration.
@@ -566,6 +667,7 @@ impl PostFlopGame {
         self.storage2 = Vec::new();
         self.storage_ip = Vec::new();
         self.storage_chance = Vec::new();
+        self.storage_raw_regrets = Vec::new();
     }
 
     /// Counts the number of nodes in the game tree.
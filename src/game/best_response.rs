@@ -0,0 +1,379 @@
+use super::*;
+use crate::interface::*;
+use crate::sliceop::*;
+use crate::utility::*;
+use std::mem::MaybeUninit;
+
+/// The EV gain of the best response at a decision node (see [`PostFlopGame::best_response`]).
+#[derive(Debug, Clone, PartialEq)]
+pub struct BestResponseNode {
+    /// History of the node, which can be passed to [`PostFlopGame::apply_history`].
+    ///
+    /// Suit-isomorphic runouts share a node, and only the representative card appears.
+    pub history: Vec<usize>,
+
+    /// EV gain of switching to the best response from this node onward, compared with the
+    /// original strategy.
+    ///
+    /// The gain is weighted by the probability of reaching the node under the original strategy,
+    /// so the gain of the first decision node equals the total gain when the node is certainly
+    /// reached.
+    pub ev_gain: f32,
+}
+
+/// A report of [`PostFlopGame::best_response`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct BestResponseReport {
+    /// The player who plays the best response.
+    pub player: usize,
+
+    /// EV of the player with the original strategy.
+    ///
+    /// As in [`compute_current_ev`], (starting pot) / 2 is already subtracted.
+    ///
+    /// [`compute_current_ev`]: crate::compute_current_ev
+    pub base_ev: f32,
+
+    /// EV of the player with the best response (the same bias as `base_ev` is subtracted).
+    pub best_response_ev: f32,
+
+    /// EV gains of the decision nodes of the player, in depth-first order.
+    pub nodes: Vec<BestResponseNode>,
+}
+
+impl PostFlopGame {
+    /// Computes the maximally exploitative strategy of `player` against the current strategy of
+    /// the opponent.
+    ///
+    /// The opponent's strategy is the current strategy of this game, including the locked
+    /// strategies, so an externally supplied strategy can be given by locking the opponent's
+    /// nodes (see [`lock_current_strategy`]). The returned game has the same configuration as
+    /// this game, the strategy of the opponent copied from this game, and the pure best response
+    /// as the strategy of `player`; it is finalized, so it can be inspected as usual. The locks of
+    /// `player` are removed in the returned game. This game is not modified.
+    ///
+    /// The report contains the EVs of `player` before and after the deviation and the EV gain at
+    /// each decision node of `player`.
+    ///
+    /// The memory of this game must be allocated (with the storage mode of river), and the game
+    /// does not have to be solved. Bunching effect is not supported.
+    ///
+    /// [`lock_current_strategy`]: #method.lock_current_strategy
+    pub fn best_response(
+        &self,
+        player: usize,
+    ) -> Result<(PostFlopGame, BestResponseReport), String> {
+        if self.state < State::MemoryAllocated || self.storage_mode != BoardState::River {
+            return Err("Memory is not allocated with the storage mode of river".to_string());
+        }
+
+        if player > 1 {
+            return Err(format!("Invalid player: {player}"));
+        }
+
+        if self.bunching_num_dead_cards != 0 {
+            return Err("Bunching effect is not supported".to_string());
+        }
+
+        let mut game = self.clone_with_strategy()?;
+
+        let num_hands = game.num_private_hands(player);
+        let mut base_cfv = Vec::with_capacity(num_hands);
+        let mut best_cfv = Vec::with_capacity(num_hands);
+        let ev_gains = MutexLike::new(vec![0.0; game.node_arena.len()]);
+
+        game.deviation_recursive(
+            base_cfv.spare_capacity_mut(),
+            best_cfv.spare_capacity_mut(),
+            &mut game.root(),
+            player,
+            &game.initial_weights[player],
+            &game.initial_weights[player ^ 1],
+            &ev_gains,
+            &|node, _, _, best_actions| pure_best_response(best_actions, node.num_actions()),
+        );
+
+        unsafe {
+            base_cfv.set_len(num_hands);
+            best_cfv.set_len(num_hands);
+        }
+
+        // remove the locks of `player`
+        for node in &game.node_arena {
+            let mut node = node.lock();
+            if !node.is_terminal() && !node.is_chance() && node.player() == player {
+                node.is_locked = false;
+            }
+        }
+        let arena = &game.node_arena;
+        game.locking_strategy
+            .retain(|&index, _| arena[index].lock().is_locked);
+
+        finalize(&mut game);
+
+        let ev_gains = ev_gains.lock();
+        let mut nodes = Vec::new();
+        let mut history = Vec::new();
+        game.collect_ev_gains_recursive(0, player, &ev_gains, &mut history, &mut nodes);
+
+        let weights = &game.initial_weights[player];
+        let report = BestResponseReport {
+            player,
+            base_ev: weighted_sum(&base_cfv, weights),
+            best_response_ev: weighted_sum(&best_cfv, weights),
+            nodes,
+        };
+
+        Ok((game, report))
+    }
+
+    /// Builds a game with the same configuration and copies the cumulative strategy and the
+    /// locks. The memory of the returned game is allocated.
+    pub(super) fn clone_with_strategy(&self) -> Result<PostFlopGame, String> {
+        let mut action_tree = ActionTree::new(self.tree_config.clone())?;
+        for line in &self.removed_lines {
+            action_tree.remove_line(line)?;
+        }
+        for line in &self.added_lines {
+            action_tree.add_line(line)?;
+        }
+
+        let mut game = PostFlopGame::with_config(self.card_config.clone(), action_tree)?;
+        game.payoff_model = self.payoff_model.clone();
+        game.leaf_evaluator = self.leaf_evaluator.clone();
+        game.allocate_memory(self.is_compression_enabled);
+
+        if game.node_arena.len() != self.node_arena.len() || game.num_storage != self.num_storage {
+            return Err("Failed to rebuild the game tree".to_string());
+        }
+
+        game.storage1.copy_from_slice(&self.storage1);
+        for (dst, src) in game.node_arena.iter().zip(&self.node_arena) {
+            let (mut dst, src) = (dst.lock(), src.lock());
+            dst.scale1 = src.scale1;
+            dst.is_locked = src.is_locked;
+        }
+
+        game.locking_strategy = self.locking_strategy.clone();
+        Ok(game)
+    }
+
+    /// The recursive helper function for computing the values of a deviation of `player` from
+    /// the current strategy, such as the best response.
+    ///
+    /// `base_result` and `best_result` receive the counterfactual values of `player` with the
+    /// original strategy and with the deviation, respectively, and `reach` is the reach
+    /// probabilities of `player` under the original strategy. At each decision node of `player`,
+    /// `deviate` is called with the node, the original strategy, `reach`, and the counterfactual
+    /// values of the actions under the deviation, and returns the strategy of the deviation,
+    /// which is written to the node.
+    #[allow(clippy::too_many_arguments)]
+    pub(super) fn deviation_recursive<F>(
+        &self,
+        base_result: &mut [MaybeUninit<f32>],
+        best_result: &mut [MaybeUninit<f32>],
+        node: &mut PostFlopNode,
+        player: usize,
+        reach: &[f32],
+        cfreach: &[f32],
+        ev_gains: &MutexLike<Vec<f32>>,
+        deviate: &F,
+    ) where
+        F: Fn(&PostFlopNode, &[f32], &[f32], &[f32]) -> Vec<f32> + Sync,
+    {
+        let num_hands = base_result.len();
+
+        // terminal node
+        if node.is_terminal() {
+            self.evaluate(base_result, node, player, cfreach);
+            for (dst, src) in best_result.iter_mut().zip(&*base_result) {
+                dst.write(unsafe { src.assume_init() });
+            }
+            return;
+        }
+
+        let num_actions = node.num_actions();
+        let base_actions = MutexLike::new(Vec::with_capacity(num_actions * num_hands));
+        let best_actions = MutexLike::new(Vec::with_capacity(num_actions * num_hands));
+
+        // chance node
+        if node.is_chance() {
+            let cfreach_updated = cfreach
+                .iter()
+                .map(|&x| x / self.chance_factor(node) as f32)
+                .collect::<Vec<_>>();
+
+            for_each_child(node, |action| {
+                self.deviation_recursive(
+                    row_mut(base_actions.lock().spare_capacity_mut(), action, num_hands),
+                    row_mut(best_actions.lock().spare_capacity_mut(), action, num_hands),
+                    &mut node.play(action),
+                    player,
+                    reach,
+                    &cfreach_updated,
+                    ev_gains,
+                    deviate,
+                );
+            });
+
+            let isomorphic_chances = self.isomorphic_chances(node);
+            for (actions, result) in [(&base_actions, base_result), (&best_actions, best_result)] {
+                let mut actions = actions.lock();
+                unsafe { actions.set_len(num_actions * num_hands) };
+
+                let mut result_f64 = Vec::with_capacity(num_hands);
+                sum_slices_f64_uninit(result_f64.spare_capacity_mut(), &actions);
+                unsafe { result_f64.set_len(num_hands) };
+
+                // process isomorphic chances
+                for (i, &isomorphic_index) in isomorphic_chances.iter().enumerate() {
+                    let swap_list = &self.isomorphic_swap(node, i)[player];
+                    let tmp = row_mut(&mut actions, isomorphic_index as usize, num_hands);
+
+                    apply_swap(tmp, swap_list);
+
+                    result_f64.iter_mut().zip(&*tmp).for_each(|(r, &v)| {
+                        *r += v as f64;
+                    });
+
+                    apply_swap(tmp, swap_list);
+                }
+
+                result.iter_mut().zip(&result_f64).for_each(|(r, &v)| {
+                    r.write(v as f32);
+                });
+            }
+
+            return;
+        }
+
+        // obtain the original strategy
+        let mut strategy = if self.is_compression_enabled {
+            normalized_strategy_compressed(node.strategy_compressed(), num_actions)
+        } else {
+            normalized_strategy(node.strategy(), num_actions)
+        };
+
+        let locking = self.locking_strategy(node);
+        apply_locking_strategy(&mut strategy, locking);
+
+        // player node
+        if node.player() == player {
+            let mut reach_actions = strategy.clone();
+            reach_actions.chunks_exact_mut(num_hands).for_each(|row| {
+                mul_slice(row, reach);
+            });
+
+            for_each_child(node, |action| {
+                self.deviation_recursive(
+                    row_mut(base_actions.lock().spare_capacity_mut(), action, num_hands),
+                    row_mut(best_actions.lock().spare_capacity_mut(), action, num_hands),
+                    &mut node.play(action),
+                    player,
+                    row(&reach_actions, action, num_hands),
+                    cfreach,
+                    ev_gains,
+                    deviate,
+                );
+            });
+
+            let mut base_actions = base_actions.lock();
+            let mut best_actions = best_actions.lock();
+            unsafe {
+                base_actions.set_len(num_actions * num_hands);
+                best_actions.set_len(num_actions * num_hands);
+            }
+
+            let base_result = fma_slices_uninit(base_result, &strategy, &base_actions);
+
+            // choose the strategy of the deviation
+            let deviation = deviate(node, &strategy, reach, &best_actions);
+            let best_result = fma_slices_uninit(best_result, &deviation, &best_actions);
+
+            if self.is_compression_enabled {
+                let scale = encode_unsigned_slice(node.strategy_compressed_mut(), &deviation);
+                node.set_strategy_scale(scale);
+            } else {
+                node.strategy_mut().copy_from_slice(&deviation);
+            }
+
+            let gain = weighted_sum(best_result, reach) - weighted_sum(base_result, reach);
+            ev_gains.lock()[self.node_index(node)] = gain;
+        }
+        // opponent node
+        else {
+            let row_size = cfreach.len();
+            strategy.chunks_exact_mut(row_size).for_each(|row| {
+                mul_slice(row, cfreach);
+            });
+
+            for_each_child(node, |action| {
+                self.deviation_recursive(
+                    row_mut(base_actions.lock().spare_capacity_mut(), action, num_hands),
+                    row_mut(best_actions.lock().spare_capacity_mut(), action, num_hands),
+                    &mut node.play(action),
+                    player,
+                    reach,
+                    row(&strategy, action, row_size),
+                    ev_gains,
+                    deviate,
+                );
+            });
+
+            for (actions, result) in [(&base_actions, base_result), (&best_actions, best_result)] {
+                let mut actions = actions.lock();
+                unsafe { actions.set_len(num_actions * num_hands) };
+                sum_slices_uninit(result, &actions);
+            }
+        }
+    }
+
+    /// The recursive helper function for collecting the EV gains of the decision nodes.
+    pub(super) fn collect_ev_gains_recursive(
+        &self,
+        node_index: usize,
+        player: usize,
+        ev_gains: &[f32],
+        history: &mut Vec<usize>,
+        nodes: &mut Vec<BestResponseNode>,
+    ) {
+        let node = self.node_arena[node_index].lock();
+        if node.is_terminal() {
+            return;
+        }
+
+        if !node.is_chance() && node.player() == player {
+            nodes.push(BestResponseNode {
+                history: history.clone(),
+                ev_gain: ev_gains[node_index],
+            });
+        }
+
+        let children_index = node_index + node.children_offset as usize;
+        for (i, child) in node.children().iter().enumerate() {
+            match child.lock().prev_action {
+                Action::Chance(card) => history.push(card as usize),
+                _ => history.push(i),
+            }
+            self.collect_ev_gains_recursive(children_index + i, player, ev_gains, history, nodes);
+            history.pop();
+        }
+    }
+}
+
+/// Returns the pure strategy that takes the action with the highest value for each hand.
+fn pure_best_response(action_values: &[f32], num_actions: usize) -> Vec<f32> {
+    let num_hands = action_values.len() / num_actions;
+    let mut best_response = vec![0.0; num_actions * num_hands];
+    for hand in 0..num_hands {
+        let best_action = (0..num_actions)
+            .max_by(|&a, &b| {
+                let x = action_values[a * num_hands + hand];
+                let y = action_values[b * num_hands + hand];
+                x.total_cmp(&y).then(b.cmp(&a))
+            })
+            .unwrap();
+        best_response[best_action * num_hands + hand] = 1.0;
+    }
+    best_response
+}
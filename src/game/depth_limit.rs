@@ -0,0 +1,257 @@
+use super::*;
+use std::mem::MaybeUninit;
+
+/// A trait for the value functions that evaluate the leaves of a depth-limited game tree.
+///
+/// A leaf is the node where the street set by [`TreeConfig::depth_limit`] ends without an all-in.
+/// The evaluator is called during solving for each leaf and each player, so it must be cheap
+/// enough to be called once per leaf per iteration. Typical implementations are equity
+/// realization heuristics (see [`EquityRealization`]) or value functions computed from a set of
+/// continuation strategies.
+pub trait LeafEvaluator: Send + Sync {
+    /// Computes the counterfactual values of `info.player()` at the leaf described by `info`.
+    ///
+    /// `opponent_reach[j]` is the probability that the opponent reaches the leaf with the `j`-th
+    /// private hand, multiplied by the probabilities of the chance events. `result[i]` must be set
+    /// to the sum of `opponent_reach[j] * u(i, j)` over the opponent hands `j` not overlapping with
+    /// the `i`-th private hand, where `u(i, j)` is the payoff of the player with the `i`-th hand
+    /// in the unit of [`LeafInfo::payoff_win`]. `result` is zero-initialized, and the elements of
+    /// the hands overlapping with the board must be left zero.
+    fn evaluate(&self, info: &LeafInfo, opponent_reach: &[f32], result: &mut [f32]);
+}
+
+/// A leaf of a depth-limited game tree, passed to [`LeafEvaluator::evaluate`].
+pub struct LeafInfo<'a> {
+    game: &'a PostFlopGame,
+    node: &'a PostFlopNode,
+    player: usize,
+    payoff: (f64, f64),
+}
+
+/// A leaf evaluator that gives each player a fixed fraction of their showdown equity.
+///
+/// Each hand of the player realizes `factor * equity` of the pot (capped to the whole pot), where
+/// `equity` is the all-in equity against the opponent's range at the leaf. With the factors of
+/// `1.0` (default), the leaf is evaluated as if the players checked down to the river.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EquityRealization {
+    /// Equity realization factor of each player (OOP, IP). Must be non-negative.
+    pub factors: [f64; 2],
+}
+
+impl Default for EquityRealization {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            factors: [1.0, 1.0],
+        }
+    }
+}
+
+impl LeafEvaluator for EquityRealization {
+    fn evaluate(&self, info: &LeafInfo, opponent_reach: &[f32], result: &mut [f32]) {
+        let factor = self.factors[info.player()];
+        let equity = info.showdown_equity(opponent_reach);
+        let reach = info.opponent_reach_sum(opponent_reach);
+
+        for ((r, &eq), &reach) in result.iter_mut().zip(&equity).zip(&reach) {
+            if reach > 0.0 {
+                let share = (factor * eq).clamp(0.0, reach);
+                *r = (share * info.payoff_win() + (reach - share) * info.payoff_lose()) as f32;
+            }
+        }
+    }
+}
+
+impl<'a> LeafInfo<'a> {
+    /// Returns the player to be evaluated.
+    #[inline]
+    pub fn player(&self) -> usize {
+        self.player
+    }
+
+    /// Returns the board cards dealt at the leaf (the flop, and the turn if dealt).
+    ///
+    /// Suit-isomorphic turn cards share a leaf, and the representative card is returned.
+    #[inline]
+    pub fn board(&self) -> Vec<u8> {
+        let mut board = self.game.card_config.flop.to_vec();
+        if self.node.turn != NOT_DEALT {
+            board.push(self.node.turn);
+        }
+        board
+    }
+
+    /// Returns the pot size at the leaf.
+    #[inline]
+    pub fn pot(&self) -> i32 {
+        self.game.tree_config.starting_pot + 2 * self.node.amount
+    }
+
+    /// Returns the remaining effective stack at the leaf.
+    #[inline]
+    pub fn remaining_stack(&self) -> i32 {
+        self.game.tree_config.effective_stack - self.node.amount
+    }
+
+    /// Returns the payoff of the player when winning the pot at the leaf.
+    ///
+    /// The rake and the payoff model of the game are taken into account.
+    #[inline]
+    pub fn payoff_win(&self) -> f64 {
+        self.payoff.0
+    }
+
+    /// Returns the payoff of the player when losing the pot at the leaf.
+    #[inline]
+    pub fn payoff_lose(&self) -> f64 {
+        self.payoff.1
+    }
+
+    /// Returns the private hands of the given player.
+    #[inline]
+    pub fn private_cards(&self, player: usize) -> &[(u8, u8)] {
+        &self.game.private_cards[player]
+    }
+
+    /// Returns the sum of `opponent_reach` over the opponent hands not overlapping with each
+    /// private hand of the player.
+    pub fn opponent_reach_sum(&self, opponent_reach: &[f32]) -> Vec<f64> {
+        let game = self.game;
+        let player = self.player;
+        let valid_indices = if self.node.turn != NOT_DEALT {
+            &game.valid_indices_turn[self.node.turn as usize]
+        } else {
+            &game.valid_indices_flop
+        };
+
+        let opponent_cards = &game.private_cards[player ^ 1];
+        let mut reach_sum = 0.0;
+        let mut reach_minus = [0.0; 52];
+
+        for &j in &valid_indices[player ^ 1] {
+            let reach = opponent_reach[j as usize] as f64;
+            let (c1, c2) = opponent_cards[j as usize];
+            reach_sum += reach;
+            reach_minus[c1 as usize] += reach;
+            reach_minus[c2 as usize] += reach;
+        }
+
+        let mut ret = vec![0.0; game.private_cards[player].len()];
+        for &i in &valid_indices[player] {
+            let (c1, c2) = game.private_cards[player][i as usize];
+            let same_i = game.same_hand_index[player][i as usize];
+            let reach_same = if same_i == u16::MAX {
+                0.0
+            } else {
+                opponent_reach[same_i as usize] as f64
+            };
+            // inclusion-exclusion principle
+            ret[i as usize] =
+                reach_sum + reach_same - reach_minus[c1 as usize] - reach_minus[c2 as usize];
+        }
+
+        ret
+    }
+
+    /// Returns the sum of `opponent_reach` weighted by the all-in equity of each private hand of
+    /// the player against each opponent hand, over the runouts to the river.
+    ///
+    /// Dividing by [`opponent_reach_sum`] gives the equity of each hand against the opponent's
+    /// range.
+    ///
+    /// **Time complexity:** *O*(#(runouts) * (#(OOP private hands) + #(IP private hands))).
+    ///
+    /// [`opponent_reach_sum`]: #method.opponent_reach_sum
+    pub fn showdown_equity(&self, opponent_reach: &[f32]) -> Vec<f64> {
+        let game = self.game;
+        let player = self.player;
+        let mut ret = vec![0.0; game.private_cards[player].len()];
+
+        // `equity_internal` accumulates the equity minus 0.5
+        if self.node.turn != NOT_DEALT {
+            for river in 0..52 {
+                if river != self.node.turn {
+                    let amount = 0.5 / 44.0;
+                    game.equity_internal(
+                        &mut ret,
+                        player,
+                        self.node.turn,
+                        river,
+                        amount,
+                        opponent_reach,
+                    );
+                }
+            }
+        } else {
+            for turn in 0..52 {
+                for river in turn + 1..52 {
+                    let amount = 1.0 / (45.0 * 44.0);
+                    game.equity_internal(&mut ret, player, turn, river, amount, opponent_reach);
+                }
+            }
+        }
+
+        let reach = self.opponent_reach_sum(opponent_reach);
+        for (r, reach) in ret.iter_mut().zip(reach) {
+            *r += 0.5 * reach;
+        }
+
+        ret
+    }
+}
+
+impl PostFlopGame {
+    /// Sets the evaluator of the leaves at the depth limit (see [`TreeConfig::depth_limit`]). The
+    /// solved result will be invalid.
+    ///
+    /// If not set, [`EquityRealization::default`] is used, i.e., the leaves are evaluated as if
+    /// the players checked down to the river. The evaluator is not saved to a file.
+    #[inline]
+    pub fn set_leaf_evaluator(&mut self, evaluator: Arc<dyn LeafEvaluator>) {
+        self.leaf_evaluator = Some(evaluator);
+    }
+
+    /// Obtains the leaf evaluator (`None` means the default [`EquityRealization`]).
+    #[inline]
+    pub fn leaf_evaluator(&self) -> Option<&Arc<dyn LeafEvaluator>> {
+        self.leaf_evaluator.as_ref()
+    }
+
+    /// Computes the counterfactual values of a leaf at the depth limit.
+    pub(super) fn evaluate_leaf(
+        &self,
+        result: &mut [MaybeUninit<f32>],
+        node: &PostFlopNode,
+        player: usize,
+        cfreach: &[f32],
+    ) {
+        result.iter_mut().for_each(|v| {
+            v.write(0.0);
+        });
+
+        let result = unsafe { &mut *(result as *mut _ as *mut [f32]) };
+
+        let pot = (self.tree_config.starting_pot + 2 * node.amount) as f64;
+        let rake = (pot * self.tree_config.rake_rate).min(self.tree_config.rake_cap);
+        let (amount_win, amount_lose) = self.payoff_amounts(player, 0.5 * pot, rake);
+
+        let info = LeafInfo {
+            game: self,
+            node,
+            player,
+            payoff: (
+                amount_win * self.num_combinations,
+                amount_lose * self.num_combinations,
+            ),
+        };
+
+        match &self.leaf_evaluator {
+            Some(evaluator) => evaluator.evaluate(&info, cfreach, result),
+            None => EquityRealization::default().evaluate(&info, cfreach, result),
+        }
+
+        let scale = (1.0 / self.num_combinations) as f32;
+        result.iter_mut().for_each(|v| *v *= scale);
+    }
+}
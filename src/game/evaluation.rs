@@ -1,140 +1,97 @@
 use super::*;
 use crate::sliceop::*;
 use std::mem::MaybeUninit;
-use std::cmp::Ordering;
-use std::thread;
-use std::time::Duration;
-use serde::Deserialize;
-use std::fs::File;
-use std::io::BufReader;
-use once_cell::sync::Lazy;
-
-static UTILITY_FILE: Lazy<UtilityFile> = Lazy::new(|| {
-    let utility_file = UtilityFile::new("C:\\Users\\Nima\\OneDrive\\Desktop\\ICM\\state.json");
-    println!("UTILITY_FILE initialized successfully!");
-    utility_file
-});
-
-#[derive(Debug, Deserialize, Clone)]
-struct Player {
-    index: usize,
-    startingStack: f64,
-    remainingStack: f64,
-}
-
-#[derive(Debug, Deserialize, Clone)]
-struct Utility {
-    s: Vec<f64>,
-    u: Vec<f64>,
-}
 
-#[derive(Debug, Deserialize, Clone)]
-struct UtilityFile {
-    formatType: String,
-    formatVersion: String,
-    pot: f64,
-    #[serde(rename = "bigblind")]
-    bigblind: f64,
-    utilities: Vec<Utility>,
-    players: Vec<Player>,
+#[inline]
+fn min(x: f64, y: f64) -> f64 {
+    if x < y {
+        x
+    } else {
+        y
+    }
 }
 
-impl UtilityFile {
-    pub fn new(file_path: &str) -> Self {
-        let file = File::open(file_path).unwrap();
-        let reader = BufReader::new(file);
-        let mut utility_file: UtilityFile = serde_json::from_reader(reader).unwrap();
-
-        // Normalize 's' values
-        let bigblind = utility_file.bigblind;
-        for utility in &mut utility_file.utilities {
-            for stack in &mut utility.s {
-                *stack = (*stack / bigblind) * 100.0;
+impl PostFlopGame {
+    /// Returns the payoffs of winning and losing the pot, divided by the number of combinations.
+    pub(super) fn payoff_amounts(&self, player: usize, half_pot: f64, rake: f64) -> (f64, f64) {
+        let icm = match &self.payoff_model {
+            Some(PayoffModel::ChipEv) => {
+                let amount_win = (half_pot - rake) / self.num_combinations;
+                let amount_lose = -half_pot / self.num_combinations;
+                return (amount_win, amount_lose);
             }
-        }
+            Some(PayoffModel::Icm(icm)) => icm,
+            None => match &*DEFAULT_ICM_UTILITY {
+                Ok(icm) => icm,
+                Err(e) => panic!("{e}"),
+            },
+        };
+
+        let starting_value = icm.starting_value(player);
+        let starting_stack = icm.starting_stack(player);
+        let amount_win = (icm.lookup(starting_stack + half_pot, player) - starting_value)
+            / self.num_combinations;
+        let amount_lose = -(starting_value - icm.lookup(starting_stack - half_pot, player))
+            / self.num_combinations;
+        (amount_win, amount_lose)
+    }
 
-        // Normalize startingStack
-        for player in &mut utility_file.players {
-            player.startingStack = (player.startingStack / bigblind) * 100.0;
-        }
+    /// Computes the sum of the counterfactual reach probabilities of the opponent hands that do
+    /// not conflict with each hand of `player`, scaled like the payoffs.
+    pub(super) fn reach_sums_internal(
+        &self,
+        result: &mut [f32],
+        node: &PostFlopNode,
+        player: usize,
+        cfreach: &[f32],
+    ) {
+        let player_cards = &self.private_cards[player];
+        let opponent_cards = &self.private_cards[player ^ 1];
 
-        utility_file
-    }
+        let mut cfreach_sum = 0.0;
+        let mut cfreach_minus = [0.0; 52];
 
-    pub fn lookup(&self, value: f64, player_id: usize) -> f64 {
-        let normalized_value = value;
-    
-        let closest_utils = self.utilities.iter()
-            .filter(|u| u.s.get(player_id).is_some())
-            .min_by(|u1, u2| {
-                let stack1 = u1.s[player_id];
-                let stack2 = u2.s[player_id];
-                let diff1 = (stack1 - normalized_value).abs();
-                let diff2 = (stack2 - normalized_value).abs();
-                diff1.partial_cmp(&diff2).unwrap_or(std::cmp::Ordering::Equal)
-            });
-    
-        match closest_utils {
-            Some(closest) => {
-                let closest_stack = closest.s[player_id];
-                if closest_stack <= normalized_value {
-                    // If closest stack is less than or equal to normalized value, interpolate between it and the next one
-                    let next = self.utilities.iter().find(|u| u.s[player_id] > closest_stack);
-                    match next {
-                        Some(next) => {
-                            let slope = (next.u[player_id] - closest.u[player_id]) / (next.s[player_id] - closest.s[player_id]);
-                            closest.u[player_id] + slope * (normalized_value - closest.s[player_id])
-                        },
-                        None => closest.u[player_id], // If there's no next stack, use the utility of the closest stack
-                    }
-                } else {
-                    // If closest stack is greater than normalized value, interpolate between it and the previous one
-                    let prev = self.utilities.iter().rev().find(|u| u.s[player_id] < closest_stack);
-                    match prev {
-                        Some(prev) => {
-                            let slope = (closest.u[player_id] - prev.u[player_id]) / (closest.s[player_id] - prev.s[player_id]);
-                            prev.u[player_id] + slope * (normalized_value - prev.s[player_id])
-                        },
-                        None => closest.u[player_id], // If there's no previous stack, use the utility of the closest stack
-                    }
-                }
-            },
-            None => 0.0, // If there's no closest utility, return a default value (here 0.0)
+        result.fill(0.0);
+
+        let valid_indices = if node.river != NOT_DEALT {
+            &self.valid_indices_river[card_pair_to_index(node.turn, node.river)]
+        } else if node.turn != NOT_DEALT {
+            &self.valid_indices_turn[node.turn as usize]
+        } else {
+            &self.valid_indices_flop
+        };
+
+        for &i in &valid_indices[player ^ 1] {
+            let cfreach_i = cfreach[i as usize] as f64;
+            if cfreach_i != 0.0 {
+                let (c1, c2) = opponent_cards[i as usize];
+                cfreach_sum += cfreach_i;
+                cfreach_minus[c1 as usize] += cfreach_i;
+                cfreach_minus[c2 as usize] += cfreach_i;
+            }
         }
-    }
-}
-fn get_starting_stack(player_index: usize) -> f64 {
-    let utility_file = &*UTILITY_FILE;
-    
-    // Check if player_index is valid.
-    //if player_index >= utility_file.players.len() {
-        //panic!("Player index out of bounds! Index: {}", player_index);
-    //}
-    utility_file.players[player_index].startingStack
-}
-
-fn get_starting_val(player_index: usize) -> f64 {
-    let utility_file = &*UTILITY_FILE;
-    
-    // Check if player_index is valid.
-    //if player_index >= utility_file.players.len() {
-        //panic!("Player index out of bounds! Index: {}", player_index);
-    //}
-    let postflop_start = utility_file.players[player_index].startingStack;
-    utility_file.lookup(postflop_start,player_index)
-}
 
+        if cfreach_sum == 0.0 {
+            return;
+        }
 
-#[inline]
-fn min(x: f64, y: f64) -> f64 {
-    if x < y {
-        x
-    } else {
-        y
+        let same_hand_index = &self.same_hand_index[player];
+        for &i in &valid_indices[player] {
+            let (c1, c2) = player_cards[i as usize];
+            let same_i = same_hand_index[i as usize];
+            let cfreach_same = if same_i == u16::MAX {
+                0.0
+            } else {
+                cfreach[same_i as usize] as f64
+            };
+            // inclusion-exclusion principle
+            let cfreach = cfreach_sum + cfreach_same
+                - cfreach_minus[c1 as usize]
+                - cfreach_minus[c2 as usize];
+            result[i as usize] = (cfreach / self.num_combinations) as f32;
+        }
     }
-}
 
-impl PostFlopGame {
     pub(super) fn evaluate_internal(
         &self,
         result: &mut [MaybeUninit<f32>],
@@ -142,16 +99,10 @@ impl PostFlopGame {
         player: usize,
         cfreach: &[f32],
     ) {
-       
-        let utility_file = &*UTILITY_FILE;
         let pot = (self.tree_config.starting_pot + 2 * node.amount) as f64;
         let half_pot = 0.5 * pot;
-        let original_icm_val =  get_starting_val(player.into());
-        let starting_stack =get_starting_stack(player.into());
         let rake = min(pot * self.tree_config.rake_rate, self.tree_config.rake_cap);
-        let mut amount_lose = -(original_icm_val - utility_file.lookup(starting_stack - half_pot,player.into())) / self.num_combinations;
-        let amount_win = (utility_file.lookup(starting_stack + half_pot,player.into()) - original_icm_val) / self.num_combinations;
-
+        let (amount_win, amount_lose) = self.payoff_amounts(player, half_pot, rake);
 
         let player_cards = &self.private_cards[player];
         let opponent_cards = &self.private_cards[player ^ 1];
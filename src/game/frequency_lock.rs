@@ -0,0 +1,304 @@
+use super::*;
+use crate::interface::*;
+
+/// A lock on the aggregate action frequencies of a node.
+pub(super) struct FrequencyLock {
+    // target frequency of each action (negative for the actions that are not constrained)
+    targets: Vec<f32>,
+
+    // multiplier of each action, carried over between iterations
+    multipliers: MutexLike<Vec<f32>>,
+}
+
+/// Small probability added to every action so that the hands not taking an action can be moved
+/// to that action.
+const FREQUENCY_LOCK_EPSILON: f32 = 1e-3;
+
+/// Number of matrix scaling steps per application.
+const FREQUENCY_LOCK_NUM_STEPS: usize = 10;
+
+impl FrequencyLock {
+    /// Returns the group of each action; the unconstrained actions share the last group.
+    fn groups(&self) -> impl Iterator<Item = usize> + '_ {
+        let num_actions = self.targets.len();
+        self.targets
+            .iter()
+            .enumerate()
+            .map(move |(i, &t)| if t < 0.0 { num_actions } else { i })
+    }
+
+    /// Returns the target frequency of each group.
+    fn group_targets(&self) -> Vec<f32> {
+        let num_actions = self.targets.len();
+        let mut ret = vec![0.0; num_actions + 1];
+        for (i, &t) in self.targets.iter().enumerate() {
+            if t >= 0.0 {
+                ret[i] = t;
+            }
+        }
+        if self.targets.iter().any(|&t| t < 0.0) {
+            let sum = ret.iter().sum::<f32>();
+            ret[num_actions] = (1.0 - sum).max(0.0);
+        }
+        ret
+    }
+
+    /// Adjusts `strategy` so that the frequencies weighted by `reach` meet the targets.
+    ///
+    /// If `reach` is given, the multipliers are updated by matrix scaling (iterative proportional
+    /// fitting); otherwise, the multipliers of the last update are used.
+    fn apply(&self, strategy: &mut [f32], reach: Option<&[f32]>) {
+        let num_actions = self.targets.len();
+        let num_hands = strategy.len() / num_actions;
+        let groups = self.groups().collect::<Vec<_>>();
+        let mut multipliers = self.multipliers.lock();
+
+        strategy
+            .iter_mut()
+            .for_each(|x| *x += FREQUENCY_LOCK_EPSILON);
+
+        if let Some(reach) = reach {
+            let group_targets = self.group_targets();
+            let reach_sum = reach.iter().map(|&w| w as f64).sum::<f64>();
+            if reach_sum > 0.0 {
+                for _ in 0..FREQUENCY_LOCK_NUM_STEPS {
+                    let mut frequencies = vec![0.0; num_actions + 1];
+                    for hand in 0..num_hands {
+                        if reach[hand] == 0.0 {
+                            continue;
+                        }
+                        let mut denom = 0.0;
+                        for action in 0..num_actions {
+                            denom +=
+                                strategy[action * num_hands + hand] * multipliers[groups[action]];
+                        }
+                        if denom > 0.0 {
+                            let coef = reach[hand] as f64 / (denom as f64 * reach_sum);
+                            for action in 0..num_actions {
+                                let x = strategy[action * num_hands + hand]
+                                    * multipliers[groups[action]];
+                                frequencies[groups[action]] += x as f64 * coef;
+                            }
+                        }
+                    }
+
+                    for (m, (&f, &t)) in multipliers
+                        .iter_mut()
+                        .zip(frequencies.iter().zip(&group_targets))
+                    {
+                        *m = if t == 0.0 {
+                            0.0
+                        } else if f > 0.0 {
+                            *m * (t as f64 / f) as f32
+                        } else {
+                            *m
+                        };
+                    }
+
+                    // keep the multipliers in a reasonable range
+                    let max_multiplier = multipliers.iter().fold(0.0f32, |m, &x| m.max(x));
+                    if max_multiplier > 0.0 {
+                        multipliers.iter_mut().for_each(|x| *x /= max_multiplier);
+                    }
+                }
+            }
+        }
+
+        for hand in 0..num_hands {
+            let mut denom = 0.0;
+            for action in 0..num_actions {
+                let x = &mut strategy[action * num_hands + hand];
+                *x *= multipliers[groups[action]];
+                denom += *x;
+            }
+            for action in 0..num_actions {
+                let x = &mut strategy[action * num_hands + hand];
+                *x = if denom > 0.0 {
+                    *x / denom
+                } else {
+                    1.0 / num_actions as f32
+                };
+            }
+        }
+    }
+}
+
+impl PostFlopGame {
+    /// Locks the aggregate action frequencies of the current node.
+    ///
+    /// Unlike [`lock_current_strategy`], only the frequency of each action over the range of the
+    /// current player is constrained, and the solver chooses which hands take each action. The
+    /// `i`-th element of `frequencies` is the target frequency of the `i`-th action, and a
+    /// negative value leaves the action unconstrained; the unconstrained actions share the
+    /// remaining frequency. For example, `[0.2, -1.0, -1.0]` for `[Check, Bet(33), Bet(75)]` means
+    /// "bets 80% of the time with any size". If no action is unconstrained, the frequencies are
+    /// normalized so that their sum is 1.0.
+    ///
+    /// During solving, the strategy obtained by regret matching is rescaled per action so that
+    /// the frequencies weighted by the reach probabilities of the player meet the targets. The
+    /// constraint is enforced approximately, and the frequencies of the average strategy may
+    /// slightly deviate from the targets. The frequency locks are not saved to a file, but they are
+    /// saved to a checkpoint.
+    ///
+    /// When computing the exploitability, the best-responding player follows the average strategy
+    /// at the frequency-locked nodes instead of searching for the best assignment of hands to
+    /// actions that meets the targets, which would require the reach probabilities of the best
+    /// response itself. Therefore, the exploitability is under-reported in general, and
+    /// `target_exploitability` of [`SolveOptions`] may stop solving earlier than expected.
+    ///
+    /// Panics if the memory is not allocated, the current node is terminal or chance, the
+    /// strategy of the current node is locked, or `frequencies` is invalid.
+    ///
+    /// [`lock_current_strategy`]: #method.lock_current_strategy
+    /// [`SolveOptions`]: crate::SolveOptions
+    pub fn lock_current_frequencies(&mut self, frequencies: &[f32]) {
+        if self.state < State::MemoryAllocated {
+            panic!("Memory is not allocated");
+        }
+
+        if self.is_terminal_node() {
+            panic!("Terminal node is not allowed");
+        }
+
+        if self.is_chance_node() {
+            panic!("Chance node is not allowed");
+        }
+
+        if self.on_demand_river().is_some() {
+            panic!("Locking is not supported in river subtrees solved on demand");
+        }
+
+        let node = self.node();
+        let num_actions = node.num_actions();
+
+        if node.is_locked {
+            panic!("Strategy of the current node is locked");
+        }
+
+        if frequencies.len() != num_actions {
+            panic!("Invalid frequencies length");
+        }
+
+        if frequencies.iter().any(|x| !x.is_finite()) {
+            panic!("Frequencies must be finite");
+        }
+
+        let sum = frequencies.iter().filter(|&&x| x >= 0.0).sum::<f32>();
+        let targets = if frequencies.iter().any(|&x| x < 0.0) {
+            if sum > 1.0 + 1e-4 {
+                panic!("Sum of the constrained frequencies exceeds 1.0");
+            }
+            frequencies.to_vec()
+        } else {
+            if sum <= 0.0 {
+                panic!("Sum of the frequencies must be positive");
+            }
+            frequencies.iter().map(|&x| x / sum).collect()
+        };
+
+        let index = self.node_index(&node);
+        let lock = FrequencyLock {
+            targets,
+            multipliers: MutexLike::new(vec![1.0; num_actions + 1]),
+        };
+
+        self.frequency_locks.insert(index, lock);
+    }
+
+    /// Unlocks the aggregate action frequencies of the current node.
+    #[inline]
+    pub fn unlock_current_frequencies(&mut self) {
+        if self.state < State::MemoryAllocated {
+            panic!("Memory is not allocated");
+        }
+
+        if self.is_terminal_node() {
+            panic!("Terminal node is not allowed");
+        }
+
+        if self.is_chance_node() {
+            panic!("Chance node is not allowed");
+        }
+
+        let index = self.node_index(&self.node());
+        self.frequency_locks.remove(&index);
+    }
+
+    /// Returns the target frequencies of the current node set by [`lock_current_frequencies`].
+    ///
+    /// If the frequencies of the current node are not locked, `None` is returned.
+    ///
+    /// [`lock_current_frequencies`]: #method.lock_current_frequencies
+    #[inline]
+    pub fn current_frequency_lock(&self) -> Option<Vec<f32>> {
+        if self.state < State::MemoryAllocated {
+            panic!("Memory is not allocated");
+        }
+
+        if self.is_terminal_node() || self.is_chance_node() {
+            return None;
+        }
+
+        let index = self.node_index(&self.node());
+        self.frequency_locks.get(&index).map(|l| l.targets.clone())
+    }
+
+    /// Returns the frequency locks as tuples of the node index, the target frequencies, and the
+    /// current multipliers.
+    pub(crate) fn frequency_lock_entries(&self) -> Vec<(usize, Vec<f32>, Vec<f32>)> {
+        self.frequency_locks
+            .iter()
+            .map(|(&index, lock)| (index, lock.targets.clone(), lock.multipliers.lock().clone()))
+            .collect()
+    }
+
+    /// Restores the frequency locks from the entries returned by `frequency_lock_entries`.
+    pub(crate) fn restore_frequency_locks(
+        &mut self,
+        entries: Vec<(usize, Vec<f32>, Vec<f32>)>,
+    ) -> Result<(), String> {
+        self.frequency_locks.clear();
+        for (index, targets, multipliers) in entries {
+            let is_valid = self.node_arena.get(index).is_some_and(|node| {
+                let node = node.lock();
+                !node.is_terminal()
+                    && !node.is_chance()
+                    && targets.len() == node.num_actions()
+                    && multipliers.len() == node.num_actions() + 1
+            });
+
+            if !is_valid {
+                return Err("Invalid frequency lock".to_string());
+            }
+
+            let multipliers = MutexLike::new(multipliers);
+            self.frequency_locks.insert(
+                index,
+                FrequencyLock {
+                    targets,
+                    multipliers,
+                },
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Adjusts the strategy of the given node to meet its frequency lock, if any.
+    #[inline]
+    pub(super) fn apply_frequency_lock_internal(
+        &self,
+        node: &PostFlopNode,
+        strategy: &mut [f32],
+        reach: Option<&[f32]>,
+    ) {
+        if self.frequency_locks.is_empty() {
+            return;
+        }
+
+        let index = self.node_index(node);
+        if let Some(lock) = self.frequency_locks.get(&index) {
+            lock.apply(strategy, reach);
+        }
+    }
+}
@@ -20,6 +20,7 @@ impl PostFlopGame {
 
         self.action_history.clear();
         self.node_history.clear();
+        self.on_demand_river_key = None;
         self.is_normalized_weight_cached = false;
         self.turn = self.card_config.turn;
         self.river = self.card_config.river;
@@ -27,6 +28,9 @@ impl PostFlopGame {
         self.turn_swap = None;
         self.river_swap = None;
         self.total_bet_amount = [0, 0];
+        self.street_bet_base = 0;
+        self.real_total_bet_amount = [0, 0];
+        self.real_street_bet_base = 0;
 
         self.weights[0].copy_from_slice(&self.initial_weights[0]);
         self.weights[1].copy_from_slice(&self.initial_weights[1]);
@@ -77,6 +81,10 @@ impl PostFlopGame {
             panic!("Game is not successfully initialized");
         }
 
+        if let Some(river_game) = self.on_demand_river() {
+            return river_game.is_terminal_node();
+        }
+
         let node = self.node();
         node.is_terminal() || node.amount == self.tree_config.effective_stack
     }
@@ -90,6 +98,10 @@ impl PostFlopGame {
             panic!("Game is not successfully initialized");
         }
 
+        if let Some(river_game) = self.on_demand_river() {
+            return river_game.is_chance_node();
+        }
+
         self.node().is_chance() && !self.is_terminal_node()
     }
 
@@ -106,6 +118,10 @@ impl PostFlopGame {
             panic!("Game is not successfully initialized");
         }
 
+        if let Some(river_game) = self.on_demand_river() {
+            return river_game.available_actions();
+        }
+
         if self.is_terminal_node() {
             Vec::new()
         } else {
@@ -129,7 +145,7 @@ impl PostFlopGame {
             panic!("Game is not successfully initialized");
         }
 
-        if !self.is_chance_node() {
+        if !self.is_chance_node() || self.on_demand_river().is_some() {
             return 0;
         }
 
@@ -236,6 +252,10 @@ impl PostFlopGame {
             panic!("Game is not successfully initialized");
         }
 
+        if let Some(river_game) = self.on_demand_river() {
+            return river_game.current_player();
+        }
+
         self.node().player()
     }
 
@@ -282,21 +302,41 @@ impl PostFlopGame {
             panic!("Terminal node is not allowed");
         }
 
+        let prev_total_bet_amount = self.total_bet_amount;
+
+        // river subtree solved on demand
+        if self.on_demand_river().is_some() {
+            self.play_on_demand_river(action);
+            self.action_history.push(action);
+            self.update_real_bet_amount(prev_total_bet_amount);
+            return;
+        }
+
         // chance node
         if self.is_chance_node() {
             let is_turn = self.turn == NOT_DEALT;
-            if self.storage_mode == BoardState::Flop
-                || (!is_turn && self.storage_mode == BoardState::Turn)
-            {
-                panic!("Storage mode is not compatible");
-            }
-
             let actual_card = if action == usize::MAX {
                 self.possible_cards().trailing_zeros() as u8
             } else {
                 action as u8
             };
 
+            if !is_turn && self.needs_on_demand_river() {
+                if self.possible_cards() & (1 << actual_card) == 0 {
+                    panic!("Invalid action");
+                }
+                self.enter_on_demand_river(actual_card);
+                self.action_history.push(actual_card as usize);
+                self.update_real_bet_amount(prev_total_bet_amount);
+                return;
+            }
+
+            if self.storage_mode == BoardState::Flop
+                || (!is_turn && self.storage_mode == BoardState::Turn)
+            {
+                panic!("Storage mode is not compatible");
+            }
+
             // swap the suit if swapping was performed in turn
             let action_card = if let Some((suit1, suit2)) = self.turn_swapped_suit {
                 if actual_card & 3 == suit1 {
@@ -423,6 +463,7 @@ impl PostFlopGame {
 
         self.action_history.push(action);
         self.is_normalized_weight_cached = false;
+        self.update_real_bet_amount(prev_total_bet_amount);
     }
 
     /// Computes the normalized weights and caches them.
@@ -447,6 +488,13 @@ impl PostFlopGame {
             return;
         }
 
+        // river subtree solved on demand
+        if self.on_demand_river().is_some() {
+            self.cache_on_demand_river_weights();
+            self.is_normalized_weight_cached = true;
+            return;
+        }
+
         // no bunching
         if self.bunching_num_dead_cards == 0 {
             let mut board_mask: u64 = 0;
@@ -610,22 +658,29 @@ impl PostFlopGame {
             panic!("Normalized weights are not cached");
         }
 
+        if let Some(river_game) = self.on_demand_river() {
+            return self.map_on_demand_river_hands(&river_game.equity(player), player);
+        }
+
         let num_hands = self.num_private_hands(player);
 
         let tmp = if self.bunching_num_dead_cards == 0 {
             let mut tmp = vec![0.0; num_hands];
+            let weights = &self.weights[player ^ 1];
             if self.river != NOT_DEALT {
-                self.equity_internal(&mut tmp, player, self.turn, self.river, 0.5);
+                self.equity_internal(&mut tmp, player, self.turn, self.river, 0.5, weights);
             } else if self.turn != NOT_DEALT {
                 for river in 0..52 {
                     if self.turn != river {
-                        self.equity_internal(&mut tmp, player, self.turn, river, 0.5 / 44.0);
+                        let amount = 0.5 / 44.0;
+                        self.equity_internal(&mut tmp, player, self.turn, river, amount, weights);
                     }
                 }
             } else {
                 for turn in 0..52 {
                     for river in turn + 1..52 {
-                        self.equity_internal(&mut tmp, player, turn, river, 1.0 / (45.0 * 44.0));
+                        let amount = 1.0 / (45.0 * 44.0);
+                        self.equity_internal(&mut tmp, player, turn, river, amount, weights);
                     }
                 }
             }
@@ -669,6 +724,10 @@ impl PostFlopGame {
             panic!("Normalized weights are not cached");
         }
 
+        if let Some(river_game) = self.on_demand_river() {
+            return self.map_on_demand_river_hands(&river_game.expected_values(player), player);
+        }
+
         let expected_value_detail = self.expected_values_detail(player);
 
         if self.is_terminal_node() || self.is_chance_node() || self.current_player() != player {
@@ -721,6 +780,11 @@ impl PostFlopGame {
             panic!("Normalized weights are not cached");
         }
 
+        if let Some(river_game) = self.on_demand_river() {
+            let detail = river_game.expected_values_detail(player);
+            return self.map_on_demand_river_hands(&detail, player);
+        }
+
         let node = self.node();
         let num_hands = self.num_private_hands(player);
 
@@ -827,6 +891,11 @@ impl PostFlopGame {
             panic!("Chance node is not allowed");
         }
 
+        if let Some(river_game) = self.on_demand_river() {
+            let player = river_game.current_player();
+            return self.map_on_demand_river_hands(&river_game.strategy(), player);
+        }
+
         let node = self.node();
         let player = self.current_player();
         let num_actions = node.num_actions();
@@ -878,11 +947,19 @@ impl PostFlopGame {
             panic!("Chance node is not allowed");
         }
 
+        if self.on_demand_river().is_some() {
+            panic!("Locking is not supported in river subtrees solved on demand");
+        }
+
         let mut node = self.node();
         let player = self.current_player();
         let num_actions = node.num_actions();
         let num_hands = self.num_private_hands(player);
 
+        if self.frequency_locks.contains_key(&self.node_index(&node)) {
+            panic!("Frequencies of the current node are locked");
+        }
+
         if strategy.len() != num_actions * num_hands {
             panic!("Invalid strategy length");
         }
@@ -933,6 +1010,15 @@ impl PostFlopGame {
             panic!("Chance node is not allowed");
         }
 
+        if self.on_demand_river().is_some() {
+            panic!("Locking is not supported in river subtrees solved on demand");
+        }
+
+        if !self.lock_rules.is_empty() {
+            let line = self.current_line();
+            self.lock_rules.retain(|(l, _)| *l != line);
+        }
+
         let mut node = self.node();
         if !node.is_locked {
             return;
@@ -966,6 +1052,10 @@ impl PostFlopGame {
             panic!("Chance node is not allowed");
         }
 
+        if self.on_demand_river().is_some() {
+            panic!("Locking is not supported in river subtrees solved on demand");
+        }
+
         let index = self.node_index(&self.node());
         self.locking_strategy.get(&index).map(|s| {
             let mut ret = s.clone();
@@ -980,7 +1070,7 @@ impl PostFlopGame {
 
     /// Returns the reference to the current node.
     #[inline]
-    fn node(&self) -> MutexGuardLike<PostFlopNode> {
+    pub(super) fn node(&self) -> MutexGuardLike<PostFlopNode> {
         self.node_arena[self.node_history.last().cloned().unwrap_or(0)].lock()
     }
 
@@ -1092,7 +1182,15 @@ impl PostFlopGame {
     }
 
     /// Internal method for calculating the equity.
-    fn equity_internal(&self, result: &mut [f64], player: usize, turn: u8, river: u8, amount: f64) {
+    pub(super) fn equity_internal(
+        &self,
+        result: &mut [f64],
+        player: usize,
+        turn: u8,
+        river: u8,
+        amount: f64,
+        opponent_weights: &[f32],
+    ) {
         let pair_index = card_pair_to_index(turn, river);
         let hand_strength = &self.hand_strength[pair_index];
         let player_strength = &hand_strength[player];
@@ -1108,7 +1206,6 @@ impl PostFlopGame {
         let player_cards = &self.private_cards[player];
         let opponent_cards = &self.private_cards[player ^ 1];
 
-        let opponent_weights = &self.weights[player ^ 1];
         let mut weight_sum = 0.0;
         let mut weight_minus = [0.0; 52];
 
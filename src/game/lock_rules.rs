@@ -0,0 +1,200 @@
+use super::*;
+use crate::hand::*;
+use crate::interface::*;
+use crate::range::*;
+
+#[cfg(feature = "bincode")]
+use bincode::{Decode, Encode};
+
+/// A rule of [`PostFlopGame::lock_current_strategy_by_rules`].
+///
+/// A hand matches the rule if it is contained in `range` (with a positive weight) and its made-hand
+/// category on the current board is one of `categories`.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "bincode", derive(Decode, Encode))]
+pub struct LockRule {
+    /// Hands matched by the rule (`None` for all hands).
+    pub range: Option<Range>,
+
+    /// Made-hand categories matched by the rule (empty for all categories).
+    pub categories: Vec<HandCategory>,
+
+    /// Frequency of each action of the node. Must be non-negative and not all zero.
+    pub frequencies: Vec<f32>,
+}
+
+impl LockRule {
+    /// Returns whether the given hand matches the rule on the given board.
+    #[inline]
+    fn matches(&self, board: &[u8], hole: (u8, u8)) -> bool {
+        let in_range = match &self.range {
+            Some(range) => range.get_weight_by_cards(hole.0, hole.1) > 0.0,
+            None => true,
+        };
+        in_range
+            && (self.categories.is_empty() || self.categories.contains(&hand_category(board, hole)))
+    }
+}
+
+impl PostFlopGame {
+    /// Locks the strategy of the current node by rules on hand ranges and made-hand categories.
+    ///
+    /// Each hand of the current player takes the frequencies of the first rule it matches, and the
+    /// hands that match no rule (or conflict with the board) are left unlocked. For example, "all
+    /// sets and two pair always raise; AK high always check" can be expressed by a rule with the
+    /// categories `[ThreeOfAKind, TwoPair]` followed by a rule with the range `"AK"` and the
+    /// category `[HighCard]`. The resulting matrix is passed to [`lock_current_strategy`].
+    ///
+    /// The rules are stored together with the line of actions to the current node and are applied
+    /// again by [`allocate_memory`], so they survive rebuilding the game with [`update_config`]
+    /// (e.g., with different ranges or a different board). On re-application, the lines that no
+    /// longer exist and the rules whose number of actions no longer matches are skipped.
+    /// [`unlock_current_strategy`] removes the stored rules of the current node.
+    ///
+    /// Panics if the memory is not allocated, the current node is terminal or chance, or the
+    /// length of the frequencies of a rule is not the number of actions.
+    ///
+    /// [`lock_current_strategy`]: #method.lock_current_strategy
+    /// [`allocate_memory`]: #method.allocate_memory
+    /// [`update_config`]: #method.update_config
+    /// [`unlock_current_strategy`]: #method.unlock_current_strategy
+    pub fn lock_current_strategy_by_rules(&mut self, rules: &[LockRule]) {
+        if self.state < State::MemoryAllocated {
+            panic!("Memory is not allocated");
+        }
+
+        if self.is_terminal_node() {
+            panic!("Terminal node is not allowed");
+        }
+
+        if self.is_chance_node() {
+            panic!("Chance node is not allowed");
+        }
+
+        let num_actions = self.node().num_actions();
+        for rule in rules {
+            if rule.frequencies.len() != num_actions {
+                panic!("Invalid frequencies length");
+            }
+
+            let is_invalid = |x: f32| x.is_nan() || x < 0.0;
+            if rule.frequencies.iter().any(|&x| is_invalid(x))
+                || rule.frequencies.iter().all(|&x| x == 0.0)
+            {
+                panic!("Frequencies must be non-negative and not all zero");
+            }
+        }
+
+        self.lock_with_rules(rules);
+
+        let line = self.current_line();
+        self.lock_rules.retain(|(l, _)| *l != line);
+        self.lock_rules.push((line, rules.to_vec()));
+    }
+
+    /// Returns the stored lock rules with the lines of actions to their nodes.
+    #[inline]
+    pub fn lock_rules(&self) -> &[(Vec<Action>, Vec<LockRule>)] {
+        &self.lock_rules
+    }
+
+    /// Restores the stored lock rules without applying them (the locked strategies are restored
+    /// separately).
+    #[inline]
+    pub(crate) fn restore_lock_rules(&mut self, lock_rules: Vec<(Vec<Action>, Vec<LockRule>)>) {
+        self.lock_rules = lock_rules;
+    }
+
+    /// Returns the line of actions from the root to the current node, with the actual cards for
+    /// the chance nodes.
+    pub(super) fn current_line(&self) -> Vec<Action> {
+        let mut parent_index = 0;
+        let mut line = Vec::with_capacity(self.action_history.len());
+        for (&action, &node_index) in self.action_history.iter().zip(&self.node_history) {
+            if self.node_arena[parent_index].lock().is_chance() {
+                line.push(Action::Chance(action as u8));
+            } else {
+                line.push(self.node_arena[node_index].lock().prev_action);
+            }
+            parent_index = node_index;
+        }
+        line
+    }
+
+    /// Moves the current node to the given line of actions. Returns `false` if the line is not
+    /// found, in which case the current node is undefined.
+    pub(super) fn apply_line(&mut self, line: &[Action]) -> bool {
+        self.back_to_root();
+        for &action in line {
+            if self.is_terminal_node() {
+                return false;
+            }
+
+            if self.is_chance_node() {
+                match action {
+                    Action::Chance(card) if self.possible_cards() & (1 << card) != 0 => {
+                        self.play(card as usize);
+                    }
+                    _ => return false,
+                }
+            } else {
+                match self.available_actions().iter().position(|&a| a == action) {
+                    Some(index) => self.play(index),
+                    None => return false,
+                }
+            }
+        }
+        true
+    }
+
+    /// Applies the stored lock rules after the memory is allocated.
+    pub(super) fn apply_lock_rules(&mut self) {
+        if self.lock_rules.is_empty() {
+            return;
+        }
+
+        let history = self.action_history.clone();
+        for (line, rules) in self.lock_rules.clone() {
+            if !self.apply_line(&line) || self.is_terminal_node() || self.is_chance_node() {
+                continue;
+            }
+
+            let index = self.node_index(&self.node());
+            if self.frequency_locks.contains_key(&index) {
+                continue;
+            }
+
+            let num_actions = self.node().num_actions();
+            let rules = rules
+                .into_iter()
+                .filter(|rule| rule.frequencies.len() == num_actions)
+                .collect::<Vec<_>>();
+            self.lock_with_rules(&rules);
+        }
+        self.apply_history(&history);
+    }
+
+    /// Translates the rules into the locking matrix of the current node and locks it.
+    fn lock_with_rules(&mut self, rules: &[LockRule]) {
+        let player = self.current_player();
+        let num_actions = self.node().num_actions();
+        let num_hands = self.num_private_hands(player);
+        let board = self.current_board();
+        let board_mask: u64 = board.iter().map(|&card| 1 << card).sum();
+
+        let mut strategy = vec![-1.0; num_actions * num_hands];
+        for (hand, &(c1, c2)) in self.private_cards[player].iter().enumerate() {
+            if board_mask & ((1 << c1) | (1 << c2)) != 0 {
+                continue;
+            }
+
+            if let Some(rule) = rules.iter().find(|rule| rule.matches(&board, (c1, c2))) {
+                for (action, &freq) in rule.frequencies.iter().enumerate() {
+                    strategy[action * num_hands + hand] = freq;
+                }
+            }
+        }
+
+        self.lock_current_strategy(&strategy);
+    }
+}
@@ -0,0 +1,227 @@
+use super::*;
+use crate::interface::*;
+use crate::range::*;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+/// A locked node of [`LockSet`].
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct NodeLock {
+    /// Line of actions from the root, separated by `/` (e.g., `"Check/Bet(50)/Qc"`; the root is
+    /// `""`). See the [`Display`] implementation of [`Action`] for the format of each action.
+    ///
+    /// [`Display`]: std::fmt::Display
+    pub history: String,
+
+    /// Available actions of the node, used to check that the node is compatible.
+    pub actions: Vec<String>,
+
+    /// Locked frequencies of the actions keyed by hole cards (e.g., `"AsKh"`). The hands that are
+    /// not listed are unlocked.
+    pub strategy: BTreeMap<String, Vec<f32>>,
+}
+
+/// A portable set of strategy locks, keyed by action histories and hole cards.
+///
+/// Obtained by [`PostFlopGame::export_locks`] and applied by [`PostFlopGame::import_locks`], so
+/// that the locks can be reused in another game with a compatible tree. With serde, the set is
+/// represented as a JSON object with a `locks` array.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct LockSet {
+    /// Locked nodes in depth-first order.
+    pub locks: Vec<NodeLock>,
+}
+
+impl LockSet {
+    /// Saves the lock set to a JSON file.
+    pub fn save_to_file<P: AsRef<Path>>(&self, path: P) -> Result<(), String> {
+        let json = serde_json::to_string_pretty(self)
+            .map_err(|e| format!("Failed to serialize locks: {e}"))?;
+        fs::write(path, json).map_err(|e| format!("Failed to write locks: {e}"))
+    }
+
+    /// Loads a lock set from a JSON file.
+    pub fn load_from_file<P: AsRef<Path>>(path: P) -> Result<Self, String> {
+        let json = fs::read_to_string(path).map_err(|e| format!("Failed to read locks: {e}"))?;
+        serde_json::from_str(&json).map_err(|e| format!("Failed to parse locks: {e}"))
+    }
+}
+
+impl PostFlopGame {
+    /// Exports all locked strategies of the game (see [`lock_current_strategy`]).
+    ///
+    /// Suit-isomorphic runouts share a node, and only the representative card appears in the
+    /// histories. The frequency locks and the lock rules are not exported.
+    ///
+    /// Panics if the memory is not allocated.
+    ///
+    /// [`lock_current_strategy`]: #method.lock_current_strategy
+    pub fn export_locks(&self) -> LockSet {
+        if self.state < State::MemoryAllocated {
+            panic!("Memory is not allocated");
+        }
+
+        let mut locks = Vec::new();
+        let mut line = Vec::new();
+        self.export_locks_recursive(0, &mut line, &mut locks);
+        LockSet { locks }
+    }
+
+    /// Imports the locks of `lock_set` and locks the corresponding nodes of this game.
+    ///
+    /// Each node is found by its action history, and the locked frequencies are assigned to the
+    /// hands of the current player by hole cards; the hands that are not in the range of this game
+    /// are ignored. A node is skipped if its history does not exist in this game (e.g., a chance
+    /// card that is already dealt), its available actions differ, or its aggregate frequencies are
+    /// locked. Returns the histories of the skipped nodes. The current node is not changed.
+    ///
+    /// Returns an error if the memory is not allocated or `lock_set` is malformed; in that case,
+    /// no node is locked.
+    pub fn import_locks(&mut self, lock_set: &LockSet) -> Result<Vec<String>, String> {
+        if self.state < State::MemoryAllocated {
+            return Err("Memory is not allocated".to_string());
+        }
+
+        let mut parsed = Vec::with_capacity(lock_set.locks.len());
+        for lock in &lock_set.locks {
+            parsed.push(parse_node_lock(lock)?);
+        }
+
+        let history = self.action_history.clone();
+        let mut skipped = Vec::new();
+
+        for (lock, (line, strategy)) in lock_set.locks.iter().zip(parsed) {
+            if !self.apply_line(&line)
+                || self.is_terminal_node()
+                || self.is_chance_node()
+                || self.on_demand_river().is_some()
+                || self
+                    .frequency_locks
+                    .contains_key(&self.node_index(&self.node()))
+            {
+                skipped.push(lock.history.clone());
+                continue;
+            }
+
+            let actions = self.available_actions();
+            if actions.len() != lock.actions.len()
+                || actions
+                    .iter()
+                    .zip(&lock.actions)
+                    .any(|(a, b)| a.to_string() != *b)
+            {
+                skipped.push(lock.history.clone());
+                continue;
+            }
+
+            let player = self.current_player();
+            let num_actions = actions.len();
+            let num_hands = self.num_private_hands(player);
+
+            let mut locking = vec![-1.0; num_actions * num_hands];
+            for (hand, &(c1, c2)) in self.private_cards[player].iter().enumerate() {
+                if let Some(frequencies) = strategy.get(&(c1, c2)) {
+                    for (action, &freq) in frequencies.iter().enumerate() {
+                        locking[action * num_hands + hand] = freq;
+                    }
+                }
+            }
+
+            self.lock_current_strategy(&locking);
+        }
+
+        self.apply_history(&history);
+        Ok(skipped)
+    }
+
+    /// The recursive helper function for exporting the locks.
+    fn export_locks_recursive(
+        &self,
+        node_index: usize,
+        line: &mut Vec<Action>,
+        locks: &mut Vec<NodeLock>,
+    ) {
+        let node = self.node_arena[node_index].lock();
+        if node.is_terminal() {
+            return;
+        }
+
+        if !node.is_chance() && node.is_locked {
+            if let Some(locking) = self.locking_strategy.get(&node_index) {
+                let player = node.player();
+                let num_hands = self.num_private_hands(player);
+                let mut strategy = BTreeMap::new();
+                for (hand, &hole) in self.private_cards[player].iter().enumerate() {
+                    if locking[hand] >= 0.0 {
+                        let frequencies = locking.iter().skip(hand).step_by(num_hands);
+                        strategy.insert(
+                            hole_to_string(hole).unwrap(),
+                            frequencies.cloned().collect(),
+                        );
+                    }
+                }
+
+                locks.push(NodeLock {
+                    history: line_to_string(line),
+                    actions: node
+                        .children()
+                        .iter()
+                        .map(|child| child.lock().prev_action.to_string())
+                        .collect(),
+                    strategy,
+                });
+            }
+        }
+
+        let children_index = node_index + node.children_offset as usize;
+        for (i, child) in node.children().iter().enumerate() {
+            line.push(child.lock().prev_action);
+            self.export_locks_recursive(children_index + i, line, locks);
+            line.pop();
+        }
+    }
+}
+
+/// Formats a line of actions as a history string of [`NodeLock`].
+fn line_to_string(line: &[Action]) -> String {
+    line.iter()
+        .map(|action| action.to_string())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Parses the history and the hole cards of a node lock.
+#[allow(clippy::type_complexity)]
+fn parse_node_lock(lock: &NodeLock) -> Result<(Vec<Action>, BTreeMap<(u8, u8), &[f32]>), String> {
+    let line = if lock.history.is_empty() {
+        Vec::new()
+    } else {
+        lock.history
+            .split('/')
+            .map(|s| s.parse())
+            .collect::<Result<Vec<Action>, _>>()?
+    };
+
+    let mut strategy = BTreeMap::new();
+    for (hand, frequencies) in &lock.strategy {
+        let mut chars = hand.chars();
+        let c1 = card_from_chars(&mut chars)?;
+        let c2 = card_from_chars(&mut chars)?;
+        if chars.next().is_some() || c1 == c2 {
+            return Err(format!("Invalid hole cards: {hand}"));
+        }
+
+        if frequencies.len() != lock.actions.len() {
+            return Err(format!(
+                "Invalid frequencies length of {hand} at \"{}\"",
+                lock.history
+            ));
+        }
+
+        strategy.insert((c1.min(c2), c1.max(c2)), frequencies.as_slice());
+    }
+
+    Ok((line, strategy))
+}
@@ -1,7 +1,19 @@
 mod base;
+mod best_response;
+mod depth_limit;
 mod evaluation;
+mod frequency_lock;
 mod interpreter;
+mod lock_rules;
+mod lock_set;
 mod node;
+mod on_demand;
+mod purification;
+mod replay;
+mod resolve;
+mod transfer;
+mod translation;
+mod warm_start;
 
 #[cfg(feature = "bincode")]
 mod serialization;
@@ -9,10 +21,23 @@ mod serialization;
 #[cfg(test)]
 mod tests;
 
+pub use best_response::*;
+pub use depth_limit::*;
+pub use lock_rules::*;
+pub use lock_set::*;
+pub use purification::*;
+pub use replay::*;
+pub use transfer::*;
+pub use translation::*;
+pub use warm_start::*;
+
 use crate::action_tree::*;
 use crate::card::*;
 use crate::mutex_like::*;
+use crate::payoff::*;
+use crate::solver::SolveOptions;
 use std::collections::BTreeMap;
+use std::sync::Arc;
 
 #[cfg(feature = "bincode")]
 use bincode::{Decode, Encode};
@@ -69,6 +94,12 @@ pub struct PostFlopGame {
     bunching_coef_flop: [Vec<usize>; 2],
     bunching_coef_turn: [Vec<Vec<usize>>; 2],
 
+    // payoff model (`None` uses the default ICM state file)
+    payoff_model: Option<PayoffModel>,
+
+    // leaf evaluator of the depth-limited tree (`None` uses `EquityRealization::default()`)
+    leaf_evaluator: Option<Arc<dyn LeafEvaluator>>,
+
     // store options
     storage_mode: BoardState,
     target_storage_mode: BoardState,
@@ -85,7 +116,15 @@ pub struct PostFlopGame {
     storage2: Vec<u8>,
     storage_ip: Vec<u8>,
     storage_chance: Vec<u8>,
+    storage_raw_regrets: Vec<u8>,
     locking_strategy: BTreeMap<usize, Vec<f32>>,
+    frequency_locks: BTreeMap<usize, frequency_lock::FrequencyLock>,
+    lock_rules: Vec<(Vec<Action>, Vec<LockRule>)>,
+
+    // on-demand river solving
+    on_demand_river_options: Option<SolveOptions>,
+    on_demand_river_cache: BTreeMap<(usize, u8, u8), on_demand::OnDemandRiver>,
+    on_demand_river_key: Option<(usize, u8, u8)>,
 
     // result interpreter
     action_history: Vec<usize>,
@@ -97,6 +136,9 @@ pub struct PostFlopGame {
     turn_swap: Option<u8>,
     river_swap: Option<(u8, u8)>,
     total_bet_amount: [i32; 2],
+    street_bet_base: i32,
+    real_total_bet_amount: [i32; 2],
+    real_street_bet_base: i32,
     weights: [Vec<f32>; 2],
     normalized_weights: [Vec<f32>; 2],
     cfvalues_cache: [Vec<f32>; 2],
@@ -121,9 +163,11 @@ pub struct PostFlopNode {
     scale1: f32,
     scale2: f32,
     scale3: f32,
+    scale4: f32,
     storage1: *mut u8, // strategy
     storage2: *mut u8, // regrets or cfvalues
     storage3: *mut u8, // IP cfvalues
+    storage4: *mut u8, // raw regrets (PCFR+)
 }
 
 unsafe impl Send for PostFlopNode {}
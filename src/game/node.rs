@@ -59,6 +59,16 @@ impl GameNode for PostFlopNode {
         unsafe { slice::from_raw_parts_mut(self.storage2 as *mut f32, self.num_elements as usize) }
     }
 
+    #[inline]
+    fn raw_regrets(&self) -> &[f32] {
+        unsafe { slice::from_raw_parts(self.storage4 as *const f32, self.num_elements as usize) }
+    }
+
+    #[inline]
+    fn raw_regrets_mut(&mut self) -> &mut [f32] {
+        unsafe { slice::from_raw_parts_mut(self.storage4 as *mut f32, self.num_elements as usize) }
+    }
+
     #[inline]
     fn cfvalues(&self) -> &[f32] {
         unsafe { slice::from_raw_parts(self.storage2 as *const f32, self.num_elements as usize) }
@@ -116,6 +126,16 @@ impl GameNode for PostFlopNode {
         unsafe { slice::from_raw_parts_mut(self.storage2 as *mut i16, self.num_elements as usize) }
     }
 
+    #[inline]
+    fn raw_regrets_compressed(&self) -> &[i16] {
+        unsafe { slice::from_raw_parts(self.storage4 as *const i16, self.num_elements as usize) }
+    }
+
+    #[inline]
+    fn raw_regrets_compressed_mut(&mut self) -> &mut [i16] {
+        unsafe { slice::from_raw_parts_mut(self.storage4 as *mut i16, self.num_elements as usize) }
+    }
+
     #[inline]
     fn cfvalues_compressed(&self) -> &[i16] {
         unsafe { slice::from_raw_parts(self.storage2 as *const i16, self.num_elements as usize) }
@@ -168,6 +188,16 @@ impl GameNode for PostFlopNode {
         self.scale2 = scale;
     }
 
+    #[inline]
+    fn raw_regret_scale(&self) -> f32 {
+        self.scale4
+    }
+
+    #[inline]
+    fn set_raw_regret_scale(&mut self, scale: f32) {
+        self.scale4 = scale;
+    }
+
     #[inline]
     fn cfvalue_scale(&self) -> f32 {
         self.scale2
@@ -220,10 +250,12 @@ impl Default for PostFlopNode {
             storage1: ptr::null_mut(),
             storage2: ptr::null_mut(),
             storage3: ptr::null_mut(),
+            storage4: ptr::null_mut(),
             num_elements: 0,
             scale1: 0.0,
             scale2: 0.0,
             scale3: 0.0,
+            scale4: 0.0,
         }
     }
 }
@@ -240,4 +272,10 @@ impl PostFlopNode {
             )
         }
     }
+
+    /// Returns whether the node is a leaf at the depth limit (a showdown before the river).
+    #[inline]
+    pub(super) fn is_leaf(&self) -> bool {
+        self.player & PLAYER_FOLD_FLAG == PLAYER_TERMINAL_FLAG && self.river == NOT_DEALT
+    }
 }
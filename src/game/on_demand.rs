@@ -0,0 +1,203 @@
+use super::*;
+use crate::solver::*;
+use std::ops::ControlFlow;
+
+/// A river subtree solved on demand, with the indices of its hands in the parent game.
+pub(super) struct OnDemandRiver {
+    game: PostFlopGame,
+    hand_map: [Vec<usize>; 2],
+    bet_amount: i32,
+    is_empty_range: [bool; 2],
+}
+
+impl PostFlopGame {
+    /// Sets the options used to solve river subtrees on demand.
+    ///
+    /// When the storage mode is [`BoardState::Turn`] (e.g., a game saved with the target storage
+    /// mode of turn and loaded again), playing a river card makes the interpreter solve the river
+    /// subtree locally: the current weights, derived from the stored flop and turn strategies,
+    /// become the ranges, and the river bet sizes of the tree configuration are used. The solved
+    /// subtree is cached, and the interpreter methods work in it as usual. Note that the river
+    /// donk sizes and the added/removed lines of the original tree are not reproduced. If a player
+    /// never reaches the river node, the subtree is solved against a uniform placeholder range of
+    /// that player, whose weights stay zero.
+    ///
+    /// If `None` is given (default), at most 1000 iterations are run with the target
+    /// exploitability of 0.5% of the pot. Changing the options clears the cache.
+    #[inline]
+    pub fn set_on_demand_river_options(&mut self, options: Option<SolveOptions>) {
+        self.on_demand_river_options = options;
+        self.clear_on_demand_river_cache();
+    }
+
+    /// Returns the options used to solve river subtrees on demand.
+    #[inline]
+    pub fn on_demand_river_options(&self) -> Option<&SolveOptions> {
+        self.on_demand_river_options.as_ref()
+    }
+
+    /// Clears the cache of river subtrees solved on demand. The current node is moved back to the
+    /// root if it is in a cached subtree.
+    #[inline]
+    pub fn clear_on_demand_river_cache(&mut self) {
+        if self.on_demand_river_key.is_some() {
+            self.back_to_root();
+        }
+        self.on_demand_river_cache.clear();
+    }
+
+    /// Returns the number of river subtrees solved on demand and cached.
+    #[inline]
+    pub fn num_on_demand_rivers(&self) -> usize {
+        self.on_demand_river_cache.len()
+    }
+
+    /// Returns whether the river should be solved on demand at the current chance node.
+    #[inline]
+    pub(super) fn needs_on_demand_river(&self) -> bool {
+        self.storage_mode == BoardState::Turn
+            && self.state == State::Solved
+            && self.turn != NOT_DEALT
+            && self.river == NOT_DEALT
+    }
+
+    /// Returns the river subtree that the current node is in, if any.
+    #[inline]
+    pub(super) fn on_demand_river(&self) -> Option<&PostFlopGame> {
+        let key = self.on_demand_river_key.as_ref()?;
+        Some(&self.on_demand_river_cache[key].game)
+    }
+
+    /// Plays the given action in the river subtree that the current node is in.
+    pub(super) fn play_on_demand_river(&mut self, action: usize) {
+        let key = self.on_demand_river_key.unwrap();
+        self.on_demand_river_cache
+            .get_mut(&key)
+            .unwrap()
+            .game
+            .play(action);
+        self.sync_on_demand_river();
+    }
+
+    /// Deals the river card at the current chance node and moves into the river subtree, solving
+    /// it if not cached.
+    pub(super) fn enter_on_demand_river(&mut self, river: u8) {
+        let key = (self.node_index(&self.node()), self.turn, river);
+
+        self.river = river;
+        self.assign_zero_weights();
+
+        if !self.on_demand_river_cache.contains_key(&key) {
+            let river_game = self.solve_on_demand_river();
+            self.on_demand_river_cache.insert(key, river_game);
+        }
+
+        let river_game = self.on_demand_river_cache.get_mut(&key).unwrap();
+        river_game.game.back_to_root();
+        self.on_demand_river_key = Some(key);
+        self.sync_on_demand_river();
+    }
+
+    /// Solves the river subtree starting from the current node.
+    ///
+    /// If a player never reaches the current node (i.e., all weights are zero), the subtree is
+    /// solved with a uniform placeholder range of the player, whose weights are kept zero in the
+    /// interpreter.
+    fn solve_on_demand_river(&self) -> OnDemandRiver {
+        let is_empty_range = [0, 1].map(|player| self.weights[player].iter().all(|&w| w == 0.0));
+        let placeholder = [0, 1].map(|player| vec![1.0; self.private_cards[player].len()]);
+        let weights = [0, 1].map(|player| match is_empty_range[player] {
+            true => placeholder[player].as_slice(),
+            false => self.weights[player].as_slice(),
+        });
+
+        let mut game = self
+            .build_subgame_with_weights(&self.tree_config, weights)
+            .unwrap_or_else(|e| panic!("Failed to build the river subtree: {e}"));
+        game.allocate_memory(self.is_compression_enabled);
+
+        let options = self.on_demand_river_options.unwrap_or(SolveOptions {
+            max_num_iterations: 1000,
+            target_exploitability: game.tree_config.starting_pot as f32 * 0.005,
+            ..Default::default()
+        });
+
+        solve_with_options(&mut game, &options, |_, _| ControlFlow::Continue(()));
+
+        let hand_map = [0, 1].map(|player| {
+            game.private_cards[player]
+                .iter()
+                .map(|hand| self.private_cards[player].binary_search(hand).unwrap())
+                .collect()
+        });
+
+        OnDemandRiver {
+            game,
+            hand_map,
+            bet_amount: self.node().amount,
+            is_empty_range,
+        }
+    }
+
+    /// Copies the weights and the bet amounts of the river subtree to the interpreter.
+    fn sync_on_demand_river(&mut self) {
+        let key = self.on_demand_river_key.unwrap();
+        let river = &self.on_demand_river_cache[&key];
+
+        for player in 0..2 {
+            self.weights[player].fill(0.0);
+            if river.is_empty_range[player] {
+                continue;
+            }
+            for (&i, &w) in river.hand_map[player]
+                .iter()
+                .zip(river.game.weights(player))
+            {
+                self.weights[player][i] = w;
+            }
+        }
+
+        let total_bet_amount = river.game.total_bet_amount();
+        self.total_bet_amount = total_bet_amount.map(|amount| river.bet_amount + amount);
+        self.is_normalized_weight_cached = false;
+    }
+
+    /// Computes the normalized weights in the river subtree and copies them to the interpreter.
+    pub(super) fn cache_on_demand_river_weights(&mut self) {
+        let key = self.on_demand_river_key.unwrap();
+        let river = self.on_demand_river_cache.get_mut(&key).unwrap();
+        river.game.cache_normalized_weights();
+
+        for player in 0..2 {
+            let river = &self.on_demand_river_cache[&key];
+            let weights = river.game.normalized_weights(player);
+            let mut weights = self.map_on_demand_river_hands(weights, player);
+            if river.is_empty_range[player] {
+                weights.fill(0.0);
+            }
+            self.normalized_weights[player] = weights;
+        }
+    }
+
+    /// Maps the per-hand values of the river subtree to the hands of this game.
+    ///
+    /// `values` may consist of multiple rows (e.g., one row per action); missing hands are zero.
+    pub(super) fn map_on_demand_river_hands(&self, values: &[f32], player: usize) -> Vec<f32> {
+        let river = &self.on_demand_river_cache[&self.on_demand_river_key.unwrap()];
+        let hand_map = &river.hand_map[player];
+        let num_hands = self.private_cards[player].len();
+        let num_rows = values.len() / hand_map.len();
+
+        let mut ret = vec![0.0; num_rows * num_hands];
+        for (dst, src) in ret
+            .chunks_exact_mut(num_hands)
+            .zip(values.chunks_exact(hand_map.len()))
+        {
+            for (&i, &v) in hand_map.iter().zip(src) {
+                dst[i] = v;
+            }
+        }
+
+        ret
+    }
+}
@@ -0,0 +1,328 @@
+use super::*;
+use crate::interface::*;
+use crate::utility::*;
+
+/// A method of purifying a strategy (see [`PostFlopGame::purify`]).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Purification {
+    /// Removes the actions taken less frequently than the given value (between `0.0` and `1.0`)
+    /// and renormalizes the rest. The most frequent action of each hand is always kept.
+    Threshold(f32),
+
+    /// Rounds the frequencies to multiples of the given step (e.g., `0.25`), keeping the sum of
+    /// the frequencies at 1.0 by the largest remainder method. The step must be the reciprocal
+    /// of a positive integer (up to rounding).
+    Round(f32),
+
+    /// Each hand takes its most frequent action.
+    Pure,
+
+    /// All hands of a hand class (e.g., `"AKs"`, `"AKo"`, or `"AA"`) take the same action, which
+    /// is the most frequent action of the class weighted by the reach probabilities.
+    PureByHandClass,
+}
+
+/// EV loss in chips and in ICM equity.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EvLoss {
+    /// EV loss in chips.
+    pub chips: f32,
+
+    /// EV loss in the unit of the ICM payoff model (`None` if the game uses the chip EV model).
+    pub icm: Option<f32>,
+}
+
+/// The EV loss at a decision node (see [`PostFlopGame::purify`]).
+#[derive(Debug, Clone, PartialEq)]
+pub struct PurificationNode {
+    /// History of the node, which can be passed to [`PostFlopGame::apply_history`].
+    ///
+    /// Suit-isomorphic runouts share a node, and only the representative card appears.
+    pub history: Vec<usize>,
+
+    /// EV loss of switching to the purified strategy from this node onward, weighted by the
+    /// probability of reaching the node under the original strategy.
+    pub ev_loss: EvLoss,
+}
+
+/// A report of [`PostFlopGame::purify`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct PurificationReport {
+    /// The player whose strategy is purified.
+    pub player: usize,
+
+    /// Total EV loss of the player.
+    pub ev_loss: EvLoss,
+
+    /// EV losses of the decision nodes of the player, in depth-first order.
+    pub nodes: Vec<PurificationNode>,
+}
+
+impl PostFlopGame {
+    /// Simplifies the strategy of `player` into an executable one and reports the EV loss.
+    ///
+    /// The strategy of every decision node of `player` is purified by `method`, except for the
+    /// locked nodes. The returned game has the same configuration as this game, the purified
+    /// strategy of `player` locked at these nodes, and the strategy of the opponent copied from
+    /// this game; it is finalized, so it can be inspected as usual. This game is not modified.
+    ///
+    /// The opponent does not adapt to the purified strategy, so the EV loss is the cost of
+    /// simplification against the current strategy of the opponent (to measure it against an
+    /// adapting opponent, solve a game with the purified strategy locked). The losses are
+    /// reported in chips and, unless the payoff model of this game is the chip EV, also in the
+    /// unit of the ICM payoff model.
+    ///
+    /// The memory of this game must be allocated (with the storage mode of river), and the game
+    /// is usually solved. Bunching effect is not supported.
+    pub fn purify(
+        &self,
+        player: usize,
+        method: Purification,
+    ) -> Result<(PostFlopGame, PurificationReport), String> {
+        if self.state < State::MemoryAllocated || self.storage_mode != BoardState::River {
+            return Err("Memory is not allocated with the storage mode of river".to_string());
+        }
+
+        if player > 1 {
+            return Err(format!("Invalid player: {player}"));
+        }
+
+        if self.bunching_num_dead_cards != 0 {
+            return Err("Bunching effect is not supported".to_string());
+        }
+
+        match method {
+            Purification::Threshold(threshold) if !(0.0..=1.0).contains(&threshold) => {
+                return Err(format!("Invalid threshold: {threshold}"));
+            }
+            Purification::Round(step)
+                if !(step > 0.0 && step <= 1.0)
+                    || (1.0 / step - (1.0 / step).round()).abs() > 1e-3 =>
+            {
+                return Err(format!("Invalid step: {step}"));
+            }
+            _ => {}
+        }
+
+        let (chips_game, chips_loss, chips_gains) =
+            self.purify_with_payoff_model(player, method, Some(PayoffModel::ChipEv))?;
+
+        let (mut game, icm_loss, icm_gains) = match &self.payoff_model {
+            Some(PayoffModel::ChipEv) => (chips_game, None, None),
+            payoff_model => {
+                let (game, loss, gains) =
+                    self.purify_with_payoff_model(player, method, payoff_model.clone())?;
+                (game, Some(loss), Some(gains))
+            }
+        };
+
+        finalize(&mut game);
+
+        let nodes = chips_gains
+            .into_iter()
+            .enumerate()
+            .map(|(i, node)| PurificationNode {
+                history: node.history,
+                ev_loss: EvLoss {
+                    chips: -node.ev_gain,
+                    icm: icm_gains.as_ref().map(|gains| -gains[i].ev_gain),
+                },
+            })
+            .collect();
+
+        let report = PurificationReport {
+            player,
+            ev_loss: EvLoss {
+                chips: chips_loss,
+                icm: icm_loss,
+            },
+            nodes,
+        };
+
+        Ok((game, report))
+    }
+
+    /// Purifies the strategy of `player` with the given payoff model and locks it. Returns the
+    /// game (not finalized), the total EV loss, and the EV gains of the decision nodes.
+    fn purify_with_payoff_model(
+        &self,
+        player: usize,
+        method: Purification,
+        payoff_model: Option<PayoffModel>,
+    ) -> Result<(PostFlopGame, f32, Vec<BestResponseNode>), String> {
+        let mut game = self.clone_with_strategy()?;
+        game.payoff_model = payoff_model;
+
+        let num_hands = game.num_private_hands(player);
+        let mut base_cfv = Vec::with_capacity(num_hands);
+        let mut purified_cfv = Vec::with_capacity(num_hands);
+        let ev_gains = MutexLike::new(vec![0.0; game.node_arena.len()]);
+
+        let private_cards = &game.private_cards[player];
+        game.deviation_recursive(
+            base_cfv.spare_capacity_mut(),
+            purified_cfv.spare_capacity_mut(),
+            &mut game.root(),
+            player,
+            &game.initial_weights[player],
+            &game.initial_weights[player ^ 1],
+            &ev_gains,
+            &|node, strategy, reach, _| {
+                if node.is_locked {
+                    strategy.to_vec()
+                } else {
+                    purify_strategy(strategy, node.num_actions(), reach, private_cards, method)
+                }
+            },
+        );
+
+        unsafe {
+            base_cfv.set_len(num_hands);
+            purified_cfv.set_len(num_hands);
+        }
+
+        // lock the purified strategy
+        for (index, node) in game.node_arena.iter().enumerate() {
+            let mut node = node.lock();
+            if node.is_terminal() || node.is_chance() || node.player() != player || node.is_locked {
+                continue;
+            }
+
+            let strategy = if game.is_compression_enabled {
+                normalized_strategy_compressed(node.strategy_compressed(), node.num_actions())
+            } else {
+                normalized_strategy(node.strategy(), node.num_actions())
+            };
+
+            node.is_locked = true;
+            game.locking_strategy.insert(index, strategy);
+        }
+
+        let ev_gains = ev_gains.lock();
+        let mut nodes = Vec::new();
+        let mut history = Vec::new();
+        game.collect_ev_gains_recursive(0, player, &ev_gains, &mut history, &mut nodes);
+
+        let weights = &game.initial_weights[player];
+        let ev_loss = weighted_sum(&base_cfv, weights) - weighted_sum(&purified_cfv, weights);
+
+        Ok((game, ev_loss, nodes))
+    }
+}
+
+/// Purifies the strategy of a node by `method`.
+fn purify_strategy(
+    strategy: &[f32],
+    num_actions: usize,
+    reach: &[f32],
+    private_cards: &[(u8, u8)],
+    method: Purification,
+) -> Vec<f32> {
+    let num_hands = strategy.len() / num_actions;
+    let mut result = vec![0.0; strategy.len()];
+
+    // the most frequent action of each hand
+    let most_frequent = |hand: usize| {
+        (0..num_actions)
+            .max_by(|&a, &b| {
+                let x = strategy[a * num_hands + hand];
+                let y = strategy[b * num_hands + hand];
+                x.total_cmp(&y).then(b.cmp(&a))
+            })
+            .unwrap()
+    };
+
+    match method {
+        Purification::Threshold(threshold) => {
+            for hand in 0..num_hands {
+                let kept = most_frequent(hand);
+                let mut sum = 0.0;
+                for action in 0..num_actions {
+                    let x = strategy[action * num_hands + hand];
+                    if action == kept || x >= threshold {
+                        result[action * num_hands + hand] = x;
+                        sum += x;
+                    }
+                }
+                for action in 0..num_actions {
+                    result[action * num_hands + hand] /= sum;
+                }
+            }
+        }
+
+        Purification::Round(step) => {
+            let num_units = (1.0 / step).round().max(1.0) as usize;
+            let mut remainders = Vec::with_capacity(num_actions);
+            for hand in 0..num_hands {
+                remainders.clear();
+                let mut num_assigned = 0;
+                for action in 0..num_actions {
+                    let x = strategy[action * num_hands + hand] * num_units as f32;
+                    let units = x.floor();
+                    result[action * num_hands + hand] = units;
+                    remainders.push((x - units, action));
+                    num_assigned += units as usize;
+                }
+
+                // distribute the remaining units to the largest remainders
+                remainders.sort_by(|a, b| b.0.total_cmp(&a.0).then(a.1.cmp(&b.1)));
+                for &(_, action) in remainders
+                    .iter()
+                    .take(num_units - num_assigned.min(num_units))
+                {
+                    result[action * num_hands + hand] += 1.0;
+                }
+
+                for action in 0..num_actions {
+                    result[action * num_hands + hand] /= num_units as f32;
+                }
+            }
+        }
+
+        Purification::Pure => {
+            for hand in 0..num_hands {
+                result[most_frequent(hand) * num_hands + hand] = 1.0;
+            }
+        }
+
+        Purification::PureByHandClass => {
+            // 13 * 13 classes for offsuit hands and pairs, followed by suited hands
+            let class_of = |(c1, c2): (u8, u8)| {
+                let (r1, r2) = (c1 / 4, c2 / 4);
+                let index = r1.max(r2) as usize * 13 + r1.min(r2) as usize;
+                if c1 % 4 == c2 % 4 {
+                    169 + index
+                } else {
+                    index
+                }
+            };
+
+            let mut weighted = vec![0.0f64; 338 * num_actions];
+            let mut unweighted = vec![0.0f64; 338 * num_actions];
+            for (hand, &cards) in private_cards.iter().enumerate() {
+                let class = class_of(cards);
+                for action in 0..num_actions {
+                    let x = strategy[action * num_hands + hand] as f64;
+                    weighted[class * num_actions + action] += x * reach[hand] as f64;
+                    unweighted[class * num_actions + action] += x;
+                }
+            }
+
+            for (hand, &cards) in private_cards.iter().enumerate() {
+                let class = class_of(cards);
+                let row = &weighted[class * num_actions..(class + 1) * num_actions];
+                let row = if row.iter().sum::<f64>() > 0.0 {
+                    row
+                } else {
+                    &unweighted[class * num_actions..(class + 1) * num_actions]
+                };
+                let action = (0..num_actions)
+                    .max_by(|&a, &b| row[a].total_cmp(&row[b]).then(b.cmp(&a)))
+                    .unwrap();
+                result[action * num_hands + hand] = 1.0;
+            }
+        }
+    }
+
+    result
+}
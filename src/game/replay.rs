@@ -0,0 +1,219 @@
+use super::*;
+use crate::hand_history::*;
+use crate::range::*;
+use crate::utility::*;
+
+/// A decision of the hero replayed by [`PostFlopGame::replay_hand_history`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReplayDecision {
+    /// History of the decision node, which can be passed to [`PostFlopGame::apply_history`].
+    pub history: Vec<usize>,
+
+    /// The action taken in the hand history.
+    pub hand_action: HandAction,
+
+    /// The action of the tree that `hand_action` is mapped to.
+    pub action: Action,
+
+    /// The available actions at the decision node.
+    pub actions: Vec<Action>,
+
+    /// The frequencies of `actions` for the hand of the hero.
+    pub strategy: Vec<f32>,
+
+    /// EV loss of `action` against the best action for the hand of the hero, in the unit of the
+    /// tree (see [`PostFlopGame::replay_hand_history`]).
+    pub ev_loss: EvLoss,
+}
+
+impl PostFlopGame {
+    /// Replays the postflop action line of `hand` through this game and grades the decisions of
+    /// the hero.
+    ///
+    /// The amounts of `hand` are scaled so that the pot at the initial street of this game equals
+    /// the starting pot of the tree, and the bets and raises are mapped to the actions of the tree
+    /// with `translation` (see [`play_amount`]). The actions of the streets before the initial
+    /// street are skipped. At each decision of the hero, the strategy of the hero's hand and the
+    /// EV loss of the chosen action against the best action are reported in chips and, unless
+    /// the payoff model of this game is the chip EV, also in the unit of the ICM payoff model.
+    /// The EV losses are in the unit of the tree (multiply by the pot of the hand divided by the
+    /// starting pot to convert them to the unit of the hand).
+    ///
+    /// The game must be solved, and the board and the hero's hand of `hand` must be consistent
+    /// with this game. The current node is moved back to the root.
+    ///
+    /// [`play_amount`]: #method.play_amount
+    pub fn replay_hand_history(
+        &mut self,
+        hand: &HandHistory,
+        translation: ActionTranslation,
+    ) -> Result<Vec<ReplayDecision>, String> {
+        if self.state != State::Solved {
+            return Err("Game is not solved".to_string());
+        }
+
+        self.back_to_root();
+        let result = self.replay_hand_history_internal(hand, translation);
+        self.back_to_root();
+        result
+    }
+
+    /// The internal implementation of [`PostFlopGame::replay_hand_history`].
+    fn replay_hand_history_internal(
+        &mut self,
+        hand: &HandHistory,
+        translation: ActionTranslation,
+    ) -> Result<Vec<ReplayDecision>, String> {
+        let hero = hand.hero.ok_or("Hero is not in the hand")?;
+        let hero_cards = hand.hero_cards.ok_or("Hero's hand is unknown")?;
+
+        if hand.board.len() < 3 {
+            return Err("Hand does not have a flop".to_string());
+        }
+
+        let mut flop = [hand.board[0], hand.board[1], hand.board[2]];
+        flop.sort_unstable();
+        if flop != self.card_config.flop
+            || (self.card_config.turn != NOT_DEALT
+                && hand.board.get(3) != Some(&self.card_config.turn))
+            || (self.card_config.river != NOT_DEALT
+                && hand.board.get(4) != Some(&self.card_config.river))
+        {
+            return Err("Hand has a different board".to_string());
+        }
+
+        if hand.board.contains(&hero_cards.0) || hand.board.contains(&hero_cards.1) {
+            return Err("Hero's hand overlaps with the board".to_string());
+        }
+
+        let hero_hand = self.private_cards[hero]
+            .iter()
+            .position(|&cards| cards == hero_cards)
+            .ok_or("Hero's hand is not in the range")?;
+
+        let initial_state = self.tree_config.initial_state;
+        let pot = *hand
+            .pots
+            .get(initial_state as usize)
+            .ok_or("Hand does not reach the initial street")?;
+        let scale = self.tree_config.starting_pot as f64 / pot;
+
+        // the chip EV game is needed to report the losses in chips with the ICM payoff model
+        let mut chips_game = match &self.payoff_model {
+            Some(PayoffModel::ChipEv) => None,
+            _ => {
+                let mut game = self.clone_with_strategy()?;
+                game.payoff_model = Some(PayoffModel::ChipEv);
+                finalize(&mut game);
+                Some(game)
+            }
+        };
+
+        let mut decisions = Vec::new();
+
+        for &hand_action in hand.actions.iter().filter(|a| a.street >= initial_state) {
+            while self.is_chance_node() {
+                let index = if self.turn == NOT_DEALT { 3 } else { 4 };
+                let card = *hand.board.get(index).ok_or("Board card is not dealt")?;
+                if self.possible_cards() & (1 << card) == 0 {
+                    return Err(format!("Card cannot be dealt: {}", card_to_string(card)?));
+                }
+                self.play(card as usize);
+            }
+
+            if self.is_terminal_node() {
+                return Err("Action line is longer than the tree".to_string());
+            }
+
+            let player = self.current_player();
+            if player != hand_action.player {
+                return Err(format!("Unexpected player at {:?}", self.history()));
+            }
+
+            let history = self.history().to_vec();
+            let actions = self.available_actions();
+            let is_hero = player == hero;
+
+            let (strategy, cfvalues) = if is_hero {
+                self.cache_normalized_weights();
+                let num_hands = self.private_cards[hero].len();
+                let strategy = self.strategy();
+                let strategy = (0..actions.len())
+                    .map(|action| strategy[action * num_hands + hero_hand])
+                    .collect::<Vec<_>>();
+                let cfvalues = hero_action_values(self, hero, hero_hand, actions.len());
+                (strategy, cfvalues)
+            } else {
+                (Vec::new(), Vec::new())
+            };
+
+            let action_index = match hand_action.kind {
+                HandActionKind::Bet(amount) | HandActionKind::Raise(amount) => {
+                    let amount = (amount * scale).round() as i32;
+                    self.play_amount(amount, translation).action_index
+                }
+                kind => {
+                    let action = match kind {
+                        HandActionKind::Fold => Action::Fold,
+                        HandActionKind::Check => Action::Check,
+                        _ => Action::Call,
+                    };
+                    let action_index = actions
+                        .iter()
+                        .position(|&a| a == action)
+                        .ok_or_else(|| format!("Action is not available: {action:?}"))?;
+                    self.play(action_index);
+                    action_index
+                }
+            };
+
+            if is_hero {
+                let loss = |values: &[f32]| {
+                    let best = values.iter().fold(f32::MIN, |m, &v| max(m, v));
+                    best - values[action_index]
+                };
+
+                let ev_loss = match &mut chips_game {
+                    None => EvLoss {
+                        chips: loss(&cfvalues),
+                        icm: None,
+                    },
+                    Some(game) => {
+                        game.apply_history(&history);
+                        let chips = hero_action_values(game, hero, hero_hand, actions.len());
+                        EvLoss {
+                            chips: loss(&chips),
+                            icm: Some(loss(&cfvalues)),
+                        }
+                    }
+                };
+
+                decisions.push(ReplayDecision {
+                    history,
+                    hand_action,
+                    action: actions[action_index],
+                    actions,
+                    strategy,
+                    ev_loss,
+                });
+            }
+        }
+
+        Ok(decisions)
+    }
+}
+
+/// Returns the expected values of each action for the hand of the hero at the current node.
+fn hero_action_values(
+    game: &mut PostFlopGame,
+    hero: usize,
+    hero_hand: usize,
+    num_actions: usize,
+) -> Vec<f32> {
+    game.cache_normalized_weights();
+    let num_hands = game.private_cards[hero].len();
+    let detail = game.expected_values_detail(hero);
+    (0..num_actions)
+        .map(|action| detail[action * num_hands + hero_hand])
+        .collect()
+}
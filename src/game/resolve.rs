@@ -0,0 +1,221 @@
+use super::*;
+use crate::interface::*;
+use crate::range::*;
+use crate::solver::*;
+use crate::utility::*;
+use std::time::Instant;
+
+impl PostFlopGame {
+    /// Re-solves the subtree rooted at the current node with a finer tree configuration.
+    ///
+    /// The current node must be the first node of the turn or the river (i.e., right after a card
+    /// is dealt) of this solved game. The returned game starts from the current board, with the
+    /// current pot as the starting pot, the remaining stack as the effective stack, and the
+    /// current weights of the players as the ranges. Only the bet sizes and the thresholds of
+    /// `tree_config` are used; the `initial_state`, `starting_pot`, `effective_stack`, and rake
+    /// fields are replaced.
+    ///
+    /// The re-solving is *safe* for `player`: the opponent is given a gadget choice, for each hand,
+    /// between entering the subgame and taking the expected value of the hand in this game. The
+    /// opponent's range in the subgame is updated every iteration by regret matching on this
+    /// choice, so the refined strategy of `player` does not give the opponent more than what the
+    /// original strategy gave. After solving, the range of the opponent is restored to the
+    /// current weights and the returned game is finalized, so it can be inspected as usual.
+    ///
+    /// Of `options`, the `max_num_iterations`, `target_exploitability`, `max_duration`,
+    /// `start_iteration`, and `algorithm` fields are respected, and the exploitability is checked
+    /// every 10 iterations. The exploitability is measured in the gadget game, i.e., against the
+    /// opponent's range restricted by the gadget.
+    pub fn resolve_subgame(
+        &mut self,
+        tree_config: &TreeConfig,
+        player: usize,
+        options: &SolveOptions,
+    ) -> Result<(PostFlopGame, SolveReport), String> {
+        if self.state != State::Solved {
+            return Err("Game is not solved".to_string());
+        }
+
+        if player > 1 {
+            return Err(format!("Invalid player: {player}"));
+        }
+
+        if self.bunching_num_dead_cards != 0 {
+            return Err("Bunching effect is not supported".to_string());
+        }
+
+        if self.is_terminal_node()
+            || self.is_chance_node()
+            || !matches!(self.node().prev_action, Action::Chance(_))
+        {
+            return Err("Current node must be the first node of the turn or river".to_string());
+        }
+
+        let start = Instant::now();
+        let opponent = player ^ 1;
+
+        // expected values of the opponent in this game
+        self.cache_normalized_weights();
+        let parent_values = self.expected_values(opponent);
+
+        let mut game = self.build_subgame(tree_config)?;
+        game.allocate_memory(self.is_compression_enabled);
+
+        if options.algorithm == CfrAlgorithm::PcfrPlus {
+            game.allocate_raw_regrets();
+        }
+
+        // gadget values of the opponent
+        let mut parent_index = vec![usize::MAX; 52 * 51 / 2];
+        for (i, &(c1, c2)) in self.private_cards[opponent].iter().enumerate() {
+            parent_index[card_pair_to_index(c1, c2)] = i;
+        }
+
+        let opponent_cards = &game.private_cards[opponent];
+        let alternative_values = opponent_cards
+            .iter()
+            .map(|&(c1, c2)| parent_values[parent_index[card_pair_to_index(c1, c2)]])
+            .collect::<Vec<_>>();
+
+        // converts the counterfactual values into the expected values
+        let normalizers = opponent_cards
+            .iter()
+            .map(|&(c1, c2)| {
+                let mask: u64 = (1 << c1) | (1 << c2);
+                let reach = game.private_cards[player]
+                    .iter()
+                    .zip(&game.initial_weights[player])
+                    .filter(|(&(c3, c4), _)| mask & ((1 << c3) | (1 << c4)) == 0)
+                    .map(|(_, &w)| w as f64)
+                    .sum::<f64>();
+                if reach > 0.0 {
+                    (game.num_combinations / reach) as f32
+                } else {
+                    0.0
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let bias = game.tree_config.starting_pot as f32 * 0.5;
+        let opponent_reach = game.initial_weights[opponent].clone();
+        let num_hands = opponent_reach.len();
+        let mut regrets_follow = vec![0.0f32; num_hands];
+        let mut regrets_terminate = vec![0.0f32; num_hands];
+        let mut follow_prob = vec![1.0f32; num_hands];
+
+        let mut num_iterations = options.start_iteration;
+        let mut stop_reason = StopReason::MaxIterations;
+
+        while num_iterations < options.max_num_iterations {
+            if let Some(max_duration) = options.max_duration {
+                if start.elapsed() >= max_duration {
+                    stop_reason = StopReason::TimeLimit;
+                    break;
+                }
+            }
+
+            let params = DiscountParams::with_algorithm(options.algorithm, num_iterations);
+            let cfvalues = solve_step_with_params(&game, &params);
+            num_iterations += 1;
+
+            // regret matching+ on the gadget
+            for hand in 0..num_hands {
+                if normalizers[hand] == 0.0 {
+                    continue;
+                }
+
+                let follow = cfvalues[opponent][hand] * normalizers[hand] + bias;
+                let terminate = alternative_values[hand];
+                let value = follow_prob[hand] * follow + (1.0 - follow_prob[hand]) * terminate;
+                regrets_follow[hand] = max(regrets_follow[hand] + follow - value, 0.0);
+                regrets_terminate[hand] = max(regrets_terminate[hand] + terminate - value, 0.0);
+
+                let sum = regrets_follow[hand] + regrets_terminate[hand];
+                follow_prob[hand] = if sum > 0.0 {
+                    regrets_follow[hand] / sum
+                } else {
+                    1.0
+                };
+            }
+
+            for (w, (&reach, &prob)) in game.initial_weights[opponent]
+                .iter_mut()
+                .zip(opponent_reach.iter().zip(&follow_prob))
+            {
+                *w = reach * prob;
+            }
+
+            #[allow(clippy::manual_is_multiple_of)]
+            let is_check_iteration = num_iterations % 10 == 0;
+            if is_check_iteration && compute_exploitability(&game) <= options.target_exploitability
+            {
+                stop_reason = StopReason::TargetExploitability;
+                break;
+            }
+        }
+
+        let exploitability = compute_exploitability(&game);
+
+        // restore the range of the opponent
+        game.initial_weights[opponent] = opponent_reach;
+        finalize(&mut game);
+
+        let report = SolveReport {
+            num_iterations,
+            exploitability,
+            elapsed: start.elapsed(),
+            stop_reason,
+        };
+
+        Ok((game, report))
+    }
+
+    /// Builds the game that starts from the current node, which must be the first node of a
+    /// street (or the chance node dealing it, after the card is set in the interpreter).
+    ///
+    /// The current pot and the remaining stack become the starting pot and the effective stack,
+    /// and the current weights become the ranges.
+    pub(super) fn build_subgame(&self, tree_config: &TreeConfig) -> Result<PostFlopGame, String> {
+        self.build_subgame_with_weights(tree_config, [&self.weights[0], &self.weights[1]])
+    }
+
+    /// Builds the game that starts from the current node with the given weights as the ranges
+    /// (see [`build_subgame`]).
+    ///
+    /// [`build_subgame`]: #method.build_subgame
+    pub(super) fn build_subgame_with_weights(
+        &self,
+        tree_config: &TreeConfig,
+        weights: [&[f32]; 2],
+    ) -> Result<PostFlopGame, String> {
+        let amount = self.node().amount;
+        let board = self.current_board();
+        let card_config = CardConfig {
+            range: [
+                Range::from_hands_weights(&self.private_cards[0], weights[0])?,
+                Range::from_hands_weights(&self.private_cards[1], weights[1])?,
+            ],
+            flop: self.card_config.flop,
+            turn: board[3],
+            river: board.get(4).cloned().unwrap_or(NOT_DEALT),
+        };
+
+        let tree_config = TreeConfig {
+            initial_state: match board.len() {
+                4 => BoardState::Turn,
+                _ => BoardState::River,
+            },
+            starting_pot: self.tree_config.starting_pot + 2 * amount,
+            effective_stack: self.tree_config.effective_stack - amount,
+            rake_rate: self.tree_config.rake_rate,
+            rake_cap: self.tree_config.rake_cap,
+            ..tree_config.clone()
+        };
+
+        let action_tree = ActionTree::new(tree_config)?;
+        let mut game = PostFlopGame::with_config(card_config, action_tree)?;
+        game.payoff_model = self.payoff_model.clone();
+        game.leaf_evaluator = self.leaf_evaluator.clone();
+        Ok(game)
+    }
+}
@@ -54,6 +54,54 @@ impl PostFlopGame {
         }
     }
 
+    /// Returns the storage of the cumulative regrets.
+    #[inline]
+    pub(crate) fn regret_storage(&self) -> &[u8] {
+        &self.storage2
+    }
+
+    /// Returns the mutable storage of the cumulative regrets.
+    #[inline]
+    pub(crate) fn regret_storage_mut(&mut self) -> &mut [u8] {
+        &mut self.storage2
+    }
+
+    /// Returns the storage of the raw cumulative regrets (empty unless PCFR+ is used) and the
+    /// scales of all nodes.
+    pub(crate) fn raw_regret_storage(&self) -> (&[u8], Vec<f32>) {
+        if self.storage_raw_regrets.is_empty() {
+            return (&[], Vec::new());
+        }
+
+        let scales = self.node_arena.iter().map(|n| n.lock().scale4).collect();
+        (&self.storage_raw_regrets, scales)
+    }
+
+    /// Restores the storage of the raw cumulative regrets.
+    pub(crate) fn restore_raw_regret_storage(
+        &mut self,
+        storage: &[u8],
+        scales: &[f32],
+    ) -> Result<(), String> {
+        if storage.is_empty() {
+            return Ok(());
+        }
+
+        if !self.allocate_raw_regrets()
+            || storage.len() != self.storage_raw_regrets.len()
+            || scales.len() != self.node_arena.len()
+        {
+            return Err("Raw regret storage size mismatch".to_string());
+        }
+
+        self.storage_raw_regrets.copy_from_slice(storage);
+        for (node, &scale) in self.node_arena.iter().zip(scales) {
+            node.lock().scale4 = scale;
+        }
+
+        Ok(())
+    }
+
     /// Returns the number of storage elements required for the target storage mode.
     fn num_target_storage(&self) -> [usize; 4] {
         if self.state <= State::TreeBuilt {
@@ -96,7 +144,7 @@ impl PostFlopGame {
     }
 }
 
-static VERSION_STR: &str = "2023-03-19";
+static VERSION_STR: &str = "2026-10-18";
 
 thread_local! {
     static PTR_BASE: Cell<[*const u8; 2]> = Cell::new([ptr::null(); 2]);
@@ -146,6 +194,9 @@ impl Encode for PostFlopGame {
         locking_strategy.retain(|&i, _| i < num_nodes);
         locking_strategy.encode(encoder)?;
 
+        // payoff model
+        self.payoff_model.encode(encoder)?;
+
         // store base pointers
         PTR_BASE.with(|c| {
             if self.state >= State::MemoryAllocated {
@@ -200,6 +251,7 @@ impl Decode for PostFlopGame {
             storage_ip: Decode::decode(decoder)?,
             storage_chance: Decode::decode(decoder)?,
             locking_strategy: Decode::decode(decoder)?,
+            payoff_model: Decode::decode(decoder)?,
             ..Default::default()
         };
 
@@ -1,8 +1,14 @@
 use super::*;
+use crate::bet_size::BetSizeCandidates;
+use crate::hand::*;
+use crate::hand_history::*;
+use crate::interface::*;
 use crate::range::*;
 use crate::solver::*;
+use crate::trainer::*;
 use crate::utility::*;
 use crate::BunchingData;
+use std::ops::ControlFlow;
 
 #[test]
 fn all_check_all_range() {
@@ -704,7 +710,6 @@ fn no_assignment() {
 
 #[test]
 fn remove_lines() {
-    use crate::bet_size::BetSizeCandidates;
     let card_config = CardConfig {
         range: ["TT+,AKo,AQs+".parse().unwrap(), "AA".parse().unwrap()],
         flop: flop_from_str("2c6dTh").unwrap(),
@@ -1140,6 +1145,60 @@ fn set_bunching_effect_always_win() {
     assert!((ev_ip - 0.0).abs() < 1e-4);
 }
 
+#[test]
+fn solve_algorithms() {
+    let card_config = CardConfig {
+        range: [
+            "AA,KK,QQ,AK,T9s".parse().unwrap(),
+            "JJ-99,AQ,KQ".parse().unwrap(),
+        ],
+        flop: flop_from_str("Td9d6h").unwrap(),
+        turn: card_from_str("Qc").unwrap(),
+        river: card_from_str("2s").unwrap(),
+    };
+
+    let bet_sizes = ("50%, a", "").try_into().unwrap();
+    let tree_config = TreeConfig {
+        initial_state: BoardState::River,
+        starting_pot: 100,
+        effective_stack: 300,
+        river_bet_sizes: [bet_sizes, Default::default()],
+        ..Default::default()
+    };
+
+    let algorithms = [
+        CfrAlgorithm::Dcfr,
+        CfrAlgorithm::DcfrCustom {
+            alpha: 1.5,
+            beta: 0.0,
+            gamma: 2.0,
+        },
+        CfrAlgorithm::CfrPlus,
+        CfrAlgorithm::LinearCfr,
+        CfrAlgorithm::PcfrPlus,
+    ];
+
+    for algorithm in algorithms {
+        for enable_compression in [false, true] {
+            let action_tree = ActionTree::new(tree_config.clone()).unwrap();
+            let mut game = PostFlopGame::with_config(card_config.clone(), action_tree).unwrap();
+            game.set_payoff_model(PayoffModel::ChipEv);
+            game.allocate_memory(enable_compression);
+
+            let options = SolveOptions {
+                max_num_iterations: 1000,
+                target_exploitability: 0.25,
+                algorithm,
+                ..Default::default()
+            };
+
+            let report = solve_with_options(&mut game, &options, |_, _| ControlFlow::Continue(()));
+            assert_eq!(report.stop_reason, StopReason::TargetExploitability);
+            assert!((report.exploitability - compute_exploitability(&game)).abs() < 1e-4);
+        }
+    }
+}
+
 #[test]
 #[ignore]
 fn solve_pio_preset_normal() {
@@ -1247,3 +1306,1100 @@ fn solve_pio_preset_raked() {
     assert!((root_ev_oop - 95.57).abs() < 0.2);
     assert!((root_ev_ip - 66.98).abs() < 0.2);
 }
+
+#[test]
+fn warm_start() {
+    let card_config = CardConfig {
+        range: [
+            "AA,KK,QQ,AK,T9s".parse().unwrap(),
+            "JJ-99,AQ,KQ".parse().unwrap(),
+        ],
+        flop: flop_from_str("Td9d6h").unwrap(),
+        turn: card_from_str("Qc").unwrap(),
+        river: card_from_str("2s").unwrap(),
+    };
+
+    let tree_config = TreeConfig {
+        initial_state: BoardState::River,
+        starting_pot: 100,
+        effective_stack: 300,
+        river_bet_sizes: [("50%, a", "").try_into().unwrap(), Default::default()],
+        ..Default::default()
+    };
+
+    let action_tree = ActionTree::new(tree_config.clone()).unwrap();
+    let mut source = PostFlopGame::with_config(card_config.clone(), action_tree).unwrap();
+    source.set_payoff_model(PayoffModel::ChipEv);
+    source.allocate_memory(false);
+    solve(&mut source, 1000, 0.05, false);
+
+    // change the range weights and add a bet size
+    let mut card_config = card_config;
+    card_config.range[1] = "JJ-99,AQ,KQ:0.7".parse().unwrap();
+    let mut tree_config = tree_config;
+    tree_config.river_bet_sizes[0] = ("50%, 100%, a", "").try_into().unwrap();
+
+    for enable_compression in [false, true] {
+        let num_iterations = [false, true].map(|warm| {
+            let action_tree = ActionTree::new(tree_config.clone()).unwrap();
+            let mut game = PostFlopGame::with_config(card_config.clone(), action_tree).unwrap();
+            game.set_payoff_model(PayoffModel::ChipEv);
+            game.allocate_memory(enable_compression);
+
+            let mut options = SolveOptions {
+                max_num_iterations: 1000,
+                target_exploitability: 0.3,
+                ..Default::default()
+            };
+
+            if warm {
+                let report = game.warm_start(&source, 10.0).unwrap();
+                assert_eq!(report.num_matched_nodes, 4);
+                assert_eq!(report.num_unmatched_nodes, 1); // IP facing the 100% bet
+                options.start_iteration = 10;
+            }
+
+            let report = solve_with_options(&mut game, &options, |_, _| ControlFlow::Continue(()));
+            assert_eq!(report.stop_reason, StopReason::TargetExploitability);
+            report.num_iterations - options.start_iteration
+        });
+
+        assert!(num_iterations[1] < num_iterations[0]);
+    }
+
+    // memory not allocated
+    let action_tree = ActionTree::new(tree_config.clone()).unwrap();
+    let mut game = PostFlopGame::with_config(card_config.clone(), action_tree).unwrap();
+    assert!(game.warm_start(&source, 10.0).is_err());
+
+    // different board
+    card_config.river = card_from_str("3s").unwrap();
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+    game.allocate_memory(false);
+    assert!(game.warm_start(&source, 10.0).is_err());
+
+    // the river cards that are isomorphic in the source are mapped by swapping the suits
+    let (mut card_config, tree_config) = test_spot(BoardState::Turn, ("50%", ""));
+    card_config.turn = card_from_str("Qh").unwrap();
+    let action_tree = ActionTree::new(tree_config.clone()).unwrap();
+    let mut source = PostFlopGame::with_config(card_config.clone(), action_tree).unwrap();
+    source.set_payoff_model(PayoffModel::ChipEv);
+    source.allocate_memory(false);
+    solve(&mut source, 50, 0.0, false);
+    source.play(0);
+
+    card_config.range[0] = "AA,KK,QQ,AKo,T9s,87s,AcKc".parse().unwrap();
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+    game.set_payoff_model(PayoffModel::ChipEv);
+    game.allocate_memory(false);
+    game.warm_start(&source, 10.0).unwrap();
+    assert_eq!(source.history(), [0]);
+
+    source.play(0);
+    game.play(0);
+    game.play(0);
+    assert!(source.available_actions().len() < game.available_actions().len());
+
+    let history = source.history().to_vec();
+    for card in 0..52 {
+        if game.possible_cards() & (1 << card) == 0 {
+            continue;
+        }
+
+        source.play(card);
+        game.play(card);
+        let source_strategy = source.strategy();
+        let strategy = game.strategy();
+        let source_num_hands = source.num_private_hands(0);
+        let num_hands = game.num_private_hands(0);
+        for (hand, &cards) in game.private_cards(0).iter().enumerate() {
+            let source_hand = source.private_cards(0).iter().position(|&c| c == cards);
+            let source_hand = source_hand.unwrap();
+            for action in 0..2 {
+                let x = strategy[action * num_hands + hand];
+                let y = source_strategy[action * source_num_hands + source_hand];
+                assert!((x - y).abs() < 1e-5);
+            }
+        }
+        source.apply_history(&history);
+        game.apply_history(&history);
+    }
+}
+
+/// Returns the configurations of the spot shared by the tests below: "AA,KK,QQ,AK,T9s,87s" vs.
+/// "JJ-88,AQ,KQ,QJs" on Td9d6h Qc 2s, dealt up to `initial_state`, with a starting pot of 100 and
+/// an effective stack of 300. Both players have `bet_sizes` in every street from `initial_state`.
+fn test_spot(initial_state: BoardState, bet_sizes: (&str, &str)) -> (CardConfig, TreeConfig) {
+    let deal = |street: BoardState, card: &str| {
+        if initial_state >= street {
+            card_from_str(card).unwrap()
+        } else {
+            NOT_DEALT
+        }
+    };
+
+    let card_config = CardConfig {
+        range: [
+            "AA,KK,QQ,AK,T9s,87s".parse().unwrap(),
+            "JJ-88,AQ,KQ,QJs".parse().unwrap(),
+        ],
+        flop: flop_from_str("Td9d6h").unwrap(),
+        turn: deal(BoardState::Turn, "Qc"),
+        river: deal(BoardState::River, "2s"),
+    };
+
+    let bet_sizes = BetSizeCandidates::try_from(bet_sizes).unwrap();
+    let street_sizes = |street: BoardState| {
+        if initial_state <= street {
+            [bet_sizes.clone(), bet_sizes.clone()]
+        } else {
+            Default::default()
+        }
+    };
+
+    let tree_config = TreeConfig {
+        initial_state,
+        starting_pot: 100,
+        effective_stack: 300,
+        flop_bet_sizes: street_sizes(BoardState::Flop),
+        turn_bet_sizes: street_sizes(BoardState::Turn),
+        river_bet_sizes: street_sizes(BoardState::River),
+        ..Default::default()
+    };
+
+    (card_config, tree_config)
+}
+
+/// An ICM state of two players with 10 big blinds each, where the tournament equity is concave
+/// in the stack.
+const ICM_STATE: &str = r#"{
+    "formatType": "icm",
+    "formatVersion": "1",
+    "pot": 0,
+    "bigblind": 1,
+    "utilities": [
+        { "s": [0, 0], "u": [0.0, 0.0] },
+        { "s": [10, 10], "u": [0.5, 0.5] },
+        { "s": [20, 20], "u": [0.8, 0.8] }
+    ],
+    "players": [
+        { "index": 0, "startingStack": 10, "remainingStack": 10 },
+        { "index": 1, "startingStack": 10, "remainingStack": 10 }
+    ]
+}"#;
+
+#[test]
+fn resolve_subgame() {
+    let (card_config, tree_config) = test_spot(BoardState::Turn, ("50%", ""));
+
+    let action_tree = ActionTree::new(tree_config.clone()).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+    game.set_payoff_model(PayoffModel::ChipEv);
+    game.allocate_memory(false);
+    solve(&mut game, 1000, 0.1, false);
+
+    let options = SolveOptions {
+        max_num_iterations: 500,
+        target_exploitability: 0.1,
+        ..Default::default()
+    };
+
+    let mut fine_config = tree_config;
+    fine_config.river_bet_sizes[0] = ("33%, 75%, a", "").try_into().unwrap();
+
+    // not the first node of the river
+    assert!(game.resolve_subgame(&fine_config, 0, &options).is_err());
+
+    // check, check, 2s
+    game.play(0);
+    game.play(0);
+    game.play(card_from_str("2s").unwrap() as usize);
+    game.cache_normalized_weights();
+    let ip_ev = compute_average(&game.expected_values(1), game.normalized_weights(1));
+
+    let (mut subgame, report) = game.resolve_subgame(&fine_config, 0, &options).unwrap();
+    assert!(subgame.is_solved());
+    assert_eq!(report.stop_reason, StopReason::TargetExploitability);
+    assert!(report.exploitability <= 0.1);
+    assert_eq!(subgame.tree_config().starting_pot, 100);
+    assert_eq!(subgame.current_board(), game.current_board());
+    assert_eq!(
+        format!("{:?}", subgame.available_actions()),
+        "[Check, Bet(33), Bet(75), AllIn(300)]"
+    );
+
+    // the refined strategy of OOP does not give IP more than the original one
+    subgame.cache_normalized_weights();
+    let weights = subgame.normalized_weights(1);
+    let sub_ip_ev = compute_average(&subgame.expected_values(1), weights);
+    assert!(sub_ip_ev <= ip_ev + 0.2);
+}
+
+#[test]
+fn depth_limit() {
+    let (card_config, mut tree_config) = test_spot(BoardState::Flop, ("50%", ""));
+
+    // the turn and the river are checked down
+    tree_config.turn_bet_sizes = Default::default();
+    tree_config.river_bet_sizes = Default::default();
+
+    let limited_config = TreeConfig {
+        depth_limit: Some(BoardState::Flop),
+        ..tree_config.clone()
+    };
+
+    let root_ev = |game: &mut PostFlopGame| {
+        game.back_to_root();
+        game.cache_normalized_weights();
+        compute_average(&game.expected_values(0), game.normalized_weights(0))
+    };
+
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config.clone(), action_tree).unwrap();
+    game.set_payoff_model(PayoffModel::ChipEv);
+    game.allocate_memory(false);
+    solve(&mut game, 1000, 0.05, false);
+    let full_ev = root_ev(&mut game);
+
+    let action_tree = ActionTree::new(limited_config.clone()).unwrap();
+    let mut game = PostFlopGame::with_config(card_config.clone(), action_tree).unwrap();
+    game.set_payoff_model(PayoffModel::ChipEv);
+    assert_eq!(game.num_nodes[1..], [0, 0]);
+    game.allocate_memory(false);
+    solve(&mut game, 1000, 0.05, false);
+
+    // the default leaf evaluator is equivalent to checking down
+    let limited_ev = root_ev(&mut game);
+    assert!((limited_ev - full_ev).abs() < 0.2);
+
+    // check, check
+    game.play(0);
+    game.play(0);
+    assert!(game.is_terminal_node());
+    assert!(!game.is_chance_node());
+
+    // OOP realizes less equity
+    game.set_leaf_evaluator(Arc::new(EquityRealization {
+        factors: [0.8, 1.1],
+    }));
+    game.allocate_memory(false);
+    solve(&mut game, 1000, 0.05, false);
+    assert!(root_ev(&mut game) < limited_ev);
+
+    // the depth limit must not precede the initial state
+    let invalid_config = TreeConfig {
+        initial_state: BoardState::Turn,
+        ..limited_config
+    };
+    assert!(ActionTree::new(invalid_config).is_err());
+}
+
+#[test]
+fn chance_sampling() {
+    let (card_config, tree_config) = test_spot(BoardState::Turn, ("50%", ""));
+
+    let new_game = || {
+        let action_tree = ActionTree::new(tree_config.clone()).unwrap();
+        let mut game = PostFlopGame::with_config(card_config.clone(), action_tree).unwrap();
+        game.set_payoff_model(PayoffModel::ChipEv);
+        game.allocate_memory(false);
+        game
+    };
+
+    let options = SolveOptions {
+        max_num_iterations: 1000,
+        target_exploitability: 1.0,
+        chance_sampling: Some(ChanceSampling {
+            num_samples: 8,
+            seed: 42,
+        }),
+        ..Default::default()
+    };
+
+    let mut game = new_game();
+    let report = solve_with_options(&mut game, &options, |_, _| ControlFlow::Continue(()));
+    assert_eq!(report.stop_reason, StopReason::TargetExploitability);
+    assert!(report.exploitability <= 1.0);
+
+    // the same seed gives the same result
+    let mut other = new_game();
+    solve_with_options(&mut other, &options, |_, _| ControlFlow::Continue(()));
+    assert_eq!(game.strategy(), other.strategy());
+}
+
+#[test]
+fn best_response() {
+    let (card_config, tree_config) = test_spot(BoardState::Turn, ("50%", ""));
+
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+    game.set_payoff_model(PayoffModel::ChipEv);
+    game.allocate_memory(false);
+
+    // OOP always bets at the root
+    let num_hands = game.num_private_hands(0);
+    let mut locking = vec![0.0; 2 * num_hands];
+    locking[num_hands..].fill(1.0);
+    game.lock_current_strategy(&locking);
+    solve(&mut game, 100, 0.0, false);
+
+    let current_ev = compute_current_ev(&game);
+    let mes_ev = compute_mes_ev(&game);
+
+    let (mut exploit, report) = game.best_response(1).unwrap();
+    assert_eq!(report.player, 1);
+    assert!((report.base_ev - current_ev[1]).abs() < 1e-3);
+    assert!((report.best_response_ev - mes_ev[1]).abs() < 1e-3);
+    assert!((compute_current_ev(&exploit)[1] - report.best_response_ev).abs() < 1e-3);
+    assert!(report.nodes.iter().all(|node| node.ev_gain >= -1e-4));
+
+    // the root is always bet, so the gain at the IP node facing the bet is the total gain
+    let gain = report.best_response_ev - report.base_ev;
+    let facing_bet = report.nodes.iter().find(|node| node.history == [1]);
+    assert!((facing_bet.unwrap().ev_gain - gain).abs() < 1e-3);
+
+    // the best response is pure and the lock of OOP is kept
+    assert!(exploit.current_locking_strategy().is_some());
+    exploit.play(1);
+    assert!(exploit.strategy().iter().all(|&x| x == 0.0 || x == 1.0));
+    assert!(game.best_response(2).is_err());
+}
+
+#[test]
+fn frequency_locking() {
+    let (card_config, tree_config) = test_spot(BoardState::Turn, ("50%, a", ""));
+
+    let root_frequencies = |targets: &[f32]| {
+        let action_tree = ActionTree::new(tree_config.clone()).unwrap();
+        let mut game = PostFlopGame::with_config(card_config.clone(), action_tree).unwrap();
+        game.set_payoff_model(PayoffModel::ChipEv);
+        game.allocate_memory(false);
+        game.lock_current_frequencies(targets);
+        assert_eq!(game.current_frequency_lock().unwrap(), targets);
+        solve(&mut game, 300, 0.0, false);
+
+        game.cache_normalized_weights();
+        let weights = game.normalized_weights(0).to_vec();
+        let strategy = game.strategy();
+        let num_hands = weights.len();
+        let weight_sum = weights.iter().sum::<f32>();
+        (0..3)
+            .map(|action| {
+                let row = &strategy[action * num_hands..(action + 1) * num_hands];
+                row.iter().zip(&weights).map(|(s, w)| s * w).sum::<f32>() / weight_sum
+            })
+            .collect::<Vec<_>>()
+    };
+
+    // [Check, Bet(50), AllIn(300)]
+    let frequencies = root_frequencies(&[0.2, 0.5, 0.3]);
+    assert!((frequencies[0] - 0.2).abs() < 0.03);
+    assert!((frequencies[1] - 0.5).abs() < 0.03);
+    assert!((frequencies[2] - 0.3).abs() < 0.03);
+
+    // bets 70% with any size
+    let frequencies = root_frequencies(&[0.3, -1.0, -1.0]);
+    assert!((frequencies[0] - 0.3).abs() < 0.03);
+
+    // the exploitability respects the locked frequencies and reaches a positive target
+    let action_tree = ActionTree::new(tree_config.clone()).unwrap();
+    let mut game = PostFlopGame::with_config(card_config.clone(), action_tree).unwrap();
+    game.set_payoff_model(PayoffModel::ChipEv);
+    game.allocate_memory(false);
+    game.lock_current_frequencies(&[0.2, 0.5, 0.3]);
+    let options = SolveOptions {
+        max_num_iterations: 1000,
+        target_exploitability: 0.5,
+        ..Default::default()
+    };
+    let report = solve_with_options(&mut game, &options, |_, _| ControlFlow::Continue(()));
+    assert_eq!(report.stop_reason, StopReason::TargetExploitability);
+    assert!(report.exploitability <= 0.5);
+
+    // the frequency lock is ignored with the restricted Nash response of `p = 0.0`
+    let root_strategy = |lock: bool| {
+        let action_tree = ActionTree::new(tree_config.clone()).unwrap();
+        let mut game = PostFlopGame::with_config(card_config.clone(), action_tree).unwrap();
+        game.set_payoff_model(PayoffModel::ChipEv);
+        game.allocate_memory(false);
+        if lock {
+            game.lock_current_frequencies(&[0.2, 0.5, 0.3]);
+        }
+        let options = SolveOptions {
+            max_num_iterations: 100,
+            restricted_nash_response: Some(0.0),
+            ..Default::default()
+        };
+        solve_with_options(&mut game, &options, |_, _| ControlFlow::Continue(()));
+        game.strategy()
+    };
+    assert_eq!(root_strategy(true), root_strategy(false));
+}
+
+#[test]
+fn lock_by_rules() {
+    let (mut card_config, tree_config) = test_spot(BoardState::Turn, ("50%", ""));
+    card_config.range[0] = "AA,QQ,AKo,T9s".parse().unwrap();
+
+    let action_tree = ActionTree::new(tree_config.clone()).unwrap();
+    let mut game = PostFlopGame::with_config(card_config.clone(), action_tree).unwrap();
+    game.allocate_memory(false);
+
+    // [Fold, Call]
+    let rules = [
+        LockRule {
+            range: None,
+            categories: vec![HandCategory::ThreeOfAKind, HandCategory::TwoPair],
+            frequencies: vec![0.0, 1.0],
+        },
+        LockRule {
+            range: Some("AK".parse().unwrap()),
+            categories: vec![HandCategory::HighCard],
+            frequencies: vec![1.0, 0.0],
+        },
+    ];
+
+    // check, bet
+    game.play(0);
+    game.play(1);
+    game.lock_current_strategy_by_rules(&rules);
+
+    let locked_frequencies = |game: &PostFlopGame, hand: &str| {
+        let cards = game.private_cards(0);
+        let num_hands = cards.len();
+        let index = holes_to_strings(cards)
+            .unwrap()
+            .iter()
+            .position(|s| s == hand)
+            .unwrap();
+        let locking = game.current_locking_strategy().unwrap();
+        [locking[index], locking[num_hands + index]]
+    };
+
+    assert_eq!(locked_frequencies(&game, "QsQh"), [0.0, 1.0]);
+    assert_eq!(locked_frequencies(&game, "Ts9s"), [0.0, 1.0]);
+    assert_eq!(locked_frequencies(&game, "AsKh"), [1.0, 0.0]);
+    assert_eq!(locked_frequencies(&game, "AsAh"), [-1.0, -1.0]);
+    assert_eq!(game.lock_rules().len(), 1);
+    assert_eq!(game.lock_rules()[0].0, [Action::Check, Action::Bet(50)]);
+
+    // the rules are re-applied after rebuilding the game with a different turn
+    let card_config = CardConfig {
+        turn: card_from_str("2s").unwrap(),
+        ..card_config
+    };
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    game.update_config(card_config, action_tree).unwrap();
+    game.allocate_memory(false);
+    game.apply_history(&[0, 1]);
+    assert_eq!(locked_frequencies(&game, "QhQd"), [-1.0, -1.0]);
+    assert_eq!(locked_frequencies(&game, "Th9h"), [0.0, 1.0]);
+
+    game.unlock_current_strategy();
+    assert!(game.current_locking_strategy().is_none());
+    assert!(game.lock_rules().is_empty());
+}
+
+#[test]
+fn export_import_locks() {
+    let (mut card_config, tree_config) = test_spot(BoardState::Turn, ("50%", ""));
+    card_config.range[0] = "AA,QQ,AKo,T9s".parse().unwrap();
+    card_config.range[1] = "JJ-88,AQ,KQ".parse().unwrap();
+
+    let action_tree = ActionTree::new(tree_config.clone()).unwrap();
+    let mut game = PostFlopGame::with_config(card_config.clone(), action_tree).unwrap();
+    game.allocate_memory(false);
+
+    // root: [Check, Bet(50)]
+    let num_hands = game.num_private_hands(0);
+    let mut strategy = vec![0.0; 2 * num_hands];
+    strategy[num_hands..].fill(1.0);
+    game.lock_current_strategy(&strategy);
+
+    // check, check, Ah
+    let river = card_from_str("Ah").unwrap();
+    game.apply_history(&[0, 0, river as usize]);
+    let mut strategy = vec![-1.0; 2 * num_hands];
+    strategy[0] = 1.0;
+    strategy[num_hands] = 0.0;
+    game.lock_current_strategy(&strategy);
+    game.back_to_root();
+
+    let lock_set = game.export_locks();
+    assert_eq!(lock_set.locks.len(), 2);
+    assert_eq!(lock_set.locks[0].history, "");
+    assert_eq!(lock_set.locks[0].actions, ["Check", "Bet(50)"]);
+    assert_eq!(lock_set.locks[0].strategy.len(), num_hands);
+    assert_eq!(lock_set.locks[1].history, "Check/Check/Ah");
+    assert_eq!(lock_set.locks[1].strategy.len(), 1);
+
+    let json = serde_json::to_string(&lock_set).unwrap();
+    let lock_set: LockSet = serde_json::from_str(&json).unwrap();
+
+    // import into a game with a different range and turn card
+    let card_config = CardConfig {
+        range: ["AA,AKo".parse().unwrap(), card_config.range[1]],
+        turn: card_from_str("Ah").unwrap(),
+        ..card_config
+    };
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game2 = PostFlopGame::with_config(card_config, action_tree).unwrap();
+    game2.allocate_memory(false);
+
+    let skipped = game2.import_locks(&lock_set).unwrap();
+    assert_eq!(skipped, ["Check/Check/Ah"]);
+    let num_hands = game2.num_private_hands(0);
+    let locking = game2.current_locking_strategy().unwrap();
+    assert!(locking[..num_hands].iter().all(|&x| x == 0.0));
+    assert!(locking[num_hands..].iter().all(|&x| x == 1.0));
+
+    // malformed lock set
+    let mut lock_set = lock_set;
+    lock_set.locks[0].history = "Check/Bet".to_string();
+    assert!(game2.import_locks(&lock_set).is_err());
+}
+
+#[test]
+fn entropy_regularization() {
+    let (mut card_config, tree_config) = test_spot(BoardState::River, ("50%", ""));
+    card_config.range[0] = "AA,QQ,AKo,T9s,87s".parse().unwrap();
+
+    let temperature = 5.0;
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+    game.set_payoff_model(PayoffModel::ChipEv);
+    game.allocate_memory(false);
+
+    let options = SolveOptions {
+        max_num_iterations: 1000,
+        temperature: Some(temperature),
+        ..Default::default()
+    };
+    let report = solve_with_options(&mut game, &options, |_, _| ControlFlow::Continue(()));
+    assert!(report.exploitability > 0.0);
+
+    // IP facing a bet: [Fold, Call] with terminal children, so the strategy is the logit
+    // response to the EVs of the actions
+    game.apply_history(&[1]);
+    game.cache_normalized_weights();
+    let num_hands = game.num_private_hands(1);
+    let strategy = game.strategy();
+    let ev = game.expected_values_detail(1);
+    let weights = game.normalized_weights(1);
+    let mut max_error = 0.0f32;
+    for hand in 0..num_hands {
+        if weights[hand] < 1e-2 || strategy[hand].min(strategy[num_hands + hand]) < 1e-2 {
+            continue;
+        }
+        let logit = (strategy[num_hands + hand] / strategy[hand]).ln();
+        let expected = (ev[num_hands + hand] - ev[hand]) / temperature;
+        max_error = max_error.max((logit - expected).abs());
+    }
+    assert!(max_error < 0.01);
+}
+
+#[test]
+fn restricted_nash_response() {
+    let solve_game = |lock: bool, restricted_nash_response: Option<f32>| {
+        let (mut card_config, tree_config) = test_spot(BoardState::River, ("50%", ""));
+        card_config.range[0] = "AA,QQ,AKo,T9s,87s,54s".parse().unwrap();
+
+        let action_tree = ActionTree::new(tree_config).unwrap();
+        let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+        game.set_payoff_model(PayoffModel::ChipEv);
+        game.allocate_memory(false);
+
+        // IP always folds facing a bet: [Fold, Call]
+        if lock {
+            game.play(1);
+            let num_hands = game.num_private_hands(1);
+            let mut strategy = vec![0.0; 2 * num_hands];
+            strategy[..num_hands].fill(1.0);
+            game.lock_current_strategy(&strategy);
+            game.back_to_root();
+        }
+
+        let options = SolveOptions {
+            max_num_iterations: 1000,
+            restricted_nash_response,
+            ..Default::default()
+        };
+        solve_with_options(&mut game, &options, |_, _| ControlFlow::Continue(()));
+
+        // root strategy of OOP and the EV of IP best-responding to the strategy of OOP
+        let (_, report) = game.best_response(1).unwrap();
+        (game.strategy(), report.best_response_ev)
+    };
+
+    let (locked, locked_ev) = solve_game(true, None);
+    let (full_trust, full_trust_ev) = solve_game(true, Some(1.0));
+    let (_, nash_ev) = solve_game(false, None);
+    let (_, no_trust_ev) = solve_game(true, Some(0.0));
+    let (_, half_trust_ev) = solve_game(true, Some(0.5));
+
+    // `p = 1.0` is the usual node locking, and `p = 0.0` ignores the locks
+    assert!(locked
+        .iter()
+        .zip(&full_trust)
+        .all(|(x, y)| (x - y).abs() < 1e-4));
+    assert!((locked_ev - full_trust_ev).abs() < 1e-2);
+    assert!((nash_ev - no_trust_ev).abs() < 1e-2);
+
+    // OOP is less exploitable as the read is trusted less
+    assert!(nash_ev + 1.0 < half_trust_ev);
+    assert!(half_trust_ev + 1.0 < locked_ev);
+}
+
+#[test]
+fn purification() {
+    let (card_config, tree_config) = test_spot(BoardState::Turn, ("50%", ""));
+
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+    game.set_payoff_model(PayoffModel::ChipEv);
+    game.allocate_memory(false);
+    solve(&mut game, 200, 0.0, false);
+
+    let current_ev = compute_current_ev(&game);
+
+    // each hand takes its most frequent action
+    let (purified, report) = game.purify(0, Purification::Pure).unwrap();
+    assert_eq!(report.player, 0);
+    assert!(report.ev_loss.icm.is_none());
+    assert!((current_ev[0] - report.ev_loss.chips - compute_current_ev(&purified)[0]).abs() < 1e-3);
+    assert!((report.nodes[0].ev_loss.chips - report.ev_loss.chips).abs() < 1e-3);
+    assert!(purified.current_locking_strategy().is_some());
+    assert!(purified.strategy().iter().all(|&x| x == 0.0 || x == 1.0));
+
+    // the frequencies are multiples of 0.25
+    let (purified, _) = game.purify(0, Purification::Round(0.25)).unwrap();
+    let strategy = purified.strategy();
+    assert!(strategy
+        .iter()
+        .all(|&x| (x * 4.0 - (x * 4.0).round()).abs() < 1e-4));
+
+    // the frequencies less than 0.2 are removed
+    let (purified, _) = game.purify(0, Purification::Threshold(0.2)).unwrap();
+    assert!(purified.strategy().iter().all(|&x| x == 0.0 || x >= 0.2));
+
+    // the hands of each hand class take the same action
+    let (purified, _) = game.purify(0, Purification::PureByHandClass).unwrap();
+    let hands = holes_to_strings(purified.private_cards(0)).unwrap();
+    let strategy = purified.strategy();
+    let num_hands = hands.len();
+    let action_of = |hand: &str| {
+        let index = hands.iter().position(|s| s == hand).unwrap();
+        strategy[index..]
+            .iter()
+            .step_by(num_hands)
+            .position(|&x| x == 1.0)
+    };
+    assert_eq!(action_of("AsKs"), action_of("AhKh"));
+    assert_eq!(action_of("AsKh"), action_of("AdKc"));
+    assert_eq!(action_of("AsAh"), action_of("AdAc"));
+
+    assert!(game.purify(0, Purification::Round(0.0)).is_err());
+    assert!(game.purify(0, Purification::Round(0.3)).is_err());
+}
+
+#[test]
+fn bet_size_selection() {
+    let (card_config, tree_config) = test_spot(BoardState::River, ("25%, 75%, 150%", ""));
+
+    let config = crate::bet_selection::BetSelectionConfig {
+        max_num_sizes: 1,
+        payoff_model: Some(PayoffModel::ChipEv),
+        solve_options: SolveOptions {
+            max_num_iterations: 300,
+            target_exploitability: 0.0,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let num_root_bets = action_tree.available_actions().len() - 1;
+    let report =
+        crate::bet_selection::select_bet_sizes(&card_config, action_tree, &config).unwrap();
+
+    // only one bet action is kept at the root
+    let root_usages = report.usages.iter().filter(|u| u.line.is_empty());
+    assert_eq!(root_usages.clone().count(), num_root_bets);
+    assert_eq!(root_usages.clone().filter(|u| u.is_kept).count(), 1);
+    assert!(report
+        .usages
+        .iter()
+        .all(|u| (0.0..=1.0 + 1e-4).contains(&u.frequency)));
+
+    let mut action_tree = report.action_tree().unwrap();
+    assert_eq!(action_tree.available_actions().len(), 2);
+    action_tree.play(Action::Check).unwrap();
+    assert_eq!(action_tree.available_actions().len(), 2);
+
+    let ev_cost = report.ev_cost();
+    assert!((ev_cost[0] + ev_cost[1]).abs() < 1e-2);
+    assert!(report.exploitability.iter().all(|&e| e < 1.0));
+}
+
+#[test]
+fn action_translation() {
+    let (card_config, tree_config) = test_spot(BoardState::River, ("33%, 75%", ""));
+
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+    game.set_payoff_model(PayoffModel::ChipEv);
+    game.allocate_memory(false);
+    solve(&mut game, 100, 0.0, false);
+
+    // 43% of the pot is mapped to 33% of the pot
+    let mapping = game.play_amount(43, ActionTranslation::PseudoHarmonic);
+    assert_eq!(mapping.lower, Some(Action::Bet(33)));
+    assert_eq!(mapping.upper, Some(Action::Bet(75)));
+    assert!((mapping.lower_probability - 0.7086).abs() < 1e-3);
+    assert_eq!(mapping.action, Action::Bet(33));
+    assert_eq!(game.total_bet_amount(), [33, 0]);
+    assert_eq!(game.real_total_bet_amount(), [43, 0]);
+
+    // 60% of the pot is mapped to 75% of the pot
+    game.back_to_root();
+    let mapping = game.play_amount(60, ActionTranslation::PseudoHarmonic);
+    assert_eq!(mapping.action, Action::Bet(75));
+    let mapping = game.play_amount(60, ActionTranslation::Nearest);
+    assert_eq!(mapping.action, Action::Call);
+    assert_eq!(game.real_total_bet_amount(), [60, 60]);
+
+    // the expected values are scaled to the real pot
+    game.back_to_root();
+    game.play_amount(60, ActionTranslation::Randomized(0));
+    game.cache_normalized_weights();
+    let ratio = 160.0 / (100 + game.total_bet_amount()[0]) as f32;
+    let ev = game.expected_values(1);
+    let real_ev = game.real_expected_values(1);
+    assert!(ev
+        .iter()
+        .zip(&real_ev)
+        .all(|(&a, &b)| (a * ratio - b).abs() < 1e-2));
+
+    // a check is mapped to the check action
+    game.back_to_root();
+    let mapping = game.play_amount(0, ActionTranslation::Nearest);
+    assert_eq!(mapping.action, Action::Check);
+
+    // a small bet mapped to the check action is played as a check
+    game.back_to_root();
+    let mapping = game.play_amount(5, ActionTranslation::Nearest);
+    assert_eq!(mapping.action, Action::Check);
+    assert_eq!(game.real_total_bet_amount(), [0, 0]);
+    let mapping = game.play_amount(33, ActionTranslation::Nearest);
+    assert_eq!(mapping.action, Action::Bet(33));
+    assert_eq!(game.real_total_bet_amount(), [0, 33]);
+
+    // a small raise mapped to the call action is played as a call
+    game.back_to_root();
+    game.play_amount(40, ActionTranslation::Nearest);
+    let mapping = game.play_amount(45, ActionTranslation::Nearest);
+    assert_eq!(mapping.action, Action::Call);
+    assert_eq!(game.real_total_bet_amount(), [40, 40]);
+}
+
+#[test]
+fn strategy_transfer() {
+    let build_game = |oop_sizes: &str, ip_sizes: &str| {
+        let (card_config, mut tree_config) = test_spot(BoardState::River, (oop_sizes, ""));
+        tree_config.river_bet_sizes[1] = BetSizeCandidates::try_from((ip_sizes, "")).unwrap();
+        let action_tree = ActionTree::new(tree_config).unwrap();
+        let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+        game.set_payoff_model(PayoffModel::ChipEv);
+        game.allocate_memory(false);
+        game
+    };
+
+    let mut source = build_game("33%, 75%", "");
+    solve(&mut source, 1000, 0.05, false);
+
+    // lock the transferred strategy
+    let mut target = build_game("50%", "50%");
+    let report = target
+        .transfer_strategy(&mut source, TransferMode::Lock)
+        .unwrap();
+    assert_eq!(report.num_unmapped_nodes, 1);
+    assert_eq!(
+        report.issues,
+        vec![TransferIssue {
+            history: vec![0],
+            kind: TransferIssueKind::MissingAction(Action::Bet(50)),
+        }]
+    );
+
+    // the bet frequencies of the root are merged into the single bet size
+    let source_strategy = source.strategy();
+    let target_strategy = target.current_locking_strategy().unwrap();
+    let num_hands = target.num_private_hands(0);
+    for hand in 0..num_hands {
+        let check = source_strategy[hand];
+        assert!((target_strategy[hand] - check).abs() < 1e-5);
+        assert!((target_strategy[num_hands + hand] - (1.0 - check)).abs() < 1e-5);
+    }
+
+    // seed the solver with the transferred strategy of nearby bet sizes
+    let cold = build_game("40%, 70%", "");
+    let mut warm = build_game("40%, 70%", "");
+    let mode = TransferMode::WarmStart(10.0);
+    let report = warm.transfer_strategy(&mut source, mode).unwrap();
+    assert_eq!(report.num_unmapped_nodes, 0);
+    assert!(report.issues.is_empty());
+    assert!(compute_exploitability(&warm) < 0.25 * compute_exploitability(&cold));
+}
+
+#[test]
+fn hand_history_replay() {
+    let text = "\
+PokerStars Hand #123456789:  Hold'em No Limit ($0.50/$1.00 USD) - 2024/01/01 12:00:00 ET
+Table 'Test' 6-max Seat #1 is the button
+Seat 1: Alice ($50 in chips)
+Seat 2: Bob ($45.50 in chips)
+Seat 3: Carol ($100 in chips)
+Bob: posts small blind $0.50
+Carol: posts big blind $1
+*** HOLE CARDS ***
+Dealt to Carol [Ah Kd]
+Alice: raises $1.50 to $2.50
+Bob: folds
+Carol: calls $1.50
+*** FLOP *** [Td 9d 6h]
+Carol: checks
+Alice: bets $2.25
+Carol: calls $2.25
+*** TURN *** [Td 9d 6h] [Qc]
+Carol: checks
+Alice: checks
+*** RIVER *** [Td 9d 6h Qc] [2s]
+Carol: bets $4.50
+Alice: calls $4.50
+*** SHOW DOWN ***
+Alice: shows [Jh Jc] (a pair of Jacks)
+Carol: shows [Ah Kd] (high card Ace)
+Alice collected $19 from pot
+*** SUMMARY ***
+Total pot $19 | Rake $0
+";
+
+    let hands = parse_hand_histories(&format!("{text}\n\n{text}"));
+    assert_eq!(hands.len(), 2);
+
+    let hand = HandHistory::parse(text).unwrap();
+    assert_eq!(hand.hand_id, "123456789");
+    assert_eq!(hand.players, ["Carol".to_string(), "Alice".to_string()]);
+    assert_eq!(hand.hero, Some(0));
+    let hero_cards = (card_from_str("Kd").unwrap(), card_from_str("Ah").unwrap());
+    assert_eq!(hand.hero_cards, Some(hero_cards));
+    assert_eq!(hand.board.len(), 5);
+    assert_eq!(hand.pots, vec![5.5, 10.0, 10.0]);
+    assert_eq!(hand.stacks, [97.5, 47.5]);
+    assert_eq!(hand.actions.len(), 7);
+    assert_eq!(hand.actions[6].kind, HandActionKind::Call(4.5));
+
+    let (card_config, tree_config) = test_spot(BoardState::River, ("50%", ""));
+
+    let action_tree = ActionTree::new(tree_config.clone()).unwrap();
+    let mut game = PostFlopGame::with_config(card_config.clone(), action_tree).unwrap();
+    game.set_payoff_model(PayoffModel::ChipEv);
+    game.allocate_memory(false);
+
+    let translation = ActionTranslation::PseudoHarmonic;
+    assert!(game.replay_hand_history(&hand, translation).is_err());
+    solve(&mut game, 1000, 0.1, false);
+
+    // a hand without the flop is rejected
+    let mut no_flop = hand.clone();
+    no_flop.board.truncate(2);
+    assert!(game.replay_hand_history(&no_flop, translation).is_err());
+
+    // only the river decision of the hero is replayed; $4.50 into $10 is mapped to Bet(50)
+    let decisions = game.replay_hand_history(&hand, translation).unwrap();
+    assert_eq!(decisions.len(), 1);
+    let decision = &decisions[0];
+    assert!(decision.history.is_empty());
+    assert_eq!(decision.hand_action.kind, HandActionKind::Bet(4.5));
+    assert_eq!(decision.action, Action::Bet(50));
+    assert_eq!(decision.actions.len(), decision.strategy.len());
+    assert!((decision.strategy.iter().sum::<f32>() - 1.0).abs() < 1e-4);
+    assert!(decision.ev_loss.chips >= 0.0);
+    assert_eq!(decision.ev_loss.icm, None);
+    assert!(game.history().is_empty());
+
+    // with the ICM payoff model, the losses are also reported in chips
+    let icm = IcmUtility::from_json(ICM_STATE).unwrap();
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+    game.set_payoff_model(PayoffModel::Icm(std::sync::Arc::new(icm)));
+    game.allocate_memory(false);
+    solve(&mut game, 1000, 0.001, false);
+
+    let decisions = game.replay_hand_history(&hand, translation).unwrap();
+    assert_eq!(decisions.len(), 1);
+    let decision = &decisions[0];
+    assert_eq!(decision.action, Action::Bet(50));
+    assert!(decision.ev_loss.chips >= 0.0);
+    assert!(decision.ev_loss.icm.unwrap() >= 0.0);
+    assert!(game.history().is_empty());
+}
+
+#[test]
+fn hand_history_replay_turn() {
+    let text = "\
+Poker Hand #RC1000000001: Hold'em No Limit ($0.5/$1) - 2024/01/01 12:00:00
+Table 'RushAndCash1' 2-max Seat #1 is the button
+Seat 1: Hero ($100 in chips)
+Seat 2: 5a1b2c3d ($100 in chips)
+Hero: posts small blind $0.5
+5a1b2c3d: posts big blind $1
+*** HOLE CARDS ***
+Dealt to Hero [As Qh]
+Dealt to 5a1b2c3d
+Hero: raises $4 to $5
+5a1b2c3d: calls $4
+*** FLOP *** [Td 9d 6h]
+5a1b2c3d: checks
+Hero: checks
+*** TURN *** [Td 9d 6h] [Qc]
+5a1b2c3d: bets $5
+Hero: raises $12 to $17
+5a1b2c3d: calls $12
+*** RIVER *** [Td 9d 6h Qc] [2s]
+5a1b2c3d: checks
+Hero: bets $22
+5a1b2c3d: folds
+Uncalled bet ($22) returned to Hero
+*** SHOWDOWN ***
+Hero collected $44 from pot
+*** SUMMARY ***
+Total pot $44 | Rake $0 | Jackpot $0
+";
+
+    let hand = HandHistory::parse(text).unwrap();
+    assert_eq!(hand.players, ["5a1b2c3d".to_string(), "Hero".to_string()]);
+    assert_eq!(hand.hero, Some(1));
+    assert_eq!(hand.pots, vec![10.0, 10.0, 44.0]);
+
+    let (card_config, mut tree_config) = test_spot(BoardState::Turn, ("50%", "60%"));
+    tree_config.effective_stack = 950;
+
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+    game.set_payoff_model(PayoffModel::ChipEv);
+    game.allocate_memory(false);
+    solve(&mut game, 200, 0.5, false);
+
+    // the flop is skipped, the turn raise to $17 is mapped to Raise(170), and the river is dealt
+    let translation = ActionTranslation::PseudoHarmonic;
+    let decisions = game.replay_hand_history(&hand, translation).unwrap();
+    assert_eq!(decisions.len(), 2);
+    assert_eq!(decisions[0].hand_action.kind, HandActionKind::Raise(17.0));
+    assert_eq!(decisions[0].action, Action::Raise(170));
+    assert_eq!(decisions[0].history.len(), 1);
+    assert_eq!(decisions[1].hand_action.street, BoardState::River);
+    assert_eq!(decisions[1].action, Action::Bet(220));
+    let river = card_from_str("2s").unwrap() as usize;
+    assert_eq!(decisions[1].history[3], river);
+    assert!(decisions.iter().all(|d| d.ev_loss.chips >= 0.0));
+    assert!(game.history().is_empty());
+}
+
+#[test]
+fn trainer_session() {
+    let (card_config, tree_config) = test_spot(BoardState::Turn, ("50%", ""));
+
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+    game.set_payoff_model(PayoffModel::ChipEv);
+    game.allocate_memory(false);
+
+    let config = TrainerConfig {
+        seed: 42,
+        player: Some(1),
+        ..Default::default()
+    };
+
+    let mut session = TrainerSession::new(config);
+    assert!(session.next_question(&mut game).is_err());
+    solve(&mut game, 1000, 0.1, false);
+
+    let mut replayed = TrainerSession::new(config);
+    for _ in 0..20 {
+        let question = session.next_question(&mut game).unwrap();
+        assert_eq!(question, replayed.next_question(&mut game).unwrap());
+        assert_eq!(question.player, 1);
+        assert_eq!(question.line.len(), question.history.len());
+        assert!(!question.board.contains(&question.hand.0));
+        assert!(!question.board.contains(&question.hand.1));
+        assert!(game.history().is_empty());
+
+        // the best action has no EV loss; the others are compared with the threshold
+        let best = session.answer(&mut game, &question, 0).unwrap();
+        let best_index = question
+            .actions
+            .iter()
+            .position(|&a| a == best.best_action)
+            .unwrap();
+        let answer = session.answer(&mut game, &question, best_index).unwrap();
+        assert_eq!(answer.ev_loss, 0.0);
+        assert!(answer.is_correct);
+        assert!((answer.strategy.iter().sum::<f32>() - 1.0).abs() < 1e-4);
+        assert!(best.ev_loss >= 0.0);
+        assert_eq!(best.is_correct, best.ev_loss <= 0.01 * question.pot as f32);
+    }
+
+    let stats = session.stats();
+    assert_eq!(stats.num_answers, 40);
+    assert!(stats.num_correct >= 20);
+    let category_stats = session.category_stats();
+    let num_answers = category_stats
+        .values()
+        .map(|s| s.num_answers)
+        .sum::<usize>();
+    assert_eq!(num_answers, 40);
+
+    // a question that does not match the game is rejected
+    let mut question = session.next_question(&mut game).unwrap();
+    question.hand_index ^= 1;
+    assert!(session.answer(&mut game, &question, 0).is_err());
+    assert!(session.answer(&mut game, &question, 99).is_err());
+    assert_eq!(session.stats().num_answers, 40);
+}
+
+#[test]
+fn trainer_session_icm() {
+    let (card_config, tree_config) = test_spot(BoardState::River, ("50%", ""));
+
+    let icm = std::sync::Arc::new(IcmUtility::from_json(ICM_STATE).unwrap());
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+    game.set_payoff_model(PayoffModel::Icm(icm.clone()));
+    game.allocate_memory(false);
+    solve(&mut game, 1000, 0.0001, false);
+
+    // the threshold is the fraction of the pot valued by the ICM payoff model
+    let config = TrainerConfig::default();
+    let mut session = TrainerSession::new(config);
+    for _ in 0..20 {
+        let question = session.next_question(&mut game).unwrap();
+        let stack = icm.starting_stack(question.player);
+        let half_pot = 0.5 * question.pot as f64;
+        let pot_value = icm.lookup(stack + half_pot, question.player)
+            - icm.lookup(stack - half_pot, question.player);
+        assert!(pot_value > 0.0 && pot_value < question.pot as f64 * 0.01);
+
+        for action in 0..question.actions.len() {
+            let answer = session.answer(&mut game, &question, action).unwrap();
+            let threshold = config.max_ev_loss * pot_value as f32;
+            assert_eq!(answer.is_correct, answer.ev_loss <= threshold);
+        }
+    }
+
+    let stats = session.stats();
+    assert!(stats.num_correct >= 20);
+    assert!(stats.num_correct < stats.num_answers);
+}
@@ -0,0 +1,374 @@
+use super::translation::pseudo_harmonic;
+use super::*;
+use crate::interface::*;
+use crate::sliceop::*;
+
+/// How [`PostFlopGame::transfer_strategy`] uses the transferred strategy.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TransferMode {
+    /// Locks the transferred strategy (see [`PostFlopGame::lock_current_strategy`]).
+    Lock,
+
+    /// Seeds the cumulative strategy and the regrets with the given weight (see
+    /// [`PostFlopGame::warm_start`]).
+    WarmStart(f32),
+}
+
+/// A kind of [`TransferIssue`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TransferIssueKind {
+    /// The node has no counterpart in the source game (e.g., the acting player differs or the
+    /// source line ends). The subtree is not transferred.
+    MissingNode,
+
+    /// The action of this game has no counterpart in the source game (e.g., a bet where the source
+    /// game can only check). The subtree of the action is not transferred.
+    MissingAction(Action),
+
+    /// The action of the source game has no counterpart in this game. Its frequency is dropped
+    /// and the other frequencies are renormalized.
+    DroppedAction(Action),
+}
+
+/// A node where [`PostFlopGame::transfer_strategy`] could not find a sensible mapping.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TransferIssue {
+    /// History of the node in this game, which can be passed to [`PostFlopGame::apply_history`].
+    ///
+    /// Suit-isomorphic runouts share a node, and only the representative card appears.
+    pub history: Vec<usize>,
+
+    /// The kind of the issue.
+    pub kind: TransferIssueKind,
+}
+
+/// A report of [`PostFlopGame::transfer_strategy`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TransferReport {
+    /// The number of player nodes whose strategy is transferred.
+    pub num_mapped_nodes: usize,
+
+    /// The number of player nodes whose strategy is not transferred.
+    pub num_unmapped_nodes: usize,
+
+    /// The nodes where no sensible mapping exists, in depth-first order.
+    pub issues: Vec<TransferIssue>,
+}
+
+impl PostFlopGame {
+    /// Transfers the strategy of `source`, which may have different bet sizes, to this game.
+    ///
+    /// Nodes are matched by action history from the root, translating each action to the action
+    /// of the other tree with the nearest size: fold, check, and call actions are matched exactly,
+    /// all-in actions are matched with each other if possible, and the other bet actions are
+    /// matched with the two bet actions of the other tree that bracket their size as a fraction of
+    /// the pot after calling, split by the pseudo-harmonic mapping. At each node, the frequency of
+    /// each action of `source` is distributed to the corresponding actions of this game, hand by
+    /// hand (matched by card pair), and the subtree of each action of this game follows its most
+    /// likely counterpart in `source`. The nodes and hands without a counterpart are left
+    /// untouched, and the issues are reported.
+    ///
+    /// With [`TransferMode::Lock`], the transferred strategy is locked, except for the nodes whose
+    /// aggregate frequencies are locked; with [`TransferMode::WarmStart`], it seeds the solver
+    /// in the same manner as [`warm_start`], which overwrites any previous progress.
+    ///
+    /// The memory of this game must be allocated (with the storage mode of river), and `source`
+    /// must have the same board and initial state and its memory allocated. `source` does not have
+    /// to be solved. The current node of `source` is moved back to the root.
+    ///
+    /// [`warm_start`]: #method.warm_start
+    pub fn transfer_strategy(
+        &mut self,
+        source: &mut PostFlopGame,
+        mode: TransferMode,
+    ) -> Result<TransferReport, String> {
+        match mode {
+            TransferMode::Lock => {
+                if self.state < State::MemoryAllocated || self.storage_mode != BoardState::River {
+                    return Err(
+                        "Memory is not allocated with the storage mode of river".to_string()
+                    );
+                }
+                self.check_source_game(source)?;
+            }
+            TransferMode::WarmStart(weight) => self.check_warm_start_source(source, weight)?,
+        }
+
+        let hand_map = self.hand_map_from(source);
+        let mut strategies = BTreeMap::new();
+        let mut report = TransferReport::default();
+
+        source.back_to_root();
+        self.transfer_recursive(
+            0,
+            source,
+            &hand_map,
+            &mut Vec::new(),
+            &mut strategies,
+            &mut report.issues,
+        );
+        source.back_to_root();
+
+        let num_player_nodes = self
+            .node_arena
+            .iter()
+            .filter(|node| {
+                let node = node.lock();
+                !node.is_terminal() && !node.is_chance()
+            })
+            .count();
+        report.num_mapped_nodes = strategies.len();
+        report.num_unmapped_nodes = num_player_nodes - strategies.len();
+
+        match mode {
+            TransferMode::Lock => {
+                for (index, strategy) in strategies {
+                    if !self.frequency_locks.contains_key(&index) {
+                        self.node_arena[index].lock().is_locked = true;
+                        self.locking_strategy.insert(index, strategy);
+                    }
+                }
+            }
+
+            TransferMode::WarmStart(weight) => {
+                let mut seeded = vec![false; self.node_arena.len()];
+                for node in &self.node_arena {
+                    let mut node = node.lock();
+                    if !node.is_terminal() && !node.is_chance() {
+                        let num_hands = self.private_cards[node.player()].len();
+                        let zeros = vec![0.0; node.num_actions() * num_hands];
+                        self.write_cum_strategy(&mut node, &zeros);
+                    }
+                }
+                for (index, strategy) in strategies {
+                    let cum_strategy = strategy.iter().map(|&x| weight * x.max(0.0));
+                    let cum_strategy = cum_strategy.collect::<Vec<_>>();
+                    self.write_cum_strategy(&mut self.node_arena[index].lock(), &cum_strategy);
+                    seeded[index] = true;
+                }
+                self.seed_regrets(&seeded, weight);
+            }
+        }
+
+        Ok(report)
+    }
+
+    /// The recursive helper function for transferring the strategy. The transferred strategy of
+    /// each node is stored in `strategies`, where the hands without a counterpart are `-1.0`.
+    fn transfer_recursive(
+        &self,
+        node_index: usize,
+        source: &mut PostFlopGame,
+        hand_map: &[Vec<usize>; 2],
+        history: &mut Vec<usize>,
+        strategies: &mut BTreeMap<usize, Vec<f32>>,
+        issues: &mut Vec<TransferIssue>,
+    ) {
+        let node = self.node_arena[node_index].lock();
+        if node.is_terminal() || node.amount == self.tree_config.effective_stack {
+            return;
+        }
+
+        if source.is_terminal_node() || source.on_demand_river().is_some() {
+            push_issue(issues, history, TransferIssueKind::MissingNode);
+            return;
+        }
+
+        let children_index = node_index + node.children_offset as usize;
+
+        // chance node
+        if node.is_chance() {
+            let is_turn = node.turn == NOT_DEALT;
+            if !source.is_chance_node()
+                || source.storage_mode == BoardState::Flop
+                || (!is_turn && source.storage_mode == BoardState::Turn)
+            {
+                push_issue(issues, history, TransferIssueKind::MissingNode);
+                return;
+            }
+
+            let possible_cards = source.possible_cards();
+            for (i, child) in node.children().iter().enumerate() {
+                if let Action::Chance(card) = child.lock().prev_action {
+                    if possible_cards & (1 << card) != 0 {
+                        let source_history = source.history().to_vec();
+                        source.play(card as usize);
+                        history.push(card as usize);
+                        self.transfer_recursive(
+                            children_index + i,
+                            source,
+                            hand_map,
+                            history,
+                            strategies,
+                            issues,
+                        );
+                        history.pop();
+                        source.apply_history(&source_history);
+                    }
+                }
+            }
+
+            return;
+        }
+
+        // player node
+        let player = node.player();
+        if source.is_chance_node() || source.current_player() != player {
+            push_issue(issues, history, TransferIssueKind::MissingNode);
+            return;
+        }
+
+        let actions = node
+            .children()
+            .iter()
+            .map(|child| child.lock().prev_action)
+            .collect::<Vec<_>>();
+        let fractions = self.bet_fractions(&node);
+
+        let source_actions = source.available_actions();
+        let source_fractions = source.bet_fractions(&source.node());
+
+        // distribute the frequencies of the source actions
+        let num_hands = self.private_cards[player].len();
+        let source_num_hands = source.private_cards[player].len();
+        let source_strategy = source.strategy();
+        let mut strategy = vec![0.0; actions.len() * num_hands];
+
+        for (source_action, &action) in source_actions.iter().enumerate() {
+            let targets = counterparts(
+                action,
+                source_fractions[source_action],
+                &actions,
+                &fractions,
+            );
+            if targets.is_empty() {
+                push_issue(issues, history, TransferIssueKind::DroppedAction(action));
+            }
+
+            let src = row(&source_strategy, source_action, source_num_hands);
+            for (target, ratio) in targets {
+                let dst = row_mut(&mut strategy, target, num_hands);
+                for (d, &source_hand) in dst.iter_mut().zip(&hand_map[player]) {
+                    if source_hand != usize::MAX {
+                        *d += ratio * src[source_hand];
+                    }
+                }
+            }
+        }
+
+        for hand in 0..num_hands {
+            let column = (0..actions.len()).map(|action| action * num_hands + hand);
+            let sum = column.clone().fold(0.0, |sum, i| sum + strategy[i]);
+            for i in column {
+                strategy[i] = if sum > 0.0 { strategy[i] / sum } else { -1.0 };
+            }
+        }
+
+        strategies.insert(node_index, strategy);
+
+        // follow the most likely counterpart of each action
+        for (i, &action) in actions.iter().enumerate() {
+            let sources = counterparts(action, fractions[i], &source_actions, &source_fractions);
+            let source_action = sources
+                .iter()
+                .max_by(|a, b| a.1.total_cmp(&b.1).then(b.0.cmp(&a.0)))
+                .map(|&(source_action, _)| source_action);
+
+            let Some(source_action) = source_action else {
+                push_issue(issues, history, TransferIssueKind::MissingAction(action));
+                continue;
+            };
+
+            let source_history = source.history().to_vec();
+            source.play(source_action);
+            history.push(i);
+            self.transfer_recursive(
+                children_index + i,
+                source,
+                hand_map,
+                history,
+                strategies,
+                issues,
+            );
+            history.pop();
+            source.apply_history(&source_history);
+        }
+    }
+
+    /// Returns the size of each action of the node as a fraction of the pot after calling (`None`
+    /// for the fold, check, and call actions).
+    fn bet_fractions(&self, node: &PostFlopNode) -> Vec<Option<f32>> {
+        let children = node.children();
+        let to_call = children
+            .iter()
+            .map(|child| child.lock())
+            .find(|child| child.prev_action != Action::Fold && child.prev_action != Action::Check)
+            .map_or(0, |child| child.amount - node.amount);
+        let prev_amount = match node.prev_action {
+            Action::Bet(amount) | Action::Raise(amount) | Action::AllIn(amount) => amount,
+            _ => 0,
+        };
+        let pot = self.tree_config.starting_pot + 2 * (node.amount + to_call);
+
+        children
+            .iter()
+            .map(|child| match child.lock().prev_action {
+                Action::Bet(amount) | Action::Raise(amount) | Action::AllIn(amount) => {
+                    Some((amount - prev_amount) as f32 / pot as f32)
+                }
+                _ => None,
+            })
+            .collect()
+    }
+}
+
+/// Records an issue at the node of `history`.
+#[inline]
+fn push_issue(issues: &mut Vec<TransferIssue>, history: &[usize], kind: TransferIssueKind) {
+    issues.push(TransferIssue {
+        history: history.to_vec(),
+        kind,
+    });
+}
+
+/// Returns the counterparts of `action` (whose size is `fraction`) among `others`, with the
+/// ratios of the pseudo-harmonic mapping.
+fn counterparts(
+    action: Action,
+    fraction: Option<f32>,
+    others: &[Action],
+    other_fractions: &[Option<f32>],
+) -> Vec<(usize, f32)> {
+    let exact = |f: &dyn Fn(&Action) -> bool| others.iter().position(f).map(|i| vec![(i, 1.0)]);
+
+    let Some(fraction) = fraction else {
+        return exact(&|&a| a == action).unwrap_or_default();
+    };
+
+    if let Action::AllIn(_) = action {
+        if let Some(ret) = exact(&|a| matches!(a, Action::AllIn(_))) {
+            return ret;
+        }
+    }
+
+    let bets = other_fractions
+        .iter()
+        .enumerate()
+        .filter_map(|(i, f)| f.map(|f| (i, f)));
+    let lower = bets
+        .clone()
+        .filter(|&(_, f)| f <= fraction)
+        .max_by(|a, b| a.1.total_cmp(&b.1));
+    let upper = bets
+        .filter(|&(_, f)| f >= fraction)
+        .min_by(|a, b| a.1.total_cmp(&b.1));
+
+    match (lower, upper) {
+        (Some(l), Some(u)) if l.1 < u.1 => {
+            let p = pseudo_harmonic(l.1, u.1, fraction);
+            vec![(l.0, p), (u.0, 1.0 - p)]
+        }
+        (Some(x), _) | (None, Some(x)) => vec![(x.0, 1.0)],
+        (None, None) => Vec::new(),
+    }
+}
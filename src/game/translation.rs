@@ -0,0 +1,218 @@
+use super::*;
+use crate::utility::*;
+
+/// A scheme of mapping an off-tree bet amount to the actions of the tree (see
+/// [`PostFlopGame::play_amount`]).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ActionTranslation {
+    /// Maps to the smaller action if the pseudo-harmonic mapping assigns it a probability of at
+    /// least 0.5, and to the larger action otherwise.
+    #[default]
+    PseudoHarmonic,
+
+    /// Maps to the action with the nearest size in terms of pot fractions.
+    Nearest,
+
+    /// Maps randomly with the probabilities of the pseudo-harmonic mapping. The random choice is
+    /// determined by the given seed and the length of the current history.
+    Randomized(u64),
+}
+
+/// A result of [`PostFlopGame::play_amount`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ActionMapping {
+    /// The real bet amount in the current street (the same unit as [`Action::Bet`]).
+    pub amount: i32,
+
+    /// The real bet size as a fraction of the pot after calling.
+    pub pot_fraction: f32,
+
+    /// The largest action of the tree whose size is at most the real size (a check or call
+    /// action has size zero).
+    pub lower: Option<Action>,
+
+    /// The smallest action of the tree whose size is at least the real size.
+    pub upper: Option<Action>,
+
+    /// The probability of mapping to `lower` by the translation scheme.
+    pub lower_probability: f32,
+
+    /// The action of the tree actually played.
+    pub action: Action,
+
+    /// The index of `action` in [`PostFlopGame::available_actions`].
+    pub action_index: usize,
+}
+
+impl PostFlopGame {
+    /// Plays an arbitrary bet amount by mapping it to an action of the tree with `translation`.
+    ///
+    /// `amount` is the total amount of the real bet (or raise) of the current player in the current
+    /// street, in the same unit as the amount of [`Action::Bet`] and [`Action::Raise`], measured
+    /// from the real bet amounts played so far. The sizes are compared as fractions of the pot
+    /// after calling, the real size in the real pot and the sizes of the tree in the pot of the
+    /// tree. A real size of zero (i.e., a check or call) is mapped to the check or call action.
+    /// If a real bet (or raise) is mapped to the check or call action, it is played as a check or
+    /// call also in the real bet amounts.
+    ///
+    /// The real bet amounts are carried forward (see [`real_total_bet_amount`]); actions played
+    /// with [`play`] keep the real amount to call and add the same raise as the tree. They are
+    /// reset by [`back_to_root`] and [`apply_history`].
+    ///
+    /// Panics if the memory is not allocated, the current node is a terminal node or a chance
+    /// node, or `amount` is less than the amount to call.
+    ///
+    /// [`real_total_bet_amount`]: #method.real_total_bet_amount
+    /// [`play`]: #method.play
+    /// [`back_to_root`]: #method.back_to_root
+    /// [`apply_history`]: #method.apply_history
+    pub fn play_amount(&mut self, amount: i32, translation: ActionTranslation) -> ActionMapping {
+        if self.state < State::MemoryAllocated {
+            panic!("Memory is not allocated");
+        }
+
+        if self.is_terminal_node() {
+            panic!("Terminal node is not allowed");
+        }
+
+        if self.is_chance_node() {
+            panic!("Chance node is not allowed");
+        }
+
+        let player = self.current_player();
+        let opponent = player ^ 1;
+        let real_amount = self.real_street_bet_base + amount;
+        if amount < 0 || real_amount < self.real_total_bet_amount[opponent] {
+            panic!("Invalid amount");
+        }
+
+        let starting_pot = self.tree_config.starting_pot;
+        let fraction = |new_amount: i32, total_bet_amount: [i32; 2]| {
+            let to_call = total_bet_amount[opponent] - total_bet_amount[player];
+            let pot = starting_pot + total_bet_amount[0] + total_bet_amount[1] + to_call;
+            (new_amount - total_bet_amount[opponent]) as f32 / pot as f32
+        };
+
+        let pot_fraction = fraction(real_amount, self.real_total_bet_amount);
+        let candidates = self
+            .available_actions()
+            .into_iter()
+            .enumerate()
+            .filter_map(|(i, action)| match action {
+                Action::Check | Action::Call => Some((i, action, 0.0)),
+                Action::Bet(x) | Action::Raise(x) | Action::AllIn(x) => {
+                    let new_amount = self.street_bet_base + x;
+                    Some((i, action, fraction(new_amount, self.total_bet_amount)))
+                }
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+
+        let lower = candidates
+            .iter()
+            .filter(|c| c.2 <= pot_fraction)
+            .max_by(|a, b| a.2.total_cmp(&b.2));
+        let upper = candidates
+            .iter()
+            .filter(|c| c.2 >= pot_fraction)
+            .min_by(|a, b| a.2.total_cmp(&b.2));
+
+        let (lower_probability, is_lower) = match (lower, upper) {
+            (Some(l), Some(u)) if l.2 < u.2 => {
+                let p = pseudo_harmonic(l.2, u.2, pot_fraction);
+                match translation {
+                    ActionTranslation::PseudoHarmonic => (p, p >= 0.5),
+                    ActionTranslation::Nearest => {
+                        let is_lower = pot_fraction - l.2 <= u.2 - pot_fraction;
+                        (if is_lower { 1.0 } else { 0.0 }, is_lower)
+                    }
+                    ActionTranslation::Randomized(seed) => {
+                        let len = self.action_history.len() as u64;
+                        let random = (mix_seed(seed, len) >> 40) as f32 / (1 << 24) as f32;
+                        (p, random < p)
+                    }
+                }
+            }
+            (Some(_), _) => (1.0, true),
+            (None, _) => (0.0, false),
+        };
+
+        let &(action_index, action, _) = if is_lower { lower } else { upper }.unwrap();
+
+        self.play(action_index);
+
+        // a check or call of the tree is also a check or call in the real bet amounts
+        if !matches!(action, Action::Check | Action::Call) {
+            self.real_total_bet_amount[player] = real_amount;
+            self.update_street_bet_base();
+        }
+
+        ActionMapping {
+            amount,
+            pot_fraction,
+            lower: lower.map(|c| c.1),
+            upper: upper.map(|c| c.1),
+            lower_probability,
+            action,
+            action_index,
+        }
+    }
+
+    /// Returns the real total bet amount of each player (OOP, IP), which differs from
+    /// [`total_bet_amount`] after off-tree amounts are played with [`play_amount`].
+    ///
+    /// [`total_bet_amount`]: #method.total_bet_amount
+    /// [`play_amount`]: #method.play_amount
+    #[inline]
+    pub fn real_total_bet_amount(&self) -> [i32; 2] {
+        self.real_total_bet_amount
+    }
+
+    /// Returns the expected values of each private hand of the given player, scaled from the pot
+    /// of the tree to the real pot (see [`play_amount`]).
+    ///
+    /// The scaling is proportional to the pot size, which is an approximation for display.
+    /// Otherwise, this method is the same as [`expected_values`].
+    ///
+    /// [`play_amount`]: #method.play_amount
+    /// [`expected_values`]: #method.expected_values
+    pub fn real_expected_values(&self, player: usize) -> Vec<f32> {
+        let starting_pot = self.tree_config.starting_pot;
+        let tree_pot = starting_pot + self.total_bet_amount.iter().sum::<i32>();
+        let real_pot = starting_pot + self.real_total_bet_amount.iter().sum::<i32>();
+        let ratio = real_pot as f32 / tree_pot as f32;
+
+        let mut ret = self.expected_values(player);
+        ret.iter_mut().for_each(|v| *v *= ratio);
+        ret
+    }
+
+    /// Updates the real bet amounts after an action of the tree is played.
+    pub(super) fn update_real_bet_amount(&mut self, prev_total_bet_amount: [i32; 2]) {
+        let total_bet_amount = self.total_bet_amount;
+        for (player, prev_amount) in prev_total_bet_amount.into_iter().enumerate() {
+            if total_bet_amount[player] != prev_amount {
+                // keep the real amount to call and add the raise of the tree
+                let raise = total_bet_amount[player] - total_bet_amount[player ^ 1];
+                self.real_total_bet_amount[player] = self.real_total_bet_amount[player ^ 1] + raise;
+            }
+        }
+        self.update_street_bet_base();
+    }
+
+    /// Updates the bet amounts at the beginning of the current street.
+    fn update_street_bet_base(&mut self) {
+        if self.total_bet_amount[0] == self.total_bet_amount[1] {
+            self.street_bet_base = self.total_bet_amount[0];
+            self.real_street_bet_base =
+                self.real_total_bet_amount[0].max(self.real_total_bet_amount[1]);
+        }
+    }
+}
+
+/// Computes the probability of mapping `x` to `a` instead of `b` by the pseudo-harmonic mapping,
+/// where the sizes are fractions of the pot.
+#[inline]
+pub(super) fn pseudo_harmonic(a: f32, b: f32, x: f32) -> f32 {
+    ((b - x) * (1.0 + a) / ((b - a) * (1.0 + x))).clamp(0.0, 1.0)
+}
@@ -0,0 +1,386 @@
+use super::*;
+use crate::interface::*;
+use crate::sliceop::*;
+use crate::utility::*;
+
+/// A summary of [`PostFlopGame::warm_start`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct WarmStartReport {
+    /// The number of player nodes matched with a node of the source game.
+    pub num_matched_nodes: usize,
+
+    /// The number of player nodes not found in the source game (they start from uniform).
+    pub num_unmatched_nodes: usize,
+}
+
+impl PostFlopGame {
+    /// Seeds the cumulative strategy and the regrets from the strategy of `source`.
+    ///
+    /// Nodes are matched by action history (the actions and the dealt cards from the root) and
+    /// hands are matched by card pair, so `source` may have different ranges, bet sizes, or
+    /// added/removed lines. At each matched node, the cumulative strategy is set to the strategy
+    /// of `source` multiplied by `weight`, and the regrets are set so that regret matching yields
+    /// the same strategy; their magnitude is `weight` times the largest absolute counterfactual
+    /// value of the hand, computed in this game. Unmatched nodes, actions, and hands are left zero,
+    /// i.e., they start from the uniform strategy. Any previous progress of this game is
+    /// overwritten.
+    ///
+    /// `weight` roughly corresponds to the number of iterations that the seed is worth, and the
+    /// same value should be passed as [`SolveOptions::start_iteration`]; since the discounting of
+    /// DCFR-style algorithms clears the regrets at iteration zero, solving from iteration zero
+    /// discards the seed. A moderate value such as `10.0` works well in most cases: larger values
+    /// make the seeded regrets so large that the strategy adapts to the changes slowly.
+    ///
+    /// The memory of this game must be allocated (with the storage mode of river), and `source`
+    /// must have the same board and initial state and its memory allocated. `source` does not have
+    /// to be solved, and its current node is not changed.
+    ///
+    /// [`SolveOptions::start_iteration`]: crate::SolveOptions::start_iteration
+    pub fn warm_start(
+        &mut self,
+        source: &PostFlopGame,
+        weight: f32,
+    ) -> Result<WarmStartReport, String> {
+        self.check_warm_start_source(source, weight)?;
+
+        let source_hand_index = source_hand_index(source);
+
+        // seed the cumulative strategy
+        let mut seeded = vec![false; self.node_arena.len()];
+        self.warm_start_recursive(
+            0,
+            source,
+            0,
+            [0, 1, 2, 3],
+            &source_hand_index,
+            weight,
+            &mut seeded,
+        );
+
+        Ok(self.seed_regrets(&seeded, weight))
+    }
+
+    /// Checks that this game can be seeded from `source` with `weight`.
+    pub(super) fn check_warm_start_source(
+        &self,
+        source: &PostFlopGame,
+        weight: f32,
+    ) -> Result<(), String> {
+        if self.state == State::Solved {
+            return Err("Game is already solved".to_string());
+        }
+
+        if !self.is_ready() {
+            return Err("Game is not ready for solving".to_string());
+        }
+
+        self.check_source_game(source)?;
+
+        if !(weight.is_finite() && weight > 0.0) {
+            return Err(format!("Weight must be positive: {weight}"));
+        }
+
+        Ok(())
+    }
+
+    /// Checks that the nodes of `source` can be matched with those of this game.
+    pub(super) fn check_source_game(&self, source: &PostFlopGame) -> Result<(), String> {
+        if source.state < State::MemoryAllocated {
+            return Err("Memory of the source game is not allocated".to_string());
+        }
+
+        if source.card_config.flop != self.card_config.flop
+            || source.card_config.turn != self.card_config.turn
+            || source.card_config.river != self.card_config.river
+        {
+            return Err("Source game has a different board".to_string());
+        }
+
+        if source.tree_config.initial_state != self.tree_config.initial_state {
+            return Err("Source game has a different initial state".to_string());
+        }
+
+        Ok(())
+    }
+
+    /// Maps the hand indices of this game to those of `source` (`usize::MAX` if not found).
+    pub(super) fn hand_map_from(&self, source: &PostFlopGame) -> [Vec<usize>; 2] {
+        let source_index = source_hand_index(source);
+        [0, 1].map(|player| {
+            self.private_cards[player]
+                .iter()
+                .map(|&(c1, c2)| source_index[player][card_pair_to_index(c1, c2)])
+                .collect::<Vec<_>>()
+        })
+    }
+
+    /// Writes the cumulative strategy of the node.
+    pub(super) fn write_cum_strategy(&self, node: &mut PostFlopNode, cum_strategy: &[f32]) {
+        if self.is_compression_enabled {
+            let scale = encode_unsigned_slice(node.strategy_compressed_mut(), cum_strategy);
+            node.set_strategy_scale(scale);
+        } else {
+            node.strategy_mut().copy_from_slice(cum_strategy);
+        }
+    }
+
+    /// Seeds the regrets of the nodes marked in `seeded` from the cumulative strategy, and clears
+    /// the regrets of the others.
+    pub(super) fn seed_regrets(&mut self, seeded: &[bool], weight: f32) -> WarmStartReport {
+        // seed the regrets using the counterfactual values of the seeded strategy
+        save_cfvalues(self);
+
+        let mut report = WarmStartReport::default();
+
+        for (node, &is_seeded) in self.node_arena.iter().zip(seeded) {
+            let mut node = node.lock();
+            if node.is_terminal() || node.is_chance() {
+                continue;
+            }
+
+            if is_seeded {
+                report.num_matched_nodes += 1;
+            } else {
+                report.num_unmatched_nodes += 1;
+            }
+
+            let num_actions = node.num_actions();
+            let num_hands = self.private_cards[node.player()].len();
+
+            let (cum_strategy, cfvalues) = if self.is_compression_enabled {
+                let decoder = node.cfvalue_scale() / i16::MAX as f32;
+                let cum_strategy = node.strategy_compressed().iter().map(|&x| x as f32);
+                let cfvalues = node.cfvalues_compressed().iter();
+                (
+                    cum_strategy.collect::<Vec<_>>(),
+                    cfvalues.map(|&x| x as f32 * decoder).collect::<Vec<_>>(),
+                )
+            } else {
+                (node.strategy().to_vec(), node.cfvalues().to_vec())
+            };
+
+            let strategy = normalized_strategy(&cum_strategy, num_actions);
+            let mut regrets = vec![0.0; num_actions * num_hands];
+
+            if is_seeded {
+                for hand in 0..num_hands {
+                    let column = (0..num_actions).map(|action| action * num_hands + hand);
+                    if column.clone().all(|i| cum_strategy[i] == 0.0) {
+                        continue;
+                    }
+
+                    let magnitude = column.clone().fold(0.0, |m, i| max(m, cfvalues[i].abs()));
+                    for i in column {
+                        regrets[i] = weight * magnitude * strategy[i];
+                    }
+                }
+            }
+
+            if self.is_compression_enabled {
+                let scale = encode_signed_slice(node.regrets_compressed_mut(), &regrets);
+                node.set_regret_scale(scale);
+            } else {
+                node.regrets_mut().copy_from_slice(&regrets);
+            }
+        }
+
+        // PCFR+ accumulates the raw regrets separately
+        if !self.storage_raw_regrets.is_empty() {
+            self.storage_raw_regrets.copy_from_slice(&self.storage2);
+            for node in &self.node_arena {
+                let mut node = node.lock();
+                if !node.is_terminal() && !node.is_chance() {
+                    node.scale4 = node.scale2;
+                }
+            }
+        }
+
+        report
+    }
+
+    /// The recursive helper function for seeding the cumulative strategy. `suits` maps the suits
+    /// of this game to those of `source`, which differ below the isomorphic chances of `source`.
+    #[allow(clippy::too_many_arguments)]
+    fn warm_start_recursive(
+        &self,
+        node_index: usize,
+        source: &PostFlopGame,
+        source_node_index: usize,
+        suits: [u8; 4],
+        source_hand_index: &[Vec<usize>; 2],
+        weight: f32,
+        seeded: &mut [bool],
+    ) {
+        let mut node = self.node_arena[node_index].lock();
+        let source_node = source.node_arena[source_node_index].lock();
+        if node.is_terminal()
+            || source_node.is_terminal()
+            || source_node.amount == source.tree_config.effective_stack
+        {
+            return;
+        }
+
+        let children_index = node_index + node.children_offset as usize;
+        let source_children_index = source_node_index + source_node.children_offset as usize;
+
+        // chance node
+        if node.is_chance() {
+            let is_turn = node.turn == NOT_DEALT;
+            if !source_node.is_chance()
+                || source.storage_mode == BoardState::Flop
+                || (!is_turn && source.storage_mode == BoardState::Turn)
+            {
+                return;
+            }
+
+            for (i, child) in node.children().iter().enumerate() {
+                if let Action::Chance(card) = child.lock().prev_action {
+                    let source_card = swap_card(card, &suits);
+                    if let Some((source_action, suits)) =
+                        find_source_chance(source, &source_node, source_card, suits)
+                    {
+                        self.warm_start_recursive(
+                            children_index + i,
+                            source,
+                            source_children_index + source_action,
+                            suits,
+                            source_hand_index,
+                            weight,
+                            seeded,
+                        );
+                    }
+                }
+            }
+
+            return;
+        }
+
+        // player node
+        let player = node.player();
+        if source_node.is_chance() || source_node.player() != player {
+            return;
+        }
+
+        let num_actions = node.num_actions();
+        let num_hands = self.private_cards[player].len();
+        let source_num_hands = source.private_cards[player].len();
+        let source_actions = source_node
+            .children()
+            .iter()
+            .map(|child| child.lock().prev_action)
+            .collect::<Vec<_>>();
+
+        let mut source_strategy = if source.is_compression_enabled {
+            normalized_strategy_compressed(source_node.strategy_compressed(), source_actions.len())
+        } else {
+            normalized_strategy(source_node.strategy(), source_actions.len())
+        };
+        apply_locking_strategy(&mut source_strategy, source.locking_strategy(&source_node));
+
+        let hand_map = self.private_cards[player]
+            .iter()
+            .map(|&(c1, c2)| {
+                let index = card_pair_to_index(swap_card(c1, &suits), swap_card(c2, &suits));
+                source_hand_index[player][index]
+            })
+            .collect::<Vec<_>>();
+
+        let action_map = (0..num_actions)
+            .map(|action| {
+                let prev_action = node.play(action).prev_action;
+                source_actions.iter().position(|&a| a == prev_action)
+            })
+            .collect::<Vec<_>>();
+
+        let mut cum_strategy = vec![0.0; num_actions * num_hands];
+        for (action, &source_action) in action_map.iter().enumerate() {
+            if let Some(source_action) = source_action {
+                let dst = row_mut(&mut cum_strategy, action, num_hands);
+                let src = row(&source_strategy, source_action, source_num_hands);
+                for (d, &source_hand) in dst.iter_mut().zip(&hand_map) {
+                    if source_hand != usize::MAX {
+                        *d = weight * src[source_hand];
+                    }
+                }
+            }
+        }
+
+        self.write_cum_strategy(&mut node, &cum_strategy);
+        seeded[node_index] = true;
+
+        for (action, &source_action) in action_map.iter().enumerate() {
+            if let Some(source_action) = source_action {
+                self.warm_start_recursive(
+                    children_index + action,
+                    source,
+                    source_children_index + source_action,
+                    suits,
+                    source_hand_index,
+                    weight,
+                    seeded,
+                );
+            }
+        }
+    }
+}
+
+/// Maps the card pairs (by `card_pair_to_index`) to the hand indices of `source` (`usize::MAX`
+/// if not found).
+fn source_hand_index(source: &PostFlopGame) -> [Vec<usize>; 2] {
+    [0, 1].map(|player| {
+        let mut source_index = vec![usize::MAX; 52 * 51 / 2];
+        for (i, &(c1, c2)) in source.private_cards[player].iter().enumerate() {
+            source_index[card_pair_to_index(c1, c2)] = i;
+        }
+        source_index
+    })
+}
+
+/// Replaces the suit of `card` by `suits`.
+#[inline]
+fn swap_card(card: u8, suits: &[u8; 4]) -> u8 {
+    card - (card & 3) + suits[card as usize & 3]
+}
+
+/// Finds the child of the chance node `node` of `source` that deals `card`, returning the index
+/// of the child and the updated suit mapping. If `card` is dealt through an isomorphic chance of
+/// `source`, the child of the representative card is returned and the two suits are swapped.
+fn find_source_chance(
+    source: &PostFlopGame,
+    node: &PostFlopNode,
+    card: u8,
+    mut suits: [u8; 4],
+) -> Option<(usize, [u8; 4])> {
+    let actions = node
+        .children()
+        .iter()
+        .map(|child| child.lock().prev_action)
+        .collect::<Vec<_>>();
+
+    if let Some(index) = actions.iter().position(|&a| a == Action::Chance(card)) {
+        return Some((index, suits));
+    }
+
+    let isomorphic_cards = if node.turn == NOT_DEALT {
+        &source.isomorphism_card_turn
+    } else {
+        &source.isomorphism_card_river[node.turn as usize & 3]
+    };
+
+    let isomorphism = source.isomorphic_chances(node);
+    let i = isomorphic_cards.iter().position(|&c| c == card)?;
+    let repr_index = *isomorphism.get(i)? as usize;
+
+    if let Action::Chance(repr_card) = actions[repr_index] {
+        let (suit1, suit2) = (card & 3, repr_card & 3);
+        for suit in &mut suits {
+            if *suit == suit1 {
+                *suit = suit2;
+            } else if *suit == suit2 {
+                *suit = suit1;
+            }
+        }
+    }
+
+    Some((repr_index, suits))
+}
@@ -1,4 +1,58 @@
 use crate::hand_table::*;
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "bincode")]
+use bincode::{Decode, Encode};
+
+/// A made-hand category of the hand evaluator.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[cfg_attr(feature = "bincode", derive(Decode, Encode))]
+pub enum HandCategory {
+    HighCard,
+    OnePair,
+    TwoPair,
+    ThreeOfAKind,
+    Straight,
+    Flush,
+    FullHouse,
+    FourOfAKind,
+    StraightFlush,
+}
+
+/// Returns the made-hand category of the given hole cards on the given board.
+///
+/// The board must contain three to five cards, which must not overlap with the hole cards.
+/// Note that the category is determined by the best five cards, so a board pair counts toward
+/// "one pair" even if neither hole card is paired.
+///
+/// # Examples
+/// ```
+/// use postflop_solver::*;
+///
+/// let board = flop_from_str("Td9d6h").unwrap();
+/// let hole = (card_from_str("Th").unwrap(), card_from_str("Tc").unwrap());
+/// assert_eq!(hand_category(&board, hole), HandCategory::ThreeOfAKind);
+/// ```
+pub fn hand_category(board: &[u8], hole: (u8, u8)) -> HandCategory {
+    let mut hand = Hand::new()
+        .add_card(hole.0 as usize)
+        .add_card(hole.1 as usize);
+    for &card in board {
+        hand = hand.add_card(card as usize);
+    }
+
+    match hand.evaluate_internal() >> 26 {
+        0 => HandCategory::HighCard,
+        1 => HandCategory::OnePair,
+        2 => HandCategory::TwoPair,
+        3 => HandCategory::ThreeOfAKind,
+        4 => HandCategory::Straight,
+        5 => HandCategory::Flush,
+        6 => HandCategory::FullHouse,
+        7 => HandCategory::FourOfAKind,
+        _ => HandCategory::StraightFlush,
+    }
+}
 
 #[derive(Clone, Copy, Default)]
 pub(crate) struct Hand {
@@ -60,7 +114,7 @@ impl Hand {
         let mut rankset_of_count = [0i32; 5];
         let mut rank_count = [0i32; 13];
 
-        for &card in &self.cards {
+        for &card in &self.cards[..self.num_cards] {
             let rank = card / 4;
             let suit = card % 4;
             rankset |= 1 << rank;
@@ -0,0 +1,398 @@
+use crate::action_tree::*;
+use crate::range::*;
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+static HAND_ID_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"Hand #([^:\s]+)").unwrap());
+
+static SEAT_REGEX: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^Seat \d+: (.+?) \(\D?([\d.,]+) in chips").unwrap());
+
+static DEALT_REGEX: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^Dealt to (.+?) \[(\S\S) (\S\S)\]").unwrap());
+
+static STREET_REGEX: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^\*\*\* (FLOP|TURN|RIVER) \*\*\*.*\[([^\]]+)\]\s*$").unwrap());
+
+static ACTION_REGEX: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(concat!(
+        r"^(.+?): (folds|checks|calls|bets|raises|posts)",
+        r"(?: small blind| big blind| small & big blinds| the ante)?",
+        r"(?: \D?([\d.,]+))?(?: to \D?([\d.,]+))?"
+    ))
+    .unwrap()
+});
+
+static UNCALLED_REGEX: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^Uncalled bet \(\D?([\d.,]+)\) returned to (.+)$").unwrap());
+
+/// A kind of [`HandAction`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum HandActionKind {
+    /// Fold.
+    Fold,
+
+    /// Check.
+    Check,
+
+    /// Call (the added amount).
+    Call(f64),
+
+    /// Bet (the bet amount).
+    Bet(f64),
+
+    /// Raise (the total amount of the player in the street after raising).
+    Raise(f64),
+}
+
+/// A postflop action of a [`HandHistory`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HandAction {
+    /// The street of the action.
+    pub street: BoardState,
+
+    /// The player of the action (0 = OOP, 1 = IP).
+    pub player: usize,
+
+    /// The kind of the action.
+    pub kind: HandActionKind,
+}
+
+/// The postflop portion of a heads-up hand history.
+///
+/// The amounts are in the unit of the hand history (e.g., dollars or chips).
+#[derive(Debug, Clone, PartialEq)]
+pub struct HandHistory {
+    /// ID of the hand.
+    pub hand_id: String,
+
+    /// Names of the players (OOP, IP).
+    pub players: [String; 2],
+
+    /// Index of the hero in `players` (`None` if the hero is not in the postflop).
+    pub hero: Option<usize>,
+
+    /// Hole cards of the hero, if known.
+    pub hero_cards: Option<(u8, u8)>,
+
+    /// Board cards (flop, turn, and river, as far as dealt).
+    pub board: Vec<u8>,
+
+    /// Pot at the beginning of each dealt street (flop, turn, and river).
+    pub pots: Vec<f64>,
+
+    /// Remaining stacks of the players (OOP, IP) at the beginning of the flop.
+    pub stacks: [f64; 2],
+
+    /// Postflop actions.
+    pub actions: Vec<HandAction>,
+}
+
+impl HandHistory {
+    /// Parses a PokerStars- or GGPoker-style hand history of a single hand.
+    ///
+    /// Only hands that reach the flop heads-up with at least one postflop action are supported.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut lines = text.lines().map(str::trim).filter(|line| !line.is_empty());
+
+        let first_line = lines.next().ok_or("Empty hand history")?;
+        let hand_id = HAND_ID_REGEX
+            .captures(first_line)
+            .ok_or_else(|| format!("Invalid header: {first_line}"))?[1]
+            .to_string();
+
+        let mut names = Vec::new();
+        let mut stacks = Vec::new();
+        let mut invested = Vec::new();
+        let mut street_bet = Vec::new();
+        let mut folded = Vec::new();
+        let mut hero = None;
+        let mut street = None;
+        let mut board = Vec::new();
+        let mut pots = Vec::new();
+        let mut flop_stacks = Vec::new();
+        let mut raw_actions = Vec::new();
+
+        let find_player = |names: &[String], name: &str| {
+            names
+                .iter()
+                .position(|n| n == name)
+                .ok_or_else(|| format!("Unknown player: {name}"))
+        };
+
+        for line in lines {
+            if line.starts_with("*** SHOW") || line.starts_with("*** SUMMARY") {
+                break;
+            }
+
+            if let Some(caps) = SEAT_REGEX.captures(line) {
+                if street.is_none() && !names.contains(&caps[1].to_string()) {
+                    names.push(caps[1].to_string());
+                    stacks.push(parse_amount(&caps[2])?);
+                    invested.push(0.0);
+                    street_bet.push(0.0);
+                    folded.push(false);
+                }
+            } else if let Some(caps) = DEALT_REGEX.captures(line) {
+                let c1 = card_from_str(&caps[2])?;
+                let c2 = card_from_str(&caps[3])?;
+                hero = Some((caps[1].to_string(), (c1.min(c2), c1.max(c2))));
+            } else if let Some(caps) = STREET_REGEX.captures(line) {
+                let next_street = match &caps[1] {
+                    "FLOP" => BoardState::Flop,
+                    "TURN" => BoardState::Turn,
+                    _ => BoardState::River,
+                };
+
+                if street.is_none() && next_street != BoardState::Flop {
+                    return Err("Missing flop".to_string());
+                }
+
+                if next_street == BoardState::Flop {
+                    board = flop_from_str(&caps[2].replace(' ', ""))?.to_vec();
+                    flop_stacks = (0..names.len()).map(|i| stacks[i] - invested[i]).collect();
+                } else {
+                    board.push(card_from_str(caps[2].trim())?);
+                }
+
+                street = Some(next_street);
+                pots.push(invested.iter().sum());
+                street_bet.iter_mut().for_each(|bet| *bet = 0.0);
+            } else if let Some(caps) = UNCALLED_REGEX.captures(line) {
+                let player = find_player(&names, caps[2].trim())?;
+                let amount = parse_amount(&caps[1])?;
+                invested[player] -= amount;
+                street_bet[player] -= amount;
+            } else if let Some(caps) = ACTION_REGEX.captures(line) {
+                let player = find_player(&names, &caps[1])?;
+                let amount = caps.get(3).map(|m| parse_amount(m.as_str())).transpose()?;
+                let to = caps.get(4).map(|m| parse_amount(m.as_str())).transpose()?;
+                let missing = || format!("Missing amount: {line}");
+
+                let kind = match &caps[2] {
+                    "folds" => {
+                        folded[player] = true;
+                        HandActionKind::Fold
+                    }
+                    "checks" => HandActionKind::Check,
+                    "calls" => {
+                        let amount = amount.ok_or_else(missing)?;
+                        invested[player] += amount;
+                        street_bet[player] += amount;
+                        HandActionKind::Call(amount)
+                    }
+                    "bets" => {
+                        let amount = amount.ok_or_else(missing)?;
+                        invested[player] += amount;
+                        street_bet[player] += amount;
+                        HandActionKind::Bet(amount)
+                    }
+                    "raises" => {
+                        let to = to.ok_or_else(missing)?;
+                        invested[player] += to - street_bet[player];
+                        street_bet[player] = to;
+                        HandActionKind::Raise(to)
+                    }
+                    _ => {
+                        // posts: antes are dead money and the blinds are bets of the street
+                        let amount = amount.ok_or_else(missing)?;
+                        invested[player] += amount;
+                        if !line.contains("the ante") {
+                            street_bet[player] += amount;
+                        }
+                        continue;
+                    }
+                };
+
+                if let Some(street) = street {
+                    raw_actions.push((street, player, kind));
+                }
+            }
+        }
+
+        if street.is_none() {
+            return Err("Hand does not reach the flop".to_string());
+        }
+
+        let remaining = (0..names.len())
+            .filter(|&i| {
+                let folded_preflop = folded[i] && !raw_actions.iter().any(|a| a.1 == i);
+                !folded_preflop && invested[i] > 0.0
+            })
+            .collect::<Vec<_>>();
+
+        if remaining.len() != 2 {
+            return Err("Only heads-up postflop hands are supported".to_string());
+        }
+
+        // OOP acts first on the flop
+        let oop = raw_actions.first().ok_or("No postflop action")?.1;
+        let ip = if remaining[0] == oop {
+            remaining[1]
+        } else {
+            remaining[0]
+        };
+
+        let mut actions = Vec::with_capacity(raw_actions.len());
+        for (street, player, kind) in raw_actions {
+            let player = match player {
+                p if p == oop => 0,
+                p if p == ip => 1,
+                _ => return Err(format!("Unexpected postflop player: {}", names[player])),
+            };
+            actions.push(HandAction {
+                street,
+                player,
+                kind,
+            });
+        }
+
+        let (hero, hero_cards) = match hero {
+            Some((name, cards)) if name == names[oop] => (Some(0), Some(cards)),
+            Some((name, cards)) if name == names[ip] => (Some(1), Some(cards)),
+            _ => (None, None),
+        };
+
+        Ok(Self {
+            hand_id,
+            players: [names[oop].clone(), names[ip].clone()],
+            hero,
+            hero_cards,
+            board,
+            pots,
+            stacks: [flop_stacks[oop], flop_stacks[ip]],
+            actions,
+        })
+    }
+}
+
+/// Parses a text containing multiple hand histories, each starting with a line containing
+/// `"Hand #"`. Returns the result of [`HandHistory::parse`] for each hand.
+pub fn parse_hand_histories(text: &str) -> Vec<Result<HandHistory, String>> {
+    let mut hands = Vec::new();
+    let mut current = String::new();
+
+    for line in text.lines() {
+        if HAND_ID_REGEX.is_match(line) && !current.trim().is_empty() {
+            hands.push(HandHistory::parse(&current));
+            current.clear();
+        }
+        current.push_str(line);
+        current.push('\n');
+    }
+
+    if !current.trim().is_empty() {
+        hands.push(HandHistory::parse(&current));
+    }
+
+    hands
+}
+
+/// Parses an amount such as `"1,234.5"`.
+fn parse_amount(s: &str) -> Result<f64, String> {
+    s.replace(',', "")
+        .parse()
+        .map_err(|_| format!("Invalid amount: {s}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TOURNAMENT_HAND: &str = "\
+Poker Hand #TM2000000001: Tournament #100, $10+$1 Hold'em No Limit - Level5(100/200(25))
+Table '7' 6-max Seat #3 is the button
+Seat 1: Villain (12,000 in chips)
+Seat 2: Other (8,000 in chips)
+Seat 3: Hero (15,000 in chips)
+Villain: posts the ante 25
+Other: posts the ante 25
+Hero: posts the ante 25
+Villain: posts small blind 100
+Other: posts big blind 200
+*** HOLE CARDS ***
+Dealt to Villain
+Dealt to Other
+Dealt to Hero [Kc Ks]
+Hero: raises 300 to 500
+Villain: calls 400
+Other: folds
+*** FLOP *** [Ah 7c 2d]
+Villain: checks
+Hero: bets 600
+Villain: raises 1,200 to 1,800
+Hero: calls 1,200
+*** TURN *** [Ah 7c 2d] [3s]
+Villain: bets 9,675
+Hero: folds
+Uncalled bet (9,675) returned to Villain
+*** SHOWDOWN ***
+Villain collected 4,875 from pot
+*** SUMMARY ***
+Total pot 4,875 | Rake 0 | Jackpot 0
+";
+
+    #[test]
+    fn parse_tournament_hand() {
+        let hand = HandHistory::parse(TOURNAMENT_HAND).unwrap();
+        assert_eq!(hand.hand_id, "TM2000000001");
+        assert_eq!(hand.players, ["Villain".to_string(), "Hero".to_string()]);
+        assert_eq!(hand.hero, Some(1));
+        let kings = (card_from_str("Kc").unwrap(), card_from_str("Ks").unwrap());
+        assert_eq!(hand.hero_cards, Some(kings));
+        assert_eq!(hand.board.len(), 4);
+
+        // the antes and the folded big blind are dead money in the pot
+        assert_eq!(hand.pots, vec![1275.0, 4875.0]);
+        assert_eq!(hand.stacks, [11475.0, 14475.0]);
+
+        let kinds = hand.actions.iter().map(|a| a.kind).collect::<Vec<_>>();
+        assert_eq!(
+            kinds,
+            vec![
+                HandActionKind::Check,
+                HandActionKind::Bet(600.0),
+                HandActionKind::Raise(1800.0),
+                HandActionKind::Call(1200.0),
+                HandActionKind::Bet(9675.0),
+                HandActionKind::Fold,
+            ]
+        );
+        assert_eq!(hand.actions[4].street, BoardState::Turn);
+        assert_eq!(hand.actions[4].player, 0);
+    }
+
+    #[test]
+    fn parse_invalid_hands() {
+        assert!(HandHistory::parse("").is_err());
+        assert!(HandHistory::parse("Seat 1: Hero ($10 in chips)").is_err());
+
+        // the hand ends preflop
+        let preflop = TOURNAMENT_HAND.split("*** FLOP").next().unwrap();
+        assert!(HandHistory::parse(preflop).is_err());
+
+        // three players see the flop
+        let multiway = TOURNAMENT_HAND.replace("Other: folds", "Other: calls 300");
+        assert!(HandHistory::parse(&multiway).is_err());
+
+        // a player not seated at the table
+        let unknown = TOURNAMENT_HAND.replace("Hero: bets 600", "Nobody: bets 600");
+        assert!(HandHistory::parse(&unknown).is_err());
+
+        // the turn is dealt without the flop
+        let no_flop = TOURNAMENT_HAND.replace("*** FLOP *** [Ah 7c 2d]\n", "");
+        assert_eq!(
+            HandHistory::parse(&no_flop),
+            Err("Missing flop".to_string())
+        );
+
+        // the flop has fewer than three cards
+        let short_flop = TOURNAMENT_HAND.replace("*** FLOP *** [Ah 7c 2d]", "*** FLOP *** [Ah 7c]");
+        assert!(HandHistory::parse(&short_flop).is_err());
+
+        let hands = parse_hand_histories(&format!("{TOURNAMENT_HAND}\n{preflop}"));
+        assert_eq!(hands.len(), 2);
+        assert!(hands[0].is_ok());
+        assert!(hands[1].is_err());
+    }
+}
@@ -72,11 +72,49 @@ pub trait Game: Send + Sync {
         &[]
     }
 
+    /// Adjusts the strategy to meet the frequency lock of the given node. `reach` is the reach
+    /// probabilities of the acting player, which are given only when the player is not updated.
+    #[doc(hidden)]
+    fn apply_frequency_lock(
+        &self,
+        _node: &Self::Node,
+        _strategy: &mut [f32],
+        _reach: Option<&[f32]>,
+    ) {
+    }
+
+    /// Returns whether the given node has a frequency lock.
+    #[doc(hidden)]
+    fn is_frequency_locked(&self, _node: &Self::Node) -> bool {
+        false
+    }
+
+    /// Computes the sum of the counterfactual reach probabilities of the opponent hands that do
+    /// not conflict with each hand of `player`, i.e., the counterfactual values of a unit payoff
+    /// (required by the entropy regularization). Returns `false` if the game does not support it.
+    #[doc(hidden)]
+    fn reach_sums(
+        &self,
+        _result: &mut [f32],
+        _node: &Self::Node,
+        _player: usize,
+        _cfreach: &[f32],
+    ) -> bool {
+        false
+    }
+
     /// Returns whether the compression is enabled.
     #[doc(hidden)]
     fn is_compression_enabled(&self) -> bool {
         false
     }
+
+    /// Allocates the storage for the raw cumulative regrets (required by PCFR+) if not allocated
+    /// yet. Returns `false` if the game does not support it.
+    #[doc(hidden)]
+    fn allocate_raw_regrets(&mut self) -> bool {
+        false
+    }
 }
 
 /// The trait representing a node in game tree.
@@ -117,6 +155,18 @@ pub trait GameNode: Send + Sync {
     #[doc(hidden)]
     fn regrets_mut(&mut self) -> &mut [f32];
 
+    /// Returns the raw cumulative regrets, i.e., without the prediction added (for PCFR+).
+    #[doc(hidden)]
+    fn raw_regrets(&self) -> &[f32] {
+        unreachable!()
+    }
+
+    /// Returns the mutable reference to the raw cumulative regrets.
+    #[doc(hidden)]
+    fn raw_regrets_mut(&mut self) -> &mut [f32] {
+        unreachable!()
+    }
+
     /// Returns the counterfactual values.
     #[doc(hidden)]
     fn cfvalues(&self) -> &[f32];
@@ -191,6 +241,18 @@ pub trait GameNode: Send + Sync {
         unreachable!()
     }
 
+    /// Returns the compressed raw cumulative regrets.
+    #[doc(hidden)]
+    fn raw_regrets_compressed(&self) -> &[i16] {
+        unreachable!()
+    }
+
+    /// Returns the mutable reference to the compressed raw cumulative regrets.
+    #[doc(hidden)]
+    fn raw_regrets_compressed_mut(&mut self) -> &mut [i16] {
+        unreachable!()
+    }
+
     /// Returns the compressed counterfactual values.
     #[doc(hidden)]
     fn cfvalues_compressed(&self) -> &[i16] {
@@ -251,6 +313,18 @@ pub trait GameNode: Send + Sync {
         unreachable!()
     }
 
+    /// Returns the scale of the compressed raw cumulative regrets.
+    #[doc(hidden)]
+    fn raw_regret_scale(&self) -> f32 {
+        unreachable!()
+    }
+
+    /// Sets the scale of the compressed raw cumulative regrets.
+    #[doc(hidden)]
+    fn set_raw_regret_scale(&mut self, _scale: f32) {
+        unreachable!()
+    }
+
     /// Returns the scale of the compressed counterfactual values.
     #[doc(hidden)]
     fn cfvalue_scale(&self) -> f32 {
@@ -22,6 +22,7 @@
 //!
 //! let tree_config = TreeConfig {
 //!     initial_state: BoardState::Turn,
+//!     depth_limit: None,
 //!     starting_pot: 200,
 //!     effective_stack: 900,
 //!     rake_rate: 0.0,
@@ -167,6 +168,8 @@
 //! - `bincode`: Uses [bincode] crate (2.0.0-rc.3) to serialize and deserialize the `PostFlopGame` struct.
 //!   This feature is required to save and load the game tree.
 //!   Enabled by default.
+//! - `cli`: Builds the `postflop-cli` binary (requires `bincode`) with [toml] crate to read job files.
+//!   Disabled by default.
 //! - `custom-alloc`: Uses custom memory allocator in solving process (only available in nightly Rust).
 //!   It significantly reduces the number of calls of the default allocator,
 //!   so it is recommended to use this feature when the default allocator is not so efficient.
@@ -181,6 +184,7 @@
 //!
 //! [bincode]: https://github.com/bincode-org/bincode
 //! [rayon]: https://github.com/rayon-rs/rayon
+//! [toml]: https://github.com/toml-rs/toml
 //! [zstd]: https://github.com/gyscos/zstd-rs
 
 #![cfg_attr(feature = "custom-alloc", feature(allocator_api))]
@@ -188,34 +192,51 @@
 #[cfg(feature = "custom-alloc")]
 mod alloc;
 
+#[cfg(feature = "bincode")]
+mod batch;
+
 #[cfg(feature = "bincode")]
 mod file;
 
 mod action_tree;
 mod atomic_float;
+mod bet_selection;
 mod bet_size;
 mod bunching;
 mod card;
 mod game;
 mod hand;
+mod hand_history;
 mod hand_table;
 mod interface;
 mod mutex_like;
+mod payoff;
 mod range;
+mod report;
 mod sliceop;
 mod solver;
+mod trainer;
 mod utility;
 
+#[cfg(feature = "bincode")]
+pub use batch::*;
+
 #[cfg(feature = "bincode")]
 pub use file::*;
 
 pub use action_tree::*;
+pub use bet_selection::*;
 pub use bet_size::*;
 pub use bunching::*;
 pub use card::*;
 pub use game::*;
+pub use hand::*;
+pub use hand_history::*;
 pub use interface::*;
 pub use mutex_like::*;
+pub use payoff::*;
 pub use range::*;
+pub use report::*;
 pub use solver::*;
+pub use trainer::*;
 pub use utility::*;
@@ -0,0 +1,205 @@
+use once_cell::sync::Lazy;
+use serde::Deserialize;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+use std::sync::Arc;
+
+#[cfg(feature = "bincode")]
+use bincode::{Decode, Encode};
+
+/// Path of the ICM state file that is used when no payoff model is specified.
+const DEFAULT_ICM_STATE_FILE: &str = "C:\\Users\\Nima\\OneDrive\\Desktop\\ICM\\state.json";
+
+/// The ICM utility loaded from [`DEFAULT_ICM_STATE_FILE`], or the error message of loading it.
+pub(crate) static DEFAULT_ICM_UTILITY: Lazy<Result<Arc<IcmUtility>, String>> = Lazy::new(|| {
+    IcmUtility::from_file(DEFAULT_ICM_STATE_FILE)
+        .map(Arc::new)
+        .map_err(|e| format!("Failed to load the default ICM state file: {e}"))
+});
+
+/// An enum representing how the terminal payoffs are computed.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "bincode", derive(Decode, Encode))]
+pub enum PayoffModel {
+    /// Chip EV: the winner of a showdown (or the player who did not fold) takes the pot.
+    ChipEv,
+
+    /// ICM: the payoff is the difference of the tournament equity looked up in the given table.
+    Icm(Arc<IcmUtility>),
+}
+
+impl PayoffModel {
+    /// Creates an ICM payoff model by loading the state file at the given path.
+    #[inline]
+    pub fn icm_from_file<P: AsRef<Path>>(path: P) -> Result<Self, String> {
+        Ok(Self::Icm(Arc::new(IcmUtility::from_file(path)?)))
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[cfg_attr(feature = "bincode", derive(Decode, Encode))]
+struct IcmPlayer {
+    #[allow(dead_code)]
+    index: usize,
+    #[serde(rename = "startingStack")]
+    starting_stack: f64,
+    #[allow(dead_code)]
+    #[serde(rename = "remainingStack")]
+    remaining_stack: f64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[cfg_attr(feature = "bincode", derive(Decode, Encode))]
+struct IcmEntry {
+    s: Vec<f64>,
+    u: Vec<f64>,
+}
+
+/// A table of tournament equities (ICM utilities) indexed by the stack of each player.
+///
+/// Stacks are normalized so that one big blind equals `100.0`, which is the unit of the game tree.
+#[derive(Debug, Clone, Deserialize)]
+#[cfg_attr(feature = "bincode", derive(Decode, Encode))]
+pub struct IcmUtility {
+    #[allow(dead_code)]
+    #[serde(rename = "formatType")]
+    format_type: String,
+    #[allow(dead_code)]
+    #[serde(rename = "formatVersion")]
+    format_version: String,
+    #[allow(dead_code)]
+    pot: f64,
+    #[serde(rename = "bigblind")]
+    big_blind: f64,
+    utilities: Vec<IcmEntry>,
+    players: Vec<IcmPlayer>,
+}
+
+impl IcmUtility {
+    /// Loads an ICM state file (JSON format).
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, String> {
+        let path = path.as_ref();
+        let file = File::open(path)
+            .map_err(|e| format!("Failed to open ICM state file {}: {}", path.display(), e))?;
+        let reader = BufReader::new(file);
+        let utility: Self = serde_json::from_reader(reader)
+            .map_err(|e| format!("Failed to parse ICM state file {}: {}", path.display(), e))?;
+        utility.normalized()
+    }
+
+    /// Parses an ICM state file from a JSON string.
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        let utility: Self =
+            serde_json::from_str(json).map_err(|e| format!("Failed to parse ICM state: {}", e))?;
+        utility.normalized()
+    }
+
+    fn normalized(mut self) -> Result<Self, String> {
+        if self.big_blind <= 0.0 {
+            return Err("Big blind must be positive".to_string());
+        }
+
+        if self.players.len() < 2 {
+            return Err("ICM state must contain at least two players".to_string());
+        }
+
+        let big_blind = self.big_blind;
+        for utility in &mut self.utilities {
+            for stack in &mut utility.s {
+                *stack = (*stack / big_blind) * 100.0;
+            }
+        }
+
+        for player in &mut self.players {
+            player.starting_stack = (player.starting_stack / big_blind) * 100.0;
+        }
+
+        Ok(self)
+    }
+
+    /// Returns the starting stack of the given player (normalized).
+    #[inline]
+    pub fn starting_stack(&self, player: usize) -> f64 {
+        self.players[player].starting_stack
+    }
+
+    /// Returns the tournament equity of the given player at the starting stack.
+    #[inline]
+    pub fn starting_value(&self, player: usize) -> f64 {
+        self.lookup(self.starting_stack(player), player)
+    }
+
+    /// Returns the tournament equity of the given player with the given (normalized) stack.
+    ///
+    /// The value is linearly interpolated between the nearest entries of the table.
+    pub fn lookup(&self, value: f64, player: usize) -> f64 {
+        let closest = self
+            .utilities
+            .iter()
+            .filter(|u| u.s.get(player).is_some())
+            .min_by(|u1, u2| {
+                let diff1 = (u1.s[player] - value).abs();
+                let diff2 = (u2.s[player] - value).abs();
+                diff1
+                    .partial_cmp(&diff2)
+                    .unwrap_or(std::cmp::Ordering::Equal)
+            });
+
+        match closest {
+            Some(closest) => {
+                let closest_stack = closest.s[player];
+                let other = if closest_stack <= value {
+                    // interpolate between the closest entry and the next one
+                    self.utilities.iter().find(|u| u.s[player] > closest_stack)
+                } else {
+                    // interpolate between the previous entry and the closest one
+                    self.utilities
+                        .iter()
+                        .rev()
+                        .find(|u| u.s[player] < closest_stack)
+                };
+                match other {
+                    Some(other) => {
+                        let slope = (other.u[player] - closest.u[player])
+                            / (other.s[player] - closest.s[player]);
+                        closest.u[player] + slope * (value - closest_stack)
+                    }
+                    None => closest.u[player],
+                }
+            }
+            None => 0.0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STATE: &str = r#"{
+        "formatType": "icm",
+        "formatVersion": "1",
+        "pot": 0,
+        "bigblind": 10,
+        "utilities": [
+            { "s": [0, 0], "u": [0.0, 0.0] },
+            { "s": [100, 100], "u": [0.5, 0.5] },
+            { "s": [200, 200], "u": [0.8, 0.8] }
+        ],
+        "players": [
+            { "index": 0, "startingStack": 100, "remainingStack": 100 },
+            { "index": 1, "startingStack": 100, "remainingStack": 100 }
+        ]
+    }"#;
+
+    #[test]
+    fn icm_lookup() {
+        let utility = IcmUtility::from_json(STATE).unwrap();
+        assert_eq!(utility.starting_stack(0), 1000.0);
+        assert!((utility.starting_value(0) - 0.5).abs() < 1e-9);
+        assert!((utility.lookup(500.0, 1) - 0.25).abs() < 1e-9);
+        assert!((utility.lookup(1500.0, 1) - 0.65).abs() < 1e-9);
+        assert!((utility.lookup(3000.0, 1) - 0.8).abs() < 1e-9);
+    }
+}
@@ -1,6 +1,7 @@
 use crate::card::*;
 use once_cell::sync::Lazy;
 use regex::Regex;
+use serde::{Deserialize, Serialize};
 use std::fmt::Write;
 use std::str::FromStr;
 
@@ -27,7 +28,10 @@ use bincode::{Decode, Encode};
 /// // check that the hand "AKo" is not in the range
 /// assert_eq!(range.get_weight_offsuit(ace_rank, king_rank), 0.0);
 /// ```
-#[derive(Debug, Clone, Copy, PartialEq)]
+///
+/// With serde, a range is represented by its string form (e.g., `"QQ+,AKs:0.5"`).
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
 #[cfg_attr(feature = "bincode", derive(Decode, Encode))]
 pub struct Range {
     data: [f32; 52 * 51 / 2],
@@ -994,6 +998,22 @@ impl ToString for Range {
     }
 }
 
+impl TryFrom<String> for Range {
+    type Error = String;
+
+    #[inline]
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl From<Range> for String {
+    #[inline]
+    fn from(range: Range) -> Self {
+        range.to_string()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1156,4 +1176,13 @@ mod tests {
             assert_eq!(range.unwrap().to_string(), expected);
         }
     }
+
+    #[test]
+    fn range_serde() {
+        let range = "AA:0.5,KK-QQ,AKs:0.5".parse::<Range>().unwrap();
+        let json = serde_json::to_string(&range).unwrap();
+        assert_eq!(json, r#""AA:0.5,KK-QQ,AKs:0.5""#);
+        assert_eq!(serde_json::from_str::<Range>(&json).unwrap(), range);
+        assert!(serde_json::from_str::<Range>(r#""AAA""#).is_err());
+    }
 }
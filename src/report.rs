@@ -0,0 +1,367 @@
+use crate::action_tree::*;
+use crate::card::*;
+use crate::game::*;
+use crate::interface::*;
+use crate::range::*;
+use crate::utility::*;
+use std::fmt::Write;
+
+/// Root statistics of a solved flop, used as a row of [`FlopReport`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct FlopReportRow {
+    /// Flop cards.
+    pub flop: [u8; 3],
+
+    /// Weight of the flop in the aggregation (e.g., the number of isomorphic flops).
+    pub weight: f64,
+
+    /// Average equity of each player at the root.
+    pub equity: [f32; 2],
+
+    /// Average expected value of each player at the root.
+    pub expected_values: [f32; 2],
+
+    /// Available actions of OOP at the root and of IP facing a check.
+    pub actions: [Vec<Action>; 2],
+
+    /// Average frequencies of the actions in `actions`.
+    pub frequencies: [Vec<f32>; 2],
+}
+
+/// A table that aggregates the root strategies and values of solved flops.
+///
+/// # Examples
+/// ```ignore
+/// use postflop_solver::*;
+///
+/// let mut report = FlopReport::new();
+/// for (flop, weight) in canonical_flops() {
+///     let mut game = solve_flop(flop); // user-defined
+///     report.add_game(&mut game, weight as f64).unwrap();
+/// }
+/// println!("{}", report.to_csv().unwrap());
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct FlopReport {
+    rows: Vec<FlopReportRow>,
+}
+
+/// A subset of 95 flops for aggregated reports (see [`weighted_flop_subset`]).
+///
+/// The flops are taken at evenly spaced positions from the 1,755 strategically distinct flops
+/// sorted by texture (monotone, two-tone, and rainbow; trips, paired, and unpaired; then ranks
+/// from high to low), so that they cover the textures uniformly. Weighted by
+/// [`weighted_flop_subset`], they approximate the distribution of all flops.
+pub const FLOP_SUBSET_95: [&str; 95] = [
+    "AsKs3s", "AsJs4s", "As8s6s", "As4s2s", "KsJs3s", "Ks8s6s", "Ks3s2s", "Qs9s8s", "Qs6s4s",
+    "Js9s7s", "Js6s4s", "Ts8s4s", "9s8s7s", "9s4s2s", "7s6s4s", "AsAhKs", "As7s7h", "KsTsTh",
+    "QsQh2s", "JsJh2s", "Ts9s9h", "9s4s4h", "7s7h3s", "5s3s3h", "AsKh9s", "AsKs3h", "AsQs7h",
+    "AsJh9h", "AsJh3h", "AsTh5h", "As9h6s", "As8h6s", "As7s5h", "As6s3h", "As3h2h", "KsQh6h",
+    "KsJh9s", "KsJh3s", "KsTs5h", "Ks9s6h", "Ks8h5h", "Ks7h4h", "Ks6h2s", "Ks3h2s", "QsJs5h",
+    "QsTs7h", "Qs9h7h", "Qs8h7h", "Qs7h6h", "Qs6h4s", "Qs4h3s", "JsTs6h", "Js9s7h", "Js8h6h",
+    "Js7h5h", "Js6h3s", "Js4h2s", "Ts9s4h", "Ts8s4h", "Ts7h2h", "Ts5h3h", "9s8h6s", "9s7h5s",
+    "9s6s3h", "9s4s2h", "8s6h5h", "8s5h2h", "7s6h3h", "7s4h2s", "6s4h2s", "QsQhQd", "AsAh6d",
+    "KsKhTd", "Ks3h3d", "Qs4h4d", "Js4h4d", "9s9h8d", "8s8h3d", "6s6h4d", "3s2h2d", "AsQh4d",
+    "AsTh3d", "As7h2d", "KsQh4d", "KsTh2d", "Ks7h2d", "QsJh3d", "Qs8h5d", "Qs3h2d", "Js8h4d",
+    "Ts9h8d", "Ts6h4d", "9s7h3d", "8s6h3d", "6s5h4d",
+];
+
+/// A subset of 184 flops for aggregated reports (see [`weighted_flop_subset`]).
+///
+/// The flops are taken at evenly spaced positions from the 1,755 strategically distinct flops
+/// sorted by texture (monotone, two-tone, and rainbow; trips, paired, and unpaired; then ranks
+/// from high to low), so that they cover the textures uniformly. Weighted by
+/// [`weighted_flop_subset`], they approximate the distribution of all flops.
+pub const FLOP_SUBSET_184: [&str; 184] = [
+    "AsKs8s", "AsQs8s", "AsJs8s", "AsTs6s", "As9s4s", "As7s5s", "As5s3s", "KsQs6s", "KsJs5s",
+    "KsTs4s", "Ks8s7s", "Ks7s3s", "Ks4s2s", "QsJs3s", "Qs9s8s", "Qs8s5s", "Qs6s4s", "JsTs9s",
+    "Js9s6s", "Js8s3s", "Js6s2s", "Ts9s5s", "Ts8s2s", "Ts5s4s", "9s8s4s", "9s6s3s", "8s7s5s",
+    "8s5s2s", "7s5s3s", "6s3s2s", "AsAh9s", "AsJsJh", "As2s2h", "KsKh3s", "Ks4s4h", "QsQh5s",
+    "Qs5s5h", "JsJh5s", "Js4s4h", "TsTh3s", "9s9h8s", "9s6s6h", "8s8h2s", "7s7h4s", "6s6h3s",
+    "5s4s4h", "AsKhQs", "AsKs9h", "AsKs6h", "AsKh2h", "AsQh9h", "AsQh6s", "AsQh3s", "AsJs9h",
+    "AsJs6h", "AsJh2h", "AsTh7h", "AsTh4s", "As9h8s", "As9s5h", "As8h7h", "As8h4h", "As7h6s",
+    "As7h3s", "As6s4h", "As5s4h", "As4h2h", "KsQhTh", "KsQh7s", "KsQh4s", "KsJsTh", "KsJs7h",
+    "KsJh3h", "KsTh8s", "KsTh5s", "KsTs2h", "Ks9s6h", "Ks9h2h", "Ks8h5h", "Ks8h2s", "Ks7h4s",
+    "Ks6s5h", "Ks6s2h", "Ks4h3h", "QsJhTh", "QsJh7s", "QsJs4h", "QsTs9h", "QsTh5h", "QsTh2h",
+    "Qs9h6s", "Qs9h3s", "Qs8s6h", "Qs8s3h", "Qs7h4h", "Qs6h5h", "Qs6h2s", "Qs5h2s", "Qs3s2h",
+    "JsTh6h", "JsTh3h", "Js9h7s", "Js9h4s", "Js8s7h", "Js8s4h", "Js7h5h", "Js7h2h", "Js6h3s",
+    "Js5h3s", "Js4s2h", "Ts9s7h", "Ts9h3h", "Ts8h6s", "Ts8h3s", "Ts7s5h", "Ts7s2h", "Ts6h2h",
+    "Ts5h2h", "Ts3h2s", "9s8h5s", "9s8s2h", "9s7s4h", "9s6h4h", "9s5h4h", "9s4h3s", "8s7s6h",
+    "8s7s3h", "8s6h3h", "8s5h3h", "8s4h2s", "7s6h4s", "7s5s4h", "7s4s3h", "6s5h3h", "6s4h2h",
+    "5s4h2s", "KsKhKd", "3s3h3d", "AsAh5d", "As8h8d", "KsKh9d", "KsJhJd", "QsQhJd", "QsQh2d",
+    "Qs2h2d", "JsJh2d", "TsTh9d", "Ts8h8d", "9s9h5d", "9s3h3d", "8s5h5d", "7s5h5d", "6s4h4d",
+    "4s4h2d", "AsKh8d", "AsQh8d", "AsJh8d", "AsTh6d", "As9h4d", "As7h5d", "As5h3d", "KsQh6d",
+    "KsJh6d", "KsTh4d", "Ks8h7d", "Ks7h3d", "Ks4h2d", "QsJh3d", "Qs9h8d", "Qs8h5d", "Qs6h4d",
+    "JsTh9d", "Js9h6d", "Js8h3d", "Js6h2d", "Ts9h6d", "Ts8h2d", "Ts5h4d", "9s8h4d", "9s6h3d",
+    "8s7h5d", "8s5h2d", "7s5h3d", "6s3h2d",
+];
+
+/// Parses a flop subset (e.g., [`FLOP_SUBSET_95`] or [`FLOP_SUBSET_184`]) and weights each flop
+/// by the number of flops isomorphic to it.
+///
+/// Returns an error if a flop is invalid or if two flops are isomorphic to each other.
+pub fn weighted_flop_subset(flops: &[&str]) -> Result<Vec<([u8; 3], u32)>, String> {
+    let mut canonical = Vec::with_capacity(flops.len());
+    let mut result = Vec::with_capacity(flops.len());
+
+    for flop_str in flops {
+        let flop = flop_from_str(flop_str)?;
+        let key = canonical_flop(&flop);
+        if canonical.contains(&key) {
+            return Err(format!("Duplicate flop (up to isomorphism): {flop_str}"));
+        }
+        canonical.push(key);
+        result.push((flop, flop_isomorphism_count(&flop)));
+    }
+
+    Ok(result)
+}
+
+impl FlopReportRow {
+    /// Computes the row of the given solved game. The current node is reset to the root.
+    pub fn from_game(game: &mut PostFlopGame, weight: f64) -> Result<Self, String> {
+        if !game.is_solved() {
+            return Err("Game is not solved".to_string());
+        }
+
+        if game.tree_config().initial_state != BoardState::Flop {
+            return Err("Game does not start from the flop".to_string());
+        }
+
+        game.back_to_root();
+        game.cache_normalized_weights();
+
+        let mut equity = [0.0; 2];
+        let mut expected_values = [0.0; 2];
+        for player in 0..2 {
+            let weights = game.normalized_weights(player);
+            equity[player] = compute_average(&game.equity(player), weights);
+            expected_values[player] = compute_average(&game.expected_values(player), weights);
+        }
+
+        let mut actions: [Vec<Action>; 2] = Default::default();
+        let mut frequencies: [Vec<f32>; 2] = Default::default();
+
+        actions[0] = game.available_actions();
+        frequencies[0] = root_frequencies(game);
+
+        // IP facing a check
+        if let Some(check) = actions[0].iter().position(|&a| a == Action::Check) {
+            game.play(check);
+            if !game.is_terminal_node() && !game.is_chance_node() {
+                game.cache_normalized_weights();
+                actions[1] = game.available_actions();
+                frequencies[1] = root_frequencies(game);
+            }
+            game.back_to_root();
+        }
+
+        Ok(Self {
+            flop: game.card_config().flop,
+            weight,
+            equity,
+            expected_values,
+            actions,
+            frequencies,
+        })
+    }
+}
+
+fn root_frequencies(game: &PostFlopGame) -> Vec<f32> {
+    let strategy = game.strategy();
+    let weights = game.normalized_weights(game.current_player());
+    strategy
+        .chunks_exact(weights.len())
+        .map(|chunk| compute_average(chunk, weights))
+        .collect()
+}
+
+impl FlopReport {
+    /// Creates a new empty report.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a row computed from the given solved game with the given weight.
+    #[inline]
+    pub fn add_game(&mut self, game: &mut PostFlopGame, weight: f64) -> Result<(), String> {
+        self.rows.push(FlopReportRow::from_game(game, weight)?);
+        Ok(())
+    }
+
+    /// Adds a precomputed row.
+    #[inline]
+    pub fn add_row(&mut self, row: FlopReportRow) {
+        self.rows.push(row);
+    }
+
+    /// Returns the rows of the report.
+    #[inline]
+    pub fn rows(&self) -> &[FlopReportRow] {
+        &self.rows
+    }
+
+    /// Returns the weighted average of all rows. The `flop` field of the result is `NOT_DEALT`.
+    ///
+    /// All rows must have the same actions (i.e., the games must share the tree configuration).
+    pub fn average(&self) -> Result<FlopReportRow, String> {
+        let first = self.rows.first().ok_or("Report is empty")?;
+        let total_weight = self.rows.iter().map(|row| row.weight).sum::<f64>();
+        if total_weight <= 0.0 {
+            return Err("Total weight must be positive".to_string());
+        }
+
+        let mut equity = [0.0; 2];
+        let mut expected_values = [0.0; 2];
+        let mut frequencies = [
+            vec![0.0; first.frequencies[0].len()],
+            vec![0.0; first.frequencies[1].len()],
+        ];
+
+        for row in &self.rows {
+            if row.actions != first.actions {
+                return Err(format!(
+                    "Actions of the flop {} differ from the first flop",
+                    flop_to_string(&row.flop)
+                ));
+            }
+
+            let weight = row.weight / total_weight;
+            for player in 0..2 {
+                equity[player] += weight * row.equity[player] as f64;
+                expected_values[player] += weight * row.expected_values[player] as f64;
+                for (sum, &freq) in frequencies[player].iter_mut().zip(&row.frequencies[player]) {
+                    *sum += weight * freq as f64;
+                }
+            }
+        }
+
+        Ok(FlopReportRow {
+            flop: [NOT_DEALT; 3],
+            weight: total_weight,
+            equity: equity.map(|x| x as f32),
+            expected_values: expected_values.map(|x| x as f32),
+            actions: first.actions.clone(),
+            frequencies: frequencies.map(|v| v.into_iter().map(|x| x as f32).collect()),
+        })
+    }
+
+    /// Formats the report as CSV, followed by a row of the weighted average.
+    ///
+    /// Columns: flop, weight, equity and EV of each player, and the frequency of each action.
+    pub fn to_csv(&self) -> Result<String, String> {
+        let average = self.average()?;
+        let mut result = String::new();
+
+        result.push_str("flop,weight,oop_equity,oop_ev,ip_equity,ip_ev");
+        for (player, name) in ["oop", "ip"].iter().enumerate() {
+            for action in &average.actions[player] {
+                write!(result, ",{name}_{action:?}").unwrap();
+            }
+        }
+        result.push('\n');
+
+        let rows = self.rows.iter().map(|row| (flop_to_string(&row.flop), row));
+        let average_row = std::iter::once(("average".to_string(), &average));
+
+        for (label, row) in rows.chain(average_row) {
+            write!(result, "{label},{}", row.weight).unwrap();
+            for player in 0..2 {
+                let equity = row.equity[player];
+                let ev = row.expected_values[player];
+                write!(result, ",{equity:.6},{ev:.6}").unwrap();
+            }
+            for player in 0..2 {
+                for freq in &row.frequencies[player] {
+                    write!(result, ",{freq:.6}").unwrap();
+                }
+            }
+            result.push('\n');
+        }
+
+        Ok(result)
+    }
+}
+
+fn flop_to_string(flop: &[u8; 3]) -> String {
+    flop.iter()
+        .rev()
+        .map(|&card| card_to_string(card).unwrap_or_default())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bet_size::*;
+    use crate::payoff::*;
+    use crate::solver::*;
+
+    fn solved_flop_game(flop: &str) -> PostFlopGame {
+        let card_config = CardConfig {
+            range: ["AA,KK,AK".parse().unwrap(), "QQ,JJ,AQ".parse().unwrap()],
+            flop: flop_from_str(flop).unwrap(),
+            turn: NOT_DEALT,
+            river: NOT_DEALT,
+        };
+
+        let bet_sizes = BetSizeCandidates::try_from(("50%", "")).unwrap();
+        let tree_config = TreeConfig {
+            starting_pot: 100,
+            effective_stack: 100,
+            flop_bet_sizes: [bet_sizes.clone(), bet_sizes],
+            ..Default::default()
+        };
+
+        let action_tree = ActionTree::new(tree_config).unwrap();
+        let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+        game.set_payoff_model(PayoffModel::ChipEv);
+        game.allocate_memory(false);
+        solve(&mut game, 50, 0.5, false);
+        game
+    }
+
+    #[test]
+    fn flop_subsets() {
+        for (subset, size) in [(&FLOP_SUBSET_95[..], 95), (&FLOP_SUBSET_184[..], 184)] {
+            assert_eq!(subset.len(), size);
+            let weighted = weighted_flop_subset(subset).unwrap();
+            assert_eq!(weighted.len(), size);
+            assert!(weighted.iter().all(|(_, weight)| *weight > 0));
+        }
+    }
+
+    #[test]
+    fn flop_report() {
+        let subset = weighted_flop_subset(&["Td9h6c", "7c7d2s"]).unwrap();
+        assert_eq!(subset[0].1, 24);
+        assert_eq!(subset[1].1, 12);
+        assert!(weighted_flop_subset(&["Td9d6h", "Th9h6c"]).is_err());
+
+        let mut report = FlopReport::new();
+        for (flop, weight) in &subset {
+            let flop_str = flop_to_string(flop);
+            let mut game = solved_flop_game(&flop_str);
+            report.add_game(&mut game, *weight as f64).unwrap();
+        }
+
+        let rows = report.rows();
+        assert_eq!(rows.len(), 2);
+        assert_eq!(format!("{:?}", rows[0].actions[0]), "[Check, Bet(50)]");
+        assert_eq!(format!("{:?}", rows[0].actions[1]), "[Check, Bet(50)]");
+
+        let average = report.average().unwrap();
+        let expected = (24.0 * rows[0].equity[0] + 12.0 * rows[1].equity[0]) / 36.0;
+        assert!((average.equity[0] - expected).abs() < 1e-5);
+        assert!((average.frequencies[0].iter().sum::<f32>() - 1.0).abs() < 1e-5);
+        assert!((average.equity[0] + average.equity[1] - 1.0).abs() < 1e-5);
+
+        let csv = report.to_csv().unwrap();
+        let lines = csv.lines().collect::<Vec<_>>();
+        assert_eq!(lines.len(), 4);
+        assert!(lines[0].ends_with("oop_Check,oop_Bet(50),ip_Check,ip_Bet(50)"));
+        assert!(lines[1].starts_with("Td9h6c,24,"));
+        assert!(lines[3].starts_with("average,36,"));
+    }
+}
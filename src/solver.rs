@@ -2,16 +2,76 @@ use crate::interface::*;
 use crate::mutex_like::*;
 use crate::sliceop::*;
 use crate::utility::*;
+use serde::{Deserialize, Serialize};
 use std::io::{self, Write};
 use std::mem::MaybeUninit;
+use std::ops::ControlFlow;
+use std::time::{Duration, Instant};
 
 #[cfg(feature = "custom-alloc")]
 use crate::alloc::*;
 
-struct DiscountParams {
+/// A variant of the CFR algorithm used by [`solve_with_options`].
+///
+/// In the following, `t` denotes the (zero-based) current iteration. The cumulative regrets and
+/// strategy are multiplied by the given discount factors before adding the values of iteration
+/// `t`.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub enum CfrAlgorithm {
+    /// Discounted CFR with the default schedule (α = 1.5, β = 0, γ = 3), where the averaging of
+    /// the strategy restarts at iterations 4^k.
+    #[default]
+    Dcfr,
+
+    /// Discounted CFR with the given parameters: positive and negative cumulative regrets are
+    /// multiplied by `t^alpha / (t^alpha + 1)` and `t^beta / (t^beta + 1)`, respectively, and the
+    /// cumulative strategy is multiplied by `(t / (t + 1))^gamma`.
+    DcfrCustom { alpha: f64, beta: f64, gamma: f64 },
+
+    /// CFR+: negative cumulative regrets are reset to zero, and the strategy is averaged with
+    /// weights proportional to the iteration count.
+    CfrPlus,
+
+    /// Linear CFR: both the regrets and the strategy are weighted proportionally to the iteration
+    /// count (equivalent to `DcfrCustom` with α = β = γ = 1).
+    LinearCfr,
+
+    /// Predictive CFR+: CFR+ whose strategy is computed from the cumulative regrets plus the
+    /// instantaneous regrets of the last iteration (the prediction). The strategy is averaged with
+    /// quadratic weights.
+    ///
+    /// This variant requires an additional storage of the same size as the cumulative regrets,
+    /// which is allocated when solving starts.
+    PcfrPlus,
+}
+
+/// Options of public chance sampling (see [`SolveOptions::chance_sampling`]).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct ChanceSampling {
+    /// Number of chance outcomes sampled at each chance node per iteration. Must be positive.
+    ///
+    /// Suit-isomorphic outcomes are sampled together, so this is the number of non-isomorphic
+    /// outcomes. Chance nodes with at most this number of outcomes are traversed exhaustively.
+    pub num_samples: usize,
+
+    /// Seed of the pseudo-random number generator. The same seed gives the same result.
+    pub seed: u64,
+}
+
+/// Lower bound of the probabilities in the entropy regularization, which keeps the bonus of the
+/// actions that are never taken finite.
+const ENTROPY_MIN_PROBABILITY: f32 = 1e-6;
+
+pub(crate) struct DiscountParams {
     alpha_t: f32,
     beta_t: f32,
     gamma_t: f32,
+    reset_negative_regrets: bool,
+    use_prediction: bool,
+    num_chance_samples: usize,
+    sampling_seed: u64,
+    temperature: f32,
+    model_probability: Option<f32>,
 }
 
 impl DiscountParams {
@@ -32,6 +92,254 @@ impl DiscountParams {
             alpha_t: (pow_alpha / (pow_alpha + 1.0)) as f32,
             beta_t: 0.5,
             gamma_t: pow_gamma as f32,
+            reset_negative_regrets: false,
+            use_prediction: false,
+            num_chance_samples: usize::MAX,
+            sampling_seed: 0,
+            temperature: 0.0,
+            model_probability: None,
+        }
+    }
+
+    pub fn with_algorithm(algorithm: CfrAlgorithm, current_iteration: u32) -> Self {
+        let t = current_iteration as f64;
+        let linear = t / (t + 1.0);
+
+        let (alpha_t, beta_t, gamma_t) = match algorithm {
+            CfrAlgorithm::Dcfr => return Self::new(current_iteration),
+            CfrAlgorithm::DcfrCustom { alpha, beta, gamma } => {
+                let pow_alpha = t.powf(alpha);
+                let pow_beta = t.powf(beta);
+                (
+                    pow_alpha / (pow_alpha + 1.0),
+                    pow_beta / (pow_beta + 1.0),
+                    linear.powf(gamma),
+                )
+            }
+            CfrAlgorithm::CfrPlus => (1.0, 1.0, linear),
+            CfrAlgorithm::LinearCfr => (linear, linear, linear),
+            CfrAlgorithm::PcfrPlus => (1.0, 1.0, linear * linear),
+        };
+
+        Self {
+            alpha_t: alpha_t as f32,
+            beta_t: beta_t as f32,
+            gamma_t: gamma_t as f32,
+            reset_negative_regrets: matches!(algorithm, CfrAlgorithm::CfrPlus),
+            use_prediction: matches!(algorithm, CfrAlgorithm::PcfrPlus),
+            num_chance_samples: usize::MAX,
+            sampling_seed: 0,
+            temperature: 0.0,
+            model_probability: None,
+        }
+    }
+
+    /// Enables public chance sampling in the iteration `current_iteration` if `sampling` is set.
+    pub fn with_chance_sampling(
+        mut self,
+        sampling: Option<ChanceSampling>,
+        current_iteration: u32,
+    ) -> Self {
+        if let Some(sampling) = sampling {
+            self.num_chance_samples = sampling.num_samples;
+            self.sampling_seed = mix_seed(sampling.seed, current_iteration as u64);
+        }
+        self
+    }
+
+    /// Enables the entropy regularization if `temperature` is set.
+    pub fn with_temperature(mut self, temperature: Option<f32>) -> Self {
+        if let Some(temperature) = temperature {
+            self.temperature = temperature;
+        }
+        self
+    }
+
+    /// Enables the restricted Nash response with the given probability of the locked strategies.
+    pub fn with_restricted_nash_response(mut self, probability: Option<f32>) -> Self {
+        self.model_probability = probability;
+        self
+    }
+
+    /// Returns the initial counterfactual reach probabilities of the opponent of `player`.
+    ///
+    /// With the restricted Nash response, the reach probabilities are split into those of the
+    /// branch where the opponent best-responds and those of the branch where the opponent follows
+    /// the locked strategies (the second element), weighted by the probabilities of the branches.
+    fn initial_cfreach<T: Game>(&self, game: &T, player: usize) -> (Vec<f32>, Option<Vec<f32>>) {
+        let weights = game.initial_weights(player ^ 1);
+        match self.model_probability {
+            None => (weights.to_vec(), None),
+            Some(p) => (
+                weights.iter().map(|&w| w * (1.0 - p)).collect(),
+                Some(weights.iter().map(|&w| w * p).collect()),
+            ),
+        }
+    }
+}
+
+/// Options of [`solve_with_options`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SolveOptions {
+    /// Maximum number of iterations.
+    pub max_num_iterations: u32,
+
+    /// Solving stops when the exploitability becomes less than or equal to this value.
+    pub target_exploitability: f32,
+
+    /// Maximum wall-clock time of solving (`None` for no limit).
+    ///
+    /// An iteration is not started if it is expected to exceed the limit together with the final
+    /// exploitability computation. Finalization is not included in the estimate, so the total
+    /// time may slightly exceed the limit.
+    pub max_duration: Option<Duration>,
+
+    /// Maximum fraction of the solving time spent on computing the exploitability (`None` to
+    /// compute it every 10 iterations).
+    ///
+    /// If specified, the interval between exploitability computations is adjusted from the
+    /// measured durations of an iteration and of an exploitability computation. For example,
+    /// `Some(0.1)` keeps the overhead of the best-response computations under about 10%. A value
+    /// less than or equal to `0.0` computes the exploitability only at the end.
+    pub max_exploitability_overhead: Option<f32>,
+
+    /// Number of iterations that have already been performed.
+    ///
+    /// This is nonzero only when resuming from a checkpoint. The discounting of DCFR depends on
+    /// the iteration count, so this must be the iteration count at which the checkpoint was saved
+    /// to obtain the same result as an uninterrupted solve. `max_num_iterations` includes these
+    /// iterations.
+    pub start_iteration: u32,
+
+    /// The variant of the CFR algorithm.
+    pub algorithm: CfrAlgorithm,
+
+    /// Public chance sampling (`None` to traverse all chance outcomes in every iteration).
+    ///
+    /// If specified, each iteration visits only a random subset of the outcomes at each chance
+    /// node, and the counterfactual values of the sampled outcomes are scaled up to keep them
+    /// unbiased. This makes an iteration much cheaper on large trees at the cost of noisier
+    /// updates, which is useful for quick exploratory solves. The exploitability is still
+    /// computed exactly. Note that the cumulative regrets and strategy of the subtrees that are
+    /// not sampled are not discounted in that iteration.
+    pub chance_sampling: Option<ChanceSampling>,
+
+    /// Temperature of the entropy regularization (`None` to compute a Nash equilibrium).
+    ///
+    /// If specified, each player maximizes the EV plus `temperature` times the entropy of the
+    /// strategy at each of their decision nodes (weighted by the probability of reaching the
+    /// node), and the solver converges to the entropy-regularized equilibrium instead of a Nash
+    /// equilibrium. At each node, the strategy of a hand is the logit (softmax) response to the
+    /// EVs of the actions divided by `temperature`, where the EVs include the entropy of the
+    /// later nodes; this is a quantal response equilibrium that models noisy opponents. The
+    /// temperature is in the unit of the payoffs (i.e., chips with the chip EV model), and a
+    /// higher temperature gives a more mixed strategy. Must be positive.
+    ///
+    /// The reported exploitability is still that of the unregularized game, which is positive at
+    /// the regularized equilibrium, so `target_exploitability` should be set accordingly. Bunching
+    /// effect is not supported.
+    pub temperature: Option<f32>,
+
+    /// Probability `p` of the restricted Nash response (`None` to always follow the locks).
+    ///
+    /// If specified, the locked strategies (see [`lock_current_strategy`]) are treated as a model
+    /// of the opponent that is trusted only with probability `p`: with probability `p`, the
+    /// opponent follows the locked strategies, and otherwise, the opponent best-responds with
+    /// their own strategy, which is free at the locked nodes. The other player solves against
+    /// this mixture without knowing which branch is taken. Therefore, `p = 1.0` is the same as
+    /// the usual node locking (full exploitation of the model), and `p = 0.0` ignores the locks
+    /// (Nash equilibrium); the values in between give a robust exploitation. Where the model does
+    /// not specify a strategy (unlocked nodes and hands), the opponent plays the same strategy in
+    /// both branches. If both players have locks, each player's locks are treated in the same
+    /// way. Must be between `0.0` and `1.0`, inclusive.
+    ///
+    /// The obtained strategy of the locked player is their free strategy, but the locked nodes
+    /// still show the locked strategies, and the reported exploitability is that of the usual
+    /// node locking.
+    ///
+    /// [`lock_current_strategy`]: crate::PostFlopGame::lock_current_strategy
+    pub restricted_nash_response: Option<f32>,
+}
+
+impl Default for SolveOptions {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            max_num_iterations: 1000,
+            target_exploitability: 0.0,
+            max_duration: None,
+            max_exploitability_overhead: None,
+            start_iteration: 0,
+            algorithm: CfrAlgorithm::default(),
+            chance_sampling: None,
+            temperature: None,
+            restricted_nash_response: None,
+        }
+    }
+}
+
+/// The reason why solving stopped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum StopReason {
+    /// The target exploitability was reached.
+    TargetExploitability,
+
+    /// The maximum number of iterations was reached.
+    MaxIterations,
+
+    /// The time limit was reached.
+    TimeLimit,
+
+    /// The callback cancelled solving.
+    Cancelled,
+}
+
+/// A report of [`solve_with_options`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SolveReport {
+    /// Number of performed iterations, including `start_iteration` of [`SolveOptions`].
+    pub num_iterations: u32,
+
+    /// Exploitability of the obtained strategy.
+    pub exploitability: f32,
+
+    /// Elapsed time of solving, including the exploitability computations and finalization.
+    pub elapsed: Duration,
+
+    /// The reason why solving stopped.
+    pub stop_reason: StopReason,
+}
+
+/// Progress information passed to the callback of [`solve_with_progress`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SolveProgress {
+    /// Number of completed iterations.
+    pub current_iteration: u32,
+
+    /// Maximum number of iterations.
+    pub max_num_iterations: u32,
+
+    /// Most recently computed exploitability.
+    pub exploitability: f32,
+
+    /// Elapsed time since the start of solving.
+    pub elapsed: Duration,
+}
+
+impl SolveOptions {
+    /// Returns the number of iterations between exploitability computations.
+    fn check_interval(&self, check_time: Duration, iteration_time: Duration) -> u32 {
+        match self.max_exploitability_overhead {
+            None => 10,
+            Some(ratio) if ratio <= 0.0 => u32::MAX,
+            Some(ratio) if ratio >= 1.0 || iteration_time.is_zero() => 1,
+            Some(ratio) => {
+                let ratio = ratio as f64;
+                let check_time = check_time.as_secs_f64();
+                let iteration_time = iteration_time.as_secs_f64();
+                let interval = check_time * (1.0 - ratio) / (ratio * iteration_time);
+                interval.ceil().clamp(1.0, u32::MAX as f64) as u32
+            }
         }
     }
 }
@@ -46,6 +354,77 @@ pub fn solve<T: Game>(
     target_exploitability: f32,
     print_progress: bool,
 ) -> f32 {
+    let exploitability = solve_with_progress(
+        game,
+        max_num_iterations,
+        target_exploitability,
+        |_, progress| {
+            if print_progress {
+                let iteration = progress.current_iteration;
+                let exploitability = progress.exploitability;
+                print!("\riteration: {iteration} / {max_num_iterations} ");
+                print!("(exploitability = {exploitability:.4e})");
+                io::stdout().flush().unwrap();
+            }
+            ControlFlow::Continue(())
+        },
+    );
+
+    if print_progress {
+        println!();
+        io::stdout().flush().unwrap();
+    }
+
+    exploitability
+}
+
+/// Performs Discounted CFR algorithm like [`solve`], reporting the progress to `callback`.
+///
+/// The `callback` is called once before the first iteration and then after every iteration with
+/// the game and the current progress. The exploitability is recomputed every 10 iterations, so
+/// the reported value may lag behind the current strategy. Returning [`ControlFlow::Break`] from
+/// the `callback` cancels solving; the game is finalized even in that case so that the partial
+/// strategy can be inspected and saved.
+///
+/// This method returns the exploitability of the obtained strategy.
+#[inline]
+pub fn solve_with_progress<T, F>(
+    game: &mut T,
+    max_num_iterations: u32,
+    target_exploitability: f32,
+    callback: F,
+) -> f32
+where
+    T: Game,
+    F: FnMut(&T, &SolveProgress) -> ControlFlow<()>,
+{
+    let options = SolveOptions {
+        max_num_iterations,
+        target_exploitability,
+        ..Default::default()
+    };
+
+    solve_with_options(game, &options, callback).exploitability
+}
+
+/// Performs Discounted CFR algorithm with the given options, reporting the progress to
+/// `callback`.
+///
+/// In addition to [`solve_with_progress`], this method supports a time limit and an adaptive
+/// interval of exploitability computations (see [`SolveOptions`]). The game is finalized
+/// regardless of the reason of stopping.
+///
+/// This method returns a report containing the number of performed iterations, the
+/// exploitability of the obtained strategy, and the reason why solving stopped.
+pub fn solve_with_options<T, F>(
+    game: &mut T,
+    options: &SolveOptions,
+    mut callback: F,
+) -> SolveReport
+where
+    T: Game,
+    F: FnMut(&T, &SolveProgress) -> ControlFlow<()>,
+{
     if game.is_solved() {
         panic!("the game is already solved");
     }
@@ -54,54 +433,133 @@ pub fn solve<T: Game>(
         panic!("the game is not ready");
     }
 
-    let mut root = game.root();
-    let mut exploitability = compute_exploitability(game);
-
-    if print_progress {
-        print!("iteration: 0 / {max_num_iterations} ");
-        print!("(exploitability = {exploitability:.4e})");
-        io::stdout().flush().unwrap();
+    if options.algorithm == CfrAlgorithm::PcfrPlus && !game.allocate_raw_regrets() {
+        panic!("the game does not support PCFR+");
     }
 
-    for t in 0..max_num_iterations {
-        if exploitability <= target_exploitability {
-            break;
-        }
+    if options.chance_sampling.is_some_and(|s| s.num_samples == 0) {
+        panic!("the number of chance samples must be positive");
+    }
 
-        let params = DiscountParams::new(t);
+    let start = Instant::now();
+    let mut root = game.root();
 
-        // alternating updates
-        for player in 0..2 {
-            let mut result = Vec::with_capacity(game.num_private_hands(player));
-            solve_recursive(
-                result.spare_capacity_mut(),
-                game,
-                &mut root,
-                player,
-                game.initial_weights(player ^ 1),
-                &params,
-            );
+    if let Some(temperature) = options.temperature {
+        if !(temperature > 0.0 && temperature.is_finite()) {
+            panic!("the temperature must be positive and finite");
         }
 
-        if (t + 1) % 10 == 0 || t + 1 == max_num_iterations {
-            exploitability = compute_exploitability(game);
+        let mut reach_sums = vec![0.0; game.num_private_hands(0)];
+        if !game.reach_sums(&mut reach_sums, &root, 0, game.initial_weights(1)) {
+            panic!("the game does not support the entropy regularization");
         }
+    }
+
+    if options
+        .restricted_nash_response
+        .is_some_and(|p| !(0.0..=1.0).contains(&p))
+    {
+        panic!("the probability of the restricted Nash response must be between 0 and 1");
+    }
+
+    let mut exploitability = compute_exploitability(game);
+    let mut check_time = start.elapsed();
+    let mut iteration_time = Duration::ZERO;
+    let mut num_iterations = options.start_iteration;
+    let mut num_iterations_since_check = 0;
+
+    let mut progress = SolveProgress {
+        current_iteration: num_iterations,
+        max_num_iterations: options.max_num_iterations,
+        exploitability,
+        elapsed: start.elapsed(),
+    };
+
+    let mut stop_reason = StopReason::Cancelled;
+
+    if callback(game, &progress).is_continue() {
+        loop {
+            if exploitability <= options.target_exploitability {
+                stop_reason = StopReason::TargetExploitability;
+                break;
+            }
 
-        if print_progress {
-            print!("\riteration: {} / {} ", t + 1, max_num_iterations);
-            print!("(exploitability = {exploitability:.4e})");
-            io::stdout().flush().unwrap();
+            if num_iterations >= options.max_num_iterations {
+                stop_reason = StopReason::MaxIterations;
+                break;
+            }
+
+            // stop if the next iteration and the final exploitability computation may exceed
+            // the time limit
+            if let Some(max_duration) = options.max_duration {
+                let average_time = iteration_time
+                    .checked_div(num_iterations - options.start_iteration)
+                    .unwrap_or_default();
+                if start.elapsed() + average_time + check_time > max_duration {
+                    stop_reason = StopReason::TimeLimit;
+                    break;
+                }
+            }
+
+            let iteration_start = Instant::now();
+            let params = DiscountParams::with_algorithm(options.algorithm, num_iterations)
+                .with_chance_sampling(options.chance_sampling, num_iterations)
+                .with_temperature(options.temperature)
+                .with_restricted_nash_response(options.restricted_nash_response);
+
+            // alternating updates
+            for player in 0..2 {
+                let mut result = Vec::with_capacity(game.num_private_hands(player));
+                let (cfreach, fixed_cfreach) = params.initial_cfreach(game, player);
+                solve_recursive(
+                    result.spare_capacity_mut(),
+                    game,
+                    &mut root,
+                    player,
+                    &cfreach,
+                    fixed_cfreach.as_deref(),
+                    &params,
+                    mix_seed(params.sampling_seed, player as u64),
+                );
+            }
+
+            iteration_time += iteration_start.elapsed();
+            num_iterations += 1;
+            num_iterations_since_check += 1;
+
+            let average_time = iteration_time / (num_iterations - options.start_iteration);
+            let interval = options.check_interval(check_time, average_time);
+            if num_iterations_since_check >= interval
+                || num_iterations == options.max_num_iterations
+            {
+                let check_start = Instant::now();
+                exploitability = compute_exploitability(game);
+                check_time = check_start.elapsed();
+                num_iterations_since_check = 0;
+            }
+
+            progress.current_iteration = num_iterations;
+            progress.exploitability = exploitability;
+            progress.elapsed = start.elapsed();
+            if callback(game, &progress).is_break() {
+                break;
+            }
         }
     }
 
-    if print_progress {
-        println!();
-        io::stdout().flush().unwrap();
+    // the last computed value may be stale
+    if num_iterations_since_check > 0 {
+        exploitability = compute_exploitability(game);
     }
 
     finalize(game);
 
-    exploitability
+    SolveReport {
+        num_iterations,
+        exploitability,
+        elapsed: start.elapsed(),
+        stop_reason,
+    }
 }
 
 /// Proceeds Discounted CFR algorithm for one iteration.
@@ -115,35 +573,58 @@ pub fn solve_step<T: Game>(game: &T, current_iteration: u32) {
         panic!("the game is not ready");
     }
 
+    solve_step_with_params(game, &DiscountParams::new(current_iteration));
+}
+
+/// Proceeds one iteration with the given parameters and returns the counterfactual values of
+/// each player at the root, which are computed before the update of the player.
+pub(crate) fn solve_step_with_params<T: Game>(game: &T, params: &DiscountParams) -> [Vec<f32>; 2] {
     let mut root = game.root();
-    let params = DiscountParams::new(current_iteration);
 
     // alternating updates
-    for player in 0..2 {
+    [0, 1].map(|player| {
         let mut result = Vec::with_capacity(game.num_private_hands(player));
+        let (cfreach, fixed_cfreach) = params.initial_cfreach(game, player);
         solve_recursive(
             result.spare_capacity_mut(),
             game,
             &mut root,
             player,
-            game.initial_weights(player ^ 1),
-            &params,
+            &cfreach,
+            fixed_cfreach.as_deref(),
+            params,
+            mix_seed(params.sampling_seed, player as u64),
         );
-    }
+        unsafe { result.set_len(game.num_private_hands(player)) };
+        result
+    })
 }
 
 /// Recursively solves the counterfactual values.
+///
+/// `fixed_cfreach` is the counterfactual reach probabilities of the branch where the opponent
+/// follows the locked strategies, which is given only with the restricted Nash response. `seed` is
+/// used for sampling the chance outcomes when public chance sampling is enabled.
+#[allow(clippy::too_many_arguments)]
 fn solve_recursive<T: Game>(
     result: &mut [MaybeUninit<f32>],
     game: &T,
     node: &mut T::Node,
     player: usize,
     cfreach: &[f32],
+    fixed_cfreach: Option<&[f32]>,
     params: &DiscountParams,
+    seed: u64,
 ) {
     // return the counterfactual values when the `node` is terminal
     if node.is_terminal() {
-        game.evaluate(result, node, player, cfreach);
+        match fixed_cfreach {
+            None => game.evaluate(result, node, player, cfreach),
+            Some(fixed_cfreach) => {
+                let mixed_cfreach = add_slices(cfreach, fixed_cfreach);
+                game.evaluate(result, node, player, &mixed_cfreach);
+            }
+        }
         return;
     }
 
@@ -153,7 +634,16 @@ fn solve_recursive<T: Game>(
     // simply recurse when the number of actions is one
     if num_actions == 1 && !node.is_chance() {
         let child = &mut node.play(0);
-        solve_recursive(result, game, child, player, cfreach, params);
+        solve_recursive(
+            result,
+            game,
+            child,
+            player,
+            cfreach,
+            fixed_cfreach,
+            params,
+            seed,
+        );
         return;
     }
 
@@ -165,6 +655,15 @@ fn solve_recursive<T: Game>(
 
     // if the `node` is chance
     if node.is_chance() {
+        // sample the chance outcomes (the reach probabilities are scaled to be unbiased)
+        let num_samples = params.num_chance_samples;
+        let (sampled, sampling_scale) = if num_actions > num_samples {
+            let sampled = sample_actions(num_actions, num_samples, seed);
+            (Some(sampled), num_actions as f32 / num_samples as f32)
+        } else {
+            (None, 1.0)
+        };
+
         // update the reach probabilities
         #[cfg(feature = "custom-alloc")]
         let mut cfreach_updated = Vec::with_capacity_in(cfreach.len(), StackAlloc);
@@ -173,19 +672,35 @@ fn solve_recursive<T: Game>(
         mul_slice_scalar_uninit(
             cfreach_updated.spare_capacity_mut(),
             cfreach,
-            1.0 / game.chance_factor(node) as f32,
+            sampling_scale / game.chance_factor(node) as f32,
         );
         unsafe { cfreach_updated.set_len(cfreach.len()) };
 
+        let fixed_cfreach_updated = fixed_cfreach.map(|fixed_cfreach| {
+            let scale = sampling_scale / game.chance_factor(node) as f32;
+            fixed_cfreach.iter().map(|&x| x * scale).collect::<Vec<_>>()
+        });
+
         // compute the counterfactual values of each action
         for_each_child(node, |action| {
+            if sampled.as_ref().is_some_and(|sampled| !sampled[action]) {
+                row_mut(cfv_actions.lock().spare_capacity_mut(), action, num_hands)
+                    .iter_mut()
+                    .for_each(|v| {
+                        v.write(0.0);
+                    });
+                return;
+            }
+
             solve_recursive(
                 row_mut(cfv_actions.lock().spare_capacity_mut(), action, num_hands),
                 game,
                 &mut node.play(action),
                 player,
                 &cfreach_updated,
+                fixed_cfreach_updated.as_deref(),
                 params,
+                mix_seed(seed, action as u64),
             );
         });
 
@@ -232,7 +747,9 @@ fn solve_recursive<T: Game>(
                 &mut node.play(action),
                 player,
                 cfreach,
+                fixed_cfreach,
                 params,
+                mix_seed(seed, action as u64),
             );
         });
 
@@ -243,13 +760,40 @@ fn solve_recursive<T: Game>(
             regret_matching(node.regrets(), num_actions)
         };
 
-        // node-locking
-        let locking = game.locking_strategy(node);
+        // node-locking (the player is free with the restricted Nash response)
+        let locking = match params.model_probability {
+            None => game.locking_strategy(node),
+            Some(_) => &[],
+        };
         apply_locking_strategy(&mut strategy, locking);
 
-        // sum up the counterfactual values
+        // frequency locking (the player is free with the restricted Nash response); the
+        // multipliers were fitted to the reach probabilities of this player in the last pass of
+        // the opponent that visited this node, and these reach probabilities are unchanged unless
+        // chance sampling skipped this node in that pass, because only the regrets of the
+        // opponent have been updated since then
+        if params.model_probability.is_none() {
+            game.apply_frequency_lock(node, &mut strategy, None);
+        }
+
         let mut cfv_actions = cfv_actions.lock();
         unsafe { cfv_actions.set_len(num_actions * num_hands) };
+
+        // entropy regularization
+        if params.temperature > 0.0 {
+            let mixed_cfreach = fixed_cfreach.map(|fixed| add_slices(cfreach, fixed));
+            add_entropy_bonus(
+                &mut cfv_actions,
+                &strategy,
+                game,
+                node,
+                player,
+                mixed_cfreach.as_deref().unwrap_or(cfreach),
+                params.temperature,
+            );
+        }
+
+        // sum up the counterfactual values
         let result = fma_slices_uninit(result, &strategy, &cfv_actions);
 
         if game.is_compression_enabled() {
@@ -273,19 +817,57 @@ fn solve_recursive<T: Game>(
             let new_scale = encode_unsigned_slice(cum_strategy, &strategy);
             node.set_strategy_scale(new_scale);
 
-            // update the cumulative regret
-            let scale = node.regret_scale();
-            let alpha_decoder = params.alpha_t * scale / i16::MAX as f32;
-            let beta_decoder = params.beta_t * scale / i16::MAX as f32;
-            let cum_regret = node.regrets_compressed_mut();
-
-            cfv_actions.iter_mut().zip(&*cum_regret).for_each(|(x, y)| {
-                *x += *y as f32 * if *y >= 0 { alpha_decoder } else { beta_decoder };
-            });
-
-            cfv_actions.chunks_exact_mut(num_hands).for_each(|row| {
-                sub_slice(row, result);
-            });
+            if params.use_prediction {
+                // compute the instantaneous regrets (used as the prediction)
+                cfv_actions.chunks_exact_mut(num_hands).for_each(|row| {
+                    sub_slice(row, result);
+                });
+
+                // update the raw cumulative regret
+                let decoder = node.raw_regret_scale() / i16::MAX as f32;
+                let raw_regret = node.raw_regrets_compressed_mut();
+
+                #[cfg(feature = "custom-alloc")]
+                let mut raw_regret_f32 = Vec::with_capacity_in(raw_regret.len(), StackAlloc);
+                #[cfg(not(feature = "custom-alloc"))]
+                let mut raw_regret_f32 = Vec::with_capacity(raw_regret.len());
+
+                raw_regret_f32.extend(
+                    raw_regret
+                        .iter()
+                        .zip(&*cfv_actions)
+                        .map(|(x, y)| max(*x as f32 * decoder + *y, 0.0)),
+                );
+
+                let new_scale = encode_signed_slice(raw_regret, &raw_regret_f32);
+                node.set_raw_regret_scale(new_scale);
+
+                // add the prediction
+                cfv_actions
+                    .iter_mut()
+                    .zip(&raw_regret_f32)
+                    .for_each(|(x, y)| {
+                        *x += *y;
+                    });
+            } else {
+                // update the cumulative regret
+                let scale = node.regret_scale();
+                let alpha_decoder = params.alpha_t * scale / i16::MAX as f32;
+                let beta_decoder = params.beta_t * scale / i16::MAX as f32;
+                let cum_regret = node.regrets_compressed();
+
+                cfv_actions.iter_mut().zip(cum_regret).for_each(|(x, y)| {
+                    *x += *y as f32 * if *y >= 0 { alpha_decoder } else { beta_decoder };
+                });
+
+                cfv_actions.chunks_exact_mut(num_hands).for_each(|row| {
+                    sub_slice(row, result);
+                });
+
+                if params.reset_negative_regrets {
+                    cfv_actions.iter_mut().for_each(|x| *x = max(*x, 0.0));
+                }
+            }
 
             if !locking.is_empty() {
                 cfv_actions.iter_mut().zip(locking).for_each(|(d, s)| {
@@ -295,6 +877,7 @@ fn solve_recursive<T: Game>(
                 })
             }
 
+            let cum_regret = node.regrets_compressed_mut();
             let new_scale = encode_signed_slice(cum_regret, &cfv_actions);
             node.set_regret_scale(new_scale);
         } else {
@@ -305,16 +888,42 @@ fn solve_recursive<T: Game>(
                 *x = *x * gamma + *y;
             });
 
-            // update the cumulative regret
-            let (alpha, beta) = (params.alpha_t, params.beta_t);
-            let cum_regret = node.regrets_mut();
-            cum_regret.iter_mut().zip(&*cfv_actions).for_each(|(x, y)| {
-                let coef = if x.is_sign_positive() { alpha } else { beta };
-                *x = *x * coef + *y;
-            });
-            cum_regret.chunks_exact_mut(num_hands).for_each(|row| {
-                sub_slice(row, result);
-            });
+            if params.use_prediction {
+                // compute the instantaneous regrets (used as the prediction)
+                cfv_actions.chunks_exact_mut(num_hands).for_each(|row| {
+                    sub_slice(row, result);
+                });
+
+                // update the raw cumulative regret
+                let raw_regret = node.raw_regrets_mut();
+                raw_regret.iter_mut().zip(&*cfv_actions).for_each(|(x, y)| {
+                    *x = max(*x + *y, 0.0);
+                });
+
+                // add the prediction
+                cfv_actions
+                    .iter_mut()
+                    .zip(node.raw_regrets())
+                    .for_each(|(x, y)| {
+                        *x += *y;
+                    });
+                node.regrets_mut().copy_from_slice(&cfv_actions);
+            } else {
+                // update the cumulative regret
+                let (alpha, beta) = (params.alpha_t, params.beta_t);
+                let cum_regret = node.regrets_mut();
+                cum_regret.iter_mut().zip(&*cfv_actions).for_each(|(x, y)| {
+                    let coef = if x.is_sign_positive() { alpha } else { beta };
+                    *x = *x * coef + *y;
+                });
+                cum_regret.chunks_exact_mut(num_hands).for_each(|row| {
+                    sub_slice(row, result);
+                });
+
+                if params.reset_negative_regrets {
+                    cum_regret.iter_mut().for_each(|x| *x = max(*x, 0.0));
+                }
+            }
         }
     }
     // if the current player is not `player`
@@ -326,9 +935,23 @@ fn solve_recursive<T: Game>(
             regret_matching(node.regrets(), num_actions)
         };
 
-        // node-locking
+        // node-locking and frequency locking (only the branch following the locks with the
+        // restricted Nash response)
         let locking = game.locking_strategy(node);
-        apply_locking_strategy(&mut cfreach_actions, locking);
+        if fixed_cfreach.is_none() {
+            apply_locking_strategy(&mut cfreach_actions, locking);
+            game.apply_frequency_lock(node, &mut cfreach_actions, Some(cfreach));
+        }
+
+        let fixed_cfreach_actions = fixed_cfreach.map(|fixed_cfreach| {
+            let mut fixed_cfreach_actions = cfreach_actions.to_vec();
+            apply_locking_strategy(&mut fixed_cfreach_actions, locking);
+            game.apply_frequency_lock(node, &mut fixed_cfreach_actions, Some(fixed_cfreach));
+            fixed_cfreach_actions
+                .chunks_exact_mut(fixed_cfreach.len())
+                .for_each(|row| mul_slice(row, fixed_cfreach));
+            fixed_cfreach_actions
+        });
 
         // update the reach probabilities
         let row_size = cfreach.len();
@@ -344,7 +967,11 @@ fn solve_recursive<T: Game>(
                 &mut node.play(action),
                 player,
                 row(&cfreach_actions, action, row_size),
+                fixed_cfreach_actions
+                    .as_deref()
+                    .map(|fixed| row(fixed, action, row_size)),
                 params,
+                mix_seed(seed, action as u64),
             );
         });
 
@@ -355,6 +982,56 @@ fn solve_recursive<T: Game>(
     }
 }
 
+/// Returns the element-wise sum of two slices.
+fn add_slices(lhs: &[f32], rhs: &[f32]) -> Vec<f32> {
+    lhs.iter().zip(rhs).map(|(x, y)| x + y).collect()
+}
+
+/// Adds `-temperature * ln(strategy)` times the reach of the opponent to the counterfactual values
+/// of each action. Then, the counterfactual value of the node includes the entropy bonus, and the
+/// regrets follow the gradient of the entropy-regularized utility.
+fn add_entropy_bonus<T: Game>(
+    cfv_actions: &mut [f32],
+    strategy: &[f32],
+    game: &T,
+    node: &T::Node,
+    player: usize,
+    cfreach: &[f32],
+    temperature: f32,
+) {
+    let num_hands = game.num_private_hands(player);
+    let mut reach_sums = vec![0.0; num_hands];
+    game.reach_sums(&mut reach_sums, node, player, cfreach);
+
+    cfv_actions
+        .chunks_exact_mut(num_hands)
+        .zip(strategy.chunks_exact(num_hands))
+        .for_each(|(cfv_row, strategy_row)| {
+            cfv_row
+                .iter_mut()
+                .zip(strategy_row.iter().zip(&reach_sums))
+                .for_each(|(v, (&s, &r))| {
+                    *v -= temperature * r * s.max(ENTROPY_MIN_PROBABILITY).ln();
+                });
+        });
+}
+
+/// Samples `num_samples` distinct actions out of `num_actions` uniformly at random.
+fn sample_actions(num_actions: usize, num_samples: usize, seed: u64) -> Vec<bool> {
+    let mut state = seed;
+    let mut indices = (0..num_actions).collect::<Vec<_>>();
+    let mut sampled = vec![false; num_actions];
+
+    // partial Fisher-Yates shuffle
+    for i in 0..num_samples {
+        let j = i + (splitmix64(&mut state) % (num_actions - i) as u64) as usize;
+        indices.swap(i, j);
+        sampled[indices[i]] = true;
+    }
+
+    sampled
+}
+
 /// Computes the strategy by regret-matching algorithm.
 #[cfg(feature = "custom-alloc")]
 #[inline]
@@ -0,0 +1,429 @@
+use crate::action_tree::*;
+use crate::game::*;
+use crate::hand::*;
+use crate::interface::*;
+use crate::payoff::*;
+use crate::utility::*;
+use std::collections::BTreeMap;
+
+/// A struct containing the configuration of [`TrainerSession`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TrainerConfig {
+    /// Seed of the random sampling of the questions.
+    pub seed: u64,
+
+    /// The player to be quizzed (`None` quizzes both players).
+    pub player: Option<usize>,
+
+    /// Maximum EV loss of an answer counted as correct, as a fraction of the pot at the decision
+    /// node. With an ICM payoff model, the pot is valued in the unit of the model, i.e., as the
+    /// difference between the tournament equities of winning and losing half of the pot.
+    pub max_ev_loss: f32,
+}
+
+impl Default for TrainerConfig {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            seed: 0,
+            player: None,
+            max_ev_loss: 0.01,
+        }
+    }
+}
+
+/// A decision point sampled by [`TrainerSession::next_question`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct TrainerQuestion {
+    /// History of the decision node, which can be passed to [`PostFlopGame::apply_history`].
+    pub history: Vec<usize>,
+
+    /// Line of actions from the root, including the dealt cards as [`Action::Chance`].
+    pub line: Vec<Action>,
+
+    /// Board cards at the decision node.
+    pub board: Vec<u8>,
+
+    /// Pot at the decision node (the starting pot plus the total bet amounts).
+    pub pot: i32,
+
+    /// The player to act.
+    pub player: usize,
+
+    /// Hole cards of the hero.
+    pub hand: (u8, u8),
+
+    /// Index of `hand` in [`PostFlopGame::private_cards`].
+    pub hand_index: usize,
+
+    /// Made-hand category of `hand` on `board`.
+    pub category: HandCategory,
+
+    /// Available actions at the decision node.
+    pub actions: Vec<Action>,
+}
+
+/// A result of [`TrainerSession::answer`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct TrainerAnswer {
+    /// The chosen action.
+    pub action: Action,
+
+    /// The action with the highest expected value.
+    pub best_action: Action,
+
+    /// EV loss of the chosen action against the best action.
+    pub ev_loss: f32,
+
+    /// Whether the EV loss is within [`TrainerConfig::max_ev_loss`].
+    pub is_correct: bool,
+
+    /// Frequencies of the actions for the hand of the hero.
+    pub strategy: Vec<f32>,
+
+    /// Expected values of the actions for the hand of the hero.
+    pub expected_values: Vec<f32>,
+}
+
+/// Accuracy statistics of a [`TrainerSession`].
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct TrainerStats {
+    /// The number of answered questions.
+    pub num_answers: usize,
+
+    /// The number of correct answers.
+    pub num_correct: usize,
+
+    /// Total EV loss of the answers.
+    pub total_ev_loss: f64,
+}
+
+impl TrainerStats {
+    /// Returns the ratio of the correct answers (`0.0` if no question is answered).
+    #[inline]
+    pub fn accuracy(&self) -> f64 {
+        if self.num_answers == 0 {
+            0.0
+        } else {
+            self.num_correct as f64 / self.num_answers as f64
+        }
+    }
+
+    /// Returns the average EV loss of the answers (`0.0` if no question is answered).
+    #[inline]
+    pub fn average_ev_loss(&self) -> f64 {
+        if self.num_answers == 0 {
+            0.0
+        } else {
+            self.total_ev_loss / self.num_answers as f64
+        }
+    }
+
+    #[inline]
+    fn add(&mut self, answer: &TrainerAnswer) {
+        self.num_answers += 1;
+        self.num_correct += answer.is_correct as usize;
+        self.total_ev_loss += answer.ev_loss as f64;
+    }
+}
+
+/// A quiz session on a solved [`PostFlopGame`].
+///
+/// Each question is a decision point: a node of the game and a hand of the player to act. It is
+/// sampled by playing out the game from the root, where the cards are dealt uniformly at random
+/// and the actions are taken with the frequencies of the whole range of the acting player (i.e.,
+/// the strategy weighted by [`PostFlopGame::normalized_weights`]). One of the decision nodes on
+/// the path is then chosen uniformly, and the path is rejected with the probability of one minus
+/// its number of decision nodes divided by the maximum number in the tree, so that each node is
+/// sampled with the probability proportional to its reach probability. The hand is chosen with
+/// the probability proportional to its normalized weight at the node.
+///
+/// Note that the dealt cards are uniform over the possible cards, i.e., the card removal by the
+/// ranges is not reflected in the reach probabilities of the nodes after a chance node.
+///
+/// The answers are scored by the EV loss against the best action, computed with
+/// [`PostFlopGame::expected_values_detail`] (in chips, or in the unit of the ICM payoff model if
+/// the game uses one), and the accuracy is tracked per made-hand category.
+#[derive(Debug, Clone)]
+pub struct TrainerSession {
+    config: TrainerConfig,
+    rng_state: u64,
+    stats: TrainerStats,
+    category_stats: BTreeMap<HandCategory, TrainerStats>,
+}
+
+impl TrainerSession {
+    /// Creates a new session.
+    #[inline]
+    pub fn new(config: TrainerConfig) -> Self {
+        Self {
+            config,
+            rng_state: config.seed,
+            stats: TrainerStats::default(),
+            category_stats: BTreeMap::new(),
+        }
+    }
+
+    /// Returns the configuration.
+    #[inline]
+    pub fn config(&self) -> &TrainerConfig {
+        &self.config
+    }
+
+    /// Returns the statistics of all answers in this session.
+    #[inline]
+    pub fn stats(&self) -> &TrainerStats {
+        &self.stats
+    }
+
+    /// Returns the statistics of the answers in this session for each made-hand category.
+    #[inline]
+    pub fn category_stats(&self) -> &BTreeMap<HandCategory, TrainerStats> {
+        &self.category_stats
+    }
+
+    /// Samples a decision point of `game`.
+    ///
+    /// The game must be solved. The current node of `game` is moved back to the root.
+    pub fn next_question(&mut self, game: &mut PostFlopGame) -> Result<TrainerQuestion, String> {
+        if !game.is_solved() {
+            return Err("Game is not solved".to_string());
+        }
+
+        if let Some(player) = self.config.player {
+            if player > 1 {
+                return Err(format!("Invalid player: {player}"));
+            }
+        }
+
+        const MAX_ATTEMPTS: usize = 1000;
+
+        game.back_to_root();
+        let max_num_decisions = max_num_decisions(game, self.config.player);
+
+        for _ in 0..MAX_ATTEMPTS {
+            if let Some(question) = self.sample_question(game, max_num_decisions) {
+                game.back_to_root();
+                return Ok(question);
+            }
+        }
+
+        game.back_to_root();
+        Err("Failed to sample a decision point".to_string())
+    }
+
+    /// Scores `action` (an index of [`TrainerQuestion::actions`]) for `question` and records it
+    /// to the statistics.
+    ///
+    /// The game must be the one passed to [`next_question`]. The current node of `game` is moved
+    /// back to the root.
+    ///
+    /// [`next_question`]: #method.next_question
+    pub fn answer(
+        &mut self,
+        game: &mut PostFlopGame,
+        question: &TrainerQuestion,
+        action: usize,
+    ) -> Result<TrainerAnswer, String> {
+        if !game.is_solved() {
+            return Err("Game is not solved".to_string());
+        }
+
+        if action >= question.actions.len() {
+            return Err(format!("Invalid action: {action}"));
+        }
+
+        game.apply_history(&question.history);
+        if game.is_terminal_node()
+            || game.is_chance_node()
+            || game.current_player() != question.player
+            || game.available_actions() != question.actions
+            || game.private_cards(question.player).get(question.hand_index) != Some(&question.hand)
+        {
+            game.back_to_root();
+            return Err("Question does not match the game".to_string());
+        }
+
+        game.cache_normalized_weights();
+        let num_hands = game.num_private_hands(question.player);
+        let column = |values: &[f32]| {
+            (0..question.actions.len())
+                .map(|i| values[i * num_hands + question.hand_index])
+                .collect::<Vec<_>>()
+        };
+
+        let strategy = column(&game.strategy());
+        let expected_values = column(&game.expected_values_detail(question.player));
+        game.back_to_root();
+
+        let pot_value = pot_value(game, question.player, question.pot)?;
+
+        let best = (0..expected_values.len())
+            .max_by(|&a, &b| expected_values[a].total_cmp(&expected_values[b]))
+            .unwrap();
+        let ev_loss = max(expected_values[best] - expected_values[action], 0.0);
+        let is_correct = ev_loss <= self.config.max_ev_loss * pot_value;
+
+        let answer = TrainerAnswer {
+            action: question.actions[action],
+            best_action: question.actions[best],
+            ev_loss,
+            is_correct,
+            strategy,
+            expected_values,
+        };
+
+        self.stats.add(&answer);
+        self.category_stats
+            .entry(question.category)
+            .or_default()
+            .add(&answer);
+
+        Ok(answer)
+    }
+
+    /// Plays out `game` from the root and samples a decision point on the path. Returns `None` if
+    /// the path is rejected.
+    fn sample_question(
+        &mut self,
+        game: &mut PostFlopGame,
+        max_num_decisions: usize,
+    ) -> Option<TrainerQuestion> {
+        game.back_to_root();
+
+        let mut line = Vec::new();
+        let mut candidates = Vec::new();
+
+        while !game.is_terminal_node() {
+            if game.is_chance_node() {
+                let possible_cards = game.possible_cards();
+                let num_cards = possible_cards.count_ones() as u64;
+                let k = (self.next_random() % num_cards) as usize;
+                let card = (0..52).filter(|&c| possible_cards & (1 << c) != 0).nth(k)?;
+                game.play(card);
+                line.push(Action::Chance(card as u8));
+                continue;
+            }
+
+            let player = game.current_player();
+            #[allow(clippy::unnecessary_map_or)]
+            let is_target = self.config.player.map_or(true, |p| p == player);
+            if is_target {
+                candidates.push((game.history().to_vec(), line.clone()));
+            }
+
+            game.cache_normalized_weights();
+            let weights = game.normalized_weights(player);
+            let num_hands = weights.len();
+            let strategy = game.strategy();
+            let frequencies = (0..game.available_actions().len())
+                .map(|i| weighted_sum(&strategy[i * num_hands..(i + 1) * num_hands], weights))
+                .collect::<Vec<_>>();
+
+            let action = self.sample_index(&frequencies)?;
+            line.push(game.available_actions()[action]);
+            game.play(action);
+        }
+
+        // accepts the path with the probability proportional to its number of decision nodes
+        let k = (self.next_random() % max_num_decisions.max(1) as u64) as usize;
+        if k >= candidates.len() {
+            return None;
+        }
+
+        let (history, line) = candidates.swap_remove(k);
+        game.apply_history(&history);
+        game.cache_normalized_weights();
+
+        let player = game.current_player();
+        let hand_index = self.sample_index(game.normalized_weights(player))?;
+        let hand = game.private_cards(player)[hand_index];
+        let board = game.current_board();
+        let total_bet_amount = game.total_bet_amount();
+
+        Some(TrainerQuestion {
+            history,
+            line,
+            pot: game.tree_config().starting_pot + total_bet_amount[0] + total_bet_amount[1],
+            player,
+            hand,
+            hand_index,
+            category: hand_category(&board, hand),
+            board,
+            actions: game.available_actions(),
+        })
+    }
+
+    /// Samples an index with the probability proportional to `weights` (`None` if all weights are
+    /// zero).
+    fn sample_index(&mut self, weights: &[f32]) -> Option<usize> {
+        let sum = weights.iter().fold(0.0, |sum, &w| sum + w as f64);
+        if sum <= 0.0 {
+            return None;
+        }
+
+        let mut threshold = sum * (self.next_random() >> 11) as f64 / (1u64 << 53) as f64;
+        let mut last = None;
+        for (i, &w) in weights.iter().enumerate() {
+            if w > 0.0 {
+                if threshold < w as f64 {
+                    return Some(i);
+                }
+                threshold -= w as f64;
+                last = Some(i);
+            }
+        }
+
+        last
+    }
+
+    #[inline]
+    fn next_random(&mut self) -> u64 {
+        splitmix64(&mut self.rng_state)
+    }
+}
+
+/// Returns the maximum number of decision nodes of `player` (`None` counts both players) on a
+/// path from the current node. Only the first possible card is played at chance nodes, since the
+/// action lines are the same for all cards.
+fn max_num_decisions(game: &mut PostFlopGame, player: Option<usize>) -> usize {
+    if game.is_terminal_node() {
+        return 0;
+    }
+
+    let history = game.history().to_vec();
+
+    if game.is_chance_node() {
+        let card = game.possible_cards().trailing_zeros() as usize;
+        game.play(card);
+        let result = max_num_decisions(game, player);
+        game.apply_history(&history);
+        return result;
+    }
+
+    #[allow(clippy::unnecessary_map_or)]
+    let is_target = player.map_or(true, |p| p == game.current_player());
+
+    let mut result = 0;
+    for action in 0..game.available_actions().len() {
+        game.play(action);
+        result = result.max(max_num_decisions(game, player));
+        game.apply_history(&history);
+    }
+
+    result + is_target as usize
+}
+
+/// Returns the value of `pot` for `player` in the unit of the payoff model of `game`.
+fn pot_value(game: &PostFlopGame, player: usize, pot: i32) -> Result<f32, String> {
+    let icm = match game.payoff_model() {
+        Some(PayoffModel::ChipEv) => return Ok(pot as f32),
+        Some(PayoffModel::Icm(icm)) => icm,
+        None => DEFAULT_ICM_UTILITY.as_ref().map_err(Clone::clone)?,
+    };
+
+    let stack = icm.starting_stack(player);
+    let half_pot = 0.5 * pot as f64;
+    let value = icm.lookup(stack + half_pot, player) - icm.lookup(stack - half_pot, player);
+    Ok(value as f32)
+}
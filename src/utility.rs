@@ -52,6 +52,23 @@ pub(crate) fn is_zero(x: f32) -> bool {
     x.to_bits() == 0
 }
 
+/// Advances the SplitMix64 generator and returns the next pseudo-random value.
+#[inline]
+pub(crate) fn splitmix64(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+/// Derives a new seed from `seed` and `value`.
+#[inline]
+pub(crate) fn mix_seed(seed: u64, value: u64) -> u64 {
+    let mut state = seed ^ value.wrapping_mul(0xff51_afd7_ed55_8ccd);
+    splitmix64(&mut state)
+}
+
 #[inline]
 pub(crate) fn vec_memory_usage<T>(vec: &Vec<T>) -> u64 {
     vec.capacity() as u64 * mem::size_of::<T>() as u64
@@ -70,7 +87,7 @@ pub fn compute_average(slice: &[f32], weights: &[f32]) -> f32 {
 }
 
 #[inline]
-fn weighted_sum(values: &[f32], weights: &[f32]) -> f32 {
+pub(crate) fn weighted_sum(values: &[f32], weights: &[f32]) -> f32 {
     let f = |sum: f64, (&v, &w): (&f32, &f32)| sum + v as f64 * w as f64;
     values.iter().zip(weights).fold(0.0, f) as f32
 }
@@ -252,6 +269,23 @@ pub fn finalize<T: Game>(game: &mut T) {
     }
 
     // compute the expected values and save them
+    save_cfvalues(game);
+
+    // set the game solved
+    game.set_solved();
+
+    // free buffer
+    #[cfg(all(feature = "custom-alloc", feature = "rayon"))]
+    rayon::broadcast(|_| free_custom_alloc_buffer());
+    #[cfg(all(feature = "custom-alloc", not(feature = "rayon")))]
+    free_custom_alloc_buffer();
+}
+
+/// Computes the counterfactual values of the current strategy and saves them in the storage.
+///
+/// Since the counterfactual values of the player nodes share the storage with the regrets, the
+/// regrets are overwritten.
+pub(crate) fn save_cfvalues<T: Game>(game: &T) {
     for player in 0..2 {
         let mut cfvalues = Vec::with_capacity(game.num_private_hands(player));
         compute_cfvalue_recursive(
@@ -263,15 +297,6 @@ pub fn finalize<T: Game>(game: &mut T) {
             true,
         );
     }
-
-    // set the game solved
-    game.set_solved();
-
-    // free buffer
-    #[cfg(all(feature = "custom-alloc", feature = "rayon"))]
-    rayon::broadcast(|_| free_custom_alloc_buffer());
-    #[cfg(all(feature = "custom-alloc", not(feature = "rayon")))]
-    free_custom_alloc_buffer();
 }
 
 /// Computes the exploitability of the current strategy.
@@ -668,7 +693,16 @@ fn compute_best_cfv_recursive<T: Game>(
         let mut cfv_actions = cfv_actions.lock();
         unsafe { cfv_actions.set_len(num_actions * num_hands) };
 
-        if locking.is_empty() {
+        if game.is_frequency_locked(node) {
+            // when the frequencies are locked, the player follows the current strategy (the best
+            // response under the constraint is not computed, so the value is under-estimated)
+            let strategy = if game.is_compression_enabled() {
+                normalized_strategy_compressed(node.strategy_compressed(), num_actions)
+            } else {
+                normalized_strategy(node.strategy(), num_actions)
+            };
+            fma_slices_uninit(result, &strategy, &cfv_actions);
+        } else if locking.is_empty() {
             // compute element-wise maximum (take the best response)
             max_slices_uninit(result, &cfv_actions);
         } else {
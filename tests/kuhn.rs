@@ -1,6 +1,8 @@
 extern crate postflop_solver;
 use postflop_solver::*;
 use std::mem::MaybeUninit;
+use std::ops::ControlFlow;
+use std::time::Duration;
 
 struct KuhnGame {
     root: MutexLike<KuhnNode>,
@@ -14,6 +16,7 @@ struct KuhnNode {
     children: Vec<(Action, MutexLike<KuhnNode>)>,
     strategy: Vec<f32>,
     storage: Vec<f32>,
+    raw_regrets: Vec<f32>,
 }
 
 #[derive(Clone, Copy, PartialEq, Eq)]
@@ -109,6 +112,11 @@ impl Game for KuhnGame {
     fn set_solved(&mut self) {
         self.is_solved = true;
     }
+
+    #[inline]
+    fn allocate_raw_regrets(&mut self) -> bool {
+        true
+    }
 }
 
 impl KuhnGame {
@@ -128,6 +136,7 @@ impl KuhnGame {
             children: Vec::new(),
             strategy: Default::default(),
             storage: Default::default(),
+            raw_regrets: Default::default(),
         };
         Self::build_tree_recursive(&mut root, Action::None);
         Self::allocate_memory_recursive(&mut root);
@@ -160,6 +169,7 @@ impl KuhnGame {
                     children: Vec::new(),
                     strategy: Default::default(),
                     storage: Default::default(),
+                    raw_regrets: Default::default(),
                 }),
             ));
         }
@@ -177,6 +187,7 @@ impl KuhnGame {
         let num_actions = node.num_actions();
         node.strategy = vec![0.0; num_actions * NUM_PRIVATE_HANDS];
         node.storage = vec![0.0; num_actions * NUM_PRIVATE_HANDS];
+        node.raw_regrets = vec![0.0; num_actions * NUM_PRIVATE_HANDS];
 
         for action in node.action_indices() {
             Self::allocate_memory_recursive(&mut node.play(action));
@@ -230,6 +241,16 @@ impl GameNode for KuhnNode {
         &mut self.storage
     }
 
+    #[inline]
+    fn raw_regrets(&self) -> &[f32] {
+        &self.raw_regrets
+    }
+
+    #[inline]
+    fn raw_regrets_mut(&mut self) -> &mut [f32] {
+        &mut self.raw_regrets
+    }
+
     #[inline]
     fn cfvalues(&self) -> &[f32] {
         &self.storage
@@ -241,12 +262,7 @@ impl GameNode for KuhnNode {
     }
 }
 
-#[test]
-fn kuhn() {
-    let target = 1e-4;
-    let mut game = KuhnGame::new();
-    solve(&mut game, 10000, target, false);
-
+fn root_ev(game: &KuhnGame) -> f32 {
     let root = game.root();
 
     let mut strategy = root.strategy().to_vec();
@@ -257,12 +273,91 @@ fn kuhn() {
         strategy[j] /= sum;
     }
 
-    let root_ev = root
-        .cfvalues()
+    root.cfvalues()
         .iter()
         .zip(strategy.iter())
-        .fold(0.0, |acc, (&cfv, &strategy)| acc + cfv * strategy);
+        .fold(0.0, |acc, (&cfv, &strategy)| acc + cfv * strategy)
+}
+
+#[test]
+fn kuhn() {
+    let target = 1e-4;
+    let mut game = KuhnGame::new();
+    solve(&mut game, 10000, target, false);
 
     let expected_ev = -1.0 / 18.0;
-    assert!((root_ev - expected_ev).abs() < 2.0 * target);
+    assert!((root_ev(&game) - expected_ev).abs() < 2.0 * target);
+}
+
+#[test]
+fn kuhn_algorithms() {
+    let target = 1e-4;
+    let algorithms = [
+        CfrAlgorithm::DcfrCustom {
+            alpha: 1.5,
+            beta: 0.0,
+            gamma: 2.0,
+        },
+        CfrAlgorithm::CfrPlus,
+        CfrAlgorithm::LinearCfr,
+        CfrAlgorithm::PcfrPlus,
+    ];
+
+    for algorithm in algorithms {
+        let mut game = KuhnGame::new();
+        let options = SolveOptions {
+            max_num_iterations: 10000,
+            target_exploitability: target,
+            algorithm,
+            ..Default::default()
+        };
+        let report = solve_with_options(&mut game, &options, |_, _| ControlFlow::Continue(()));
+        assert_eq!(report.stop_reason, StopReason::TargetExploitability);
+
+        let expected_ev = -1.0 / 18.0;
+        assert!((root_ev(&game) - expected_ev).abs() < 2.0 * target);
+    }
+}
+
+#[test]
+fn kuhn_cancel() {
+    let mut game = KuhnGame::new();
+    let mut iterations = Vec::new();
+    let exploitability = solve_with_progress(&mut game, 10000, 0.0, |game, progress| {
+        assert!(!game.is_solved());
+        iterations.push(progress.current_iteration);
+        if progress.current_iteration == 25 {
+            ControlFlow::Break(())
+        } else {
+            ControlFlow::Continue(())
+        }
+    });
+
+    assert_eq!(iterations, (0..=25).collect::<Vec<_>>());
+    assert!(game.is_solved());
+    assert!((exploitability - compute_exploitability(&game)).abs() < 1e-6);
+}
+
+#[test]
+fn kuhn_options() {
+    let mut game = KuhnGame::new();
+    let options = SolveOptions {
+        max_num_iterations: 100,
+        max_exploitability_overhead: Some(0.0),
+        ..Default::default()
+    };
+    let report = solve_with_options(&mut game, &options, |_, _| ControlFlow::Continue(()));
+    assert_eq!(report.num_iterations, 100);
+    assert_eq!(report.stop_reason, StopReason::MaxIterations);
+    assert!((report.exploitability - compute_exploitability(&game)).abs() < 1e-6);
+
+    let mut game = KuhnGame::new();
+    let options = SolveOptions {
+        max_duration: Some(Duration::ZERO),
+        ..Default::default()
+    };
+    let report = solve_with_options(&mut game, &options, |_, _| ControlFlow::Continue(()));
+    assert_eq!(report.num_iterations, 0);
+    assert_eq!(report.stop_reason, StopReason::TimeLimit);
+    assert!(game.is_solved());
 }